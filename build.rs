@@ -0,0 +1,13 @@
+fn main() {
+    // the gRPC service, from proto/pom.proto; protox parses it, so there
+    // is no protoc to install
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto/pom.proto");
+        let files = protox::compile(["proto/pom.proto"], ["proto"]).expect("proto/pom.proto");
+        tonic_prost_build::configure()
+            .build_client(false)
+            .compile_fds(files)
+            .expect("generating the gRPC service");
+    }
+}
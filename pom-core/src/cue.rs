@@ -0,0 +1,99 @@
+use serde::Deserialize;
+use std::{fmt, str::FromStr};
+
+/// A point in each work session worth a nudge, written `50%` or `5m left`.
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(try_from = "String")]
+pub enum Cue {
+    /// this far into the session, in percent
+    Through(u32),
+    /// this many seconds before the end
+    Left(i64),
+}
+
+impl Cue {
+    /// Seconds into a session of `planned` seconds that the cue falls on;
+    /// zero or less when it doesn't fit in.
+    pub fn at(self, planned: i64) -> i64 {
+        match self {
+            Cue::Through(percent) => planned * i64::from(percent.min(100)) / 100,
+            Cue::Left(secs) => planned - secs,
+        }
+    }
+}
+
+impl FromStr for Cue {
+    type Err = String;
+
+    /// `50%`, `5m left`, `90s left`.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("`{}` is not a cue, try `50%` or `5m left`", text);
+        if let Some(percent) = text.trim().strip_suffix('%') {
+            return percent
+                .trim()
+                .parse()
+                .map(Cue::Through)
+                .map_err(|_| invalid());
+        }
+
+        let left = text.trim().strip_suffix("left").ok_or_else(invalid)?.trim();
+        let (number, unit) = match (left.strip_suffix('m'), left.strip_suffix('s')) {
+            (Some(minutes), _) => (minutes, 60),
+            (_, Some(secs)) => (secs, 1),
+            _ => return Err(invalid()),
+        };
+        let number: u32 = number.trim().parse().map_err(|_| invalid())?;
+        Ok(Cue::Left(i64::from(number) * unit))
+    }
+}
+
+impl TryFrom<String> for Cue {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl fmt::Display for Cue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Cue::Through(percent) => write!(f, "{}% through", percent),
+            Cue::Left(secs) if secs % 60 == 0 => write!(f, "{}m left", secs / 60),
+            Cue::Left(secs) => write!(f, "{}s left", secs),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_cues() {
+        assert_eq!("50%".parse(), Ok(Cue::Through(50)));
+        assert_eq!(" 75 % ".parse(), Ok(Cue::Through(75)));
+        assert_eq!("5m left".parse(), Ok(Cue::Left(300)));
+        assert_eq!("90s left".parse(), Ok(Cue::Left(90)));
+        assert_eq!("2 m left".parse(), Ok(Cue::Left(120)));
+        for text in ["", "50", "-5%", "5m", "5h left", "-5m left", "m left", "99999999999m left"] {
+            assert!(text.parse::<Cue>().is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn reads_back_what_it_writes() {
+        for cue in [Cue::Left(300), Cue::Left(90)] {
+            assert_eq!(cue.to_string().parse(), Ok(cue));
+        }
+        assert_eq!(Cue::Through(50).to_string(), "50% through");
+    }
+
+    #[test]
+    fn falls_within_the_session() {
+        assert_eq!(Cue::Through(50).at(1500), 750);
+        assert_eq!(Cue::Through(150).at(1500), 1500);
+        assert_eq!(Cue::Left(300).at(1500), 1200);
+        assert!(Cue::Left(1800).at(1500) <= 0);
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+/// The font [`Font::standard`] falls back on: block digits, 5 rows high.
+const BLOCKS: &str = include_str!("../fonts/blocks.flf");
+
+/// A FIGlet font (`.flf`), for drawing the time in big letters.
+///
+/// Only the printable ASCII characters are read, laid side by side at
+/// full width; kerning and smushing rules are ignored.
+#[derive(Default)]
+pub struct Font {
+    height: usize,
+    glyphs: HashMap<char, Vec<String>>,
+    /// for text this font has no glyphs for
+    fallback: Option<Box<Font>>,
+}
+
+impl Font {
+    /// The bundled block digits.
+    pub fn standard() -> Self {
+        Font::parse(BLOCKS).unwrap_or_default()
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines();
+        let header = lines.next().unwrap_or_default();
+        let Some(settings) = header.strip_prefix("flf2a") else {
+            return Err("not a FIGlet font, the first line should start with flf2a".to_string());
+        };
+        let mut settings = settings.chars();
+        let hardblank = settings.next().unwrap_or('$');
+        // height, baseline, max length, old layout (signed), comment lines
+        let numbers: Vec<i64> = settings
+            .as_str()
+            .split_whitespace()
+            .map_while(|number| number.parse().ok())
+            .collect();
+        let (height, comments) = match numbers[..] {
+            [height, _, _, _, comments, ..] if height > 0 && comments >= 0 => {
+                (height as usize, comments as usize)
+            }
+            _ => return Err("the header is missing the height or comment count".to_string()),
+        };
+
+        let mut lines = lines.skip(comments);
+        let mut glyphs = HashMap::new();
+        for code in ' '..='~' {
+            let rows: Vec<String> = lines
+                .by_ref()
+                .take(height)
+                .map(|line| glyph_row(line, hardblank))
+                .collect();
+            // a font that stops early just lacks the rest
+            if rows.len() < height {
+                break;
+            }
+            if rows.iter().any(|row| !row.is_empty()) {
+                glyphs.insert(code, rows);
+            }
+        }
+        Ok(Font {
+            height,
+            glyphs,
+            fallback: None,
+        })
+    }
+
+    /// Use `fallback` for anything this font can't draw.
+    pub fn or(mut self, fallback: Font) -> Self {
+        self.fallback = Some(Box::new(fallback));
+        self
+    }
+
+    /// `text` as rows of equal width, or `None` when neither this font nor
+    /// its fallback has all the characters.
+    pub fn render(&self, text: &str) -> Option<Vec<String>> {
+        if !text.chars().all(|c| self.glyphs.contains_key(&c)) {
+            return self.fallback.as_ref()?.render(text);
+        }
+
+        let mut rows = vec![String::new(); self.height];
+        for c in text.chars() {
+            let glyph = self.glyphs.get(&c)?;
+            let width = glyph
+                .iter()
+                .map(|row| row.chars().count())
+                .max()
+                .unwrap_or(0);
+            for (row, line) in rows.iter_mut().zip(glyph) {
+                row.push_str(&format!("{:<width$}", line));
+            }
+        }
+        Some(rows)
+    }
+}
+
+/// One row of a glyph, without its end marks and with hard blanks as spaces.
+fn glyph_row(line: &str, hardblank: char) -> String {
+    let line = line.trim_end();
+    let row = match line.chars().last() {
+        Some(mark) => line.trim_end_matches(mark),
+        None => line,
+    };
+    row.replace(hardblank, " ")
+}
@@ -0,0 +1,40 @@
+use serde::Deserialize;
+
+use crate::Phase;
+
+/// Which glyphs mark the phase and the round count.
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Icons {
+    /// plain ASCII, safe anywhere
+    #[default]
+    Ascii,
+    /// 🍅 and friends, for terminals with a color emoji font
+    Emoji,
+    /// Nerd Font symbols, which need a patched font
+    Nerd,
+}
+
+impl Icons {
+    /// Put in front of the phase name, empty for ASCII.
+    pub fn phase(self, phase: Phase) -> &'static str {
+        match (self, phase) {
+            (Icons::Ascii, _) => "",
+            (Icons::Emoji, Phase::Work) => "\u{1f345} ",
+            (Icons::Emoji, Phase::ShortBreak) => "\u{2615} ",
+            (Icons::Emoji, Phase::LongBreak) => "\u{1f334} ",
+            (Icons::Nerd, Phase::Work) => "\u{f0b1} ",
+            (Icons::Nerd, Phase::ShortBreak) => "\u{f0f4} ",
+            (Icons::Nerd, Phase::LongBreak) => "\u{f236} ",
+        }
+    }
+
+    /// A finished and an unfinished round, and the columns each takes.
+    pub fn rounds(self) -> (&'static str, &'static str, u16) {
+        match self {
+            Icons::Ascii => ("*", "-", 1),
+            Icons::Emoji => ("\u{1f345}", "\u{26aa}", 2),
+            Icons::Nerd => ("\u{f111}", "\u{f10c}", 1),
+        }
+    }
+}
@@ -0,0 +1,27 @@
+//! The pomodoro state machine used by pom: phases, the schedule that
+//! strings them together, and records of finished sessions.
+//!
+//! Nothing here touches a terminal, clock or thread; callers pass in the
+//! current time and feed [`Command`]s, then render whatever they like from
+//! the [`Timer`] and forward its [`Event`]s. With the `tui` feature,
+//! [`PomodoroWidget`] draws a timer the way pom itself does.
+
+mod cue;
+mod figlet;
+mod icons;
+mod phase;
+mod schedule;
+mod session;
+mod timer;
+#[cfg(feature = "tui")]
+mod widget;
+
+pub use cue::Cue;
+pub use figlet::Font;
+pub use icons::Icons;
+pub use phase::Phase;
+pub use schedule::Schedule;
+pub use session::Session;
+pub use timer::{Command, Event, Mode, Snapshot, Timer, MAX_ADD, UNDO_SECS};
+#[cfg(feature = "tui")]
+pub use widget::PomodoroWidget;
@@ -0,0 +1,50 @@
+use serde::Deserialize;
+
+use crate::Phase;
+
+/// How long breaks are, and which phase follows which.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Schedule {
+    /// minutes
+    pub short_break: i64,
+    /// minutes
+    pub long_break: i64,
+    /// work sessions before a long break; the classics say four
+    pub long_break_every: u32,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Schedule {
+            short_break: 5,
+            long_break: 15,
+            long_break_every: 4,
+        }
+    }
+}
+
+impl Schedule {
+    /// Seconds in `phase`; work sessions last whatever was picked.
+    pub fn length(&self, phase: Phase, work: i64) -> i64 {
+        match phase {
+            Phase::Work => work,
+            Phase::ShortBreak => self.short_break * 60,
+            Phase::LongBreak => self.long_break * 60,
+        }
+    }
+
+    /// Work sessions from one long break to the next, at least one.
+    pub fn cycle(&self) -> u32 {
+        self.long_break_every.max(1)
+    }
+
+    /// The phase after `phase`, with `round` work sessions completed so far.
+    pub fn after(&self, phase: Phase, completed: bool, round: u32) -> Phase {
+        match phase {
+            Phase::Work if completed && round.is_multiple_of(self.cycle()) => Phase::LongBreak,
+            Phase::Work => Phase::ShortBreak,
+            Phase::ShortBreak | Phase::LongBreak => Phase::Work,
+        }
+    }
+}
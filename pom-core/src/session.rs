@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::Phase;
+
+/// One phase as it actually went.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub phase: Phase,
+    pub started: DateTime<Utc>,
+    pub ended: DateTime<Utc>,
+    /// seconds
+    pub planned: i64,
+    /// seconds actually run, pauses excluded
+    pub elapsed: i64,
+    /// ran out, rather than skipped or reset
+    pub completed: bool,
+    /// how often it was paused, by the user or automatically
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub pauses: u32,
+    /// what the user was working on, as given with `--tag`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// what came of it, in the user's words
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// the app it was imported from, e.g. `toggl`; none when pom timed it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// the config profile it ran under, e.g. `deep-work`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+}
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
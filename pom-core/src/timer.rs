@@ -0,0 +1,484 @@
+use chrono::{DateTime, Duration, Utc};
+use std::{fmt, str::FromStr};
+
+use crate::{Cue, Phase, Schedule, Session};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Mode {
+    /// picking how long the next work session is
+    Input,
+    Running,
+}
+
+/// Something that can be done to the timer, from a key or from outside
+/// (D-Bus and friends).
+#[derive(Clone, Copy, Debug)]
+pub enum Command {
+    /// start a work session, or resume a paused one
+    Start,
+    Pause,
+    /// pause when running, resume when paused
+    Toggle,
+    /// end the current phase early without counting it
+    Skip,
+    /// lengthen (or, negative, shorten) the current phase by seconds
+    Add(i64),
+    Reset,
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    /// The text form used by the command pipe: `pause`, `skip`, `add 300`...
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut words = line.split_whitespace();
+        let command = match words.next() {
+            Some("start") => Command::Start,
+            Some("pause") => Command::Pause,
+            Some("toggle") => Command::Toggle,
+            Some("skip") => Command::Skip,
+            Some("reset") => Command::Reset,
+            Some("add") => match words.next().map(str::parse::<i64>) {
+                Some(Ok(secs)) if secs.abs() <= MAX_ADD => Command::Add(secs),
+                Some(Ok(_)) => return Err(format!("add takes at most ±{} seconds", MAX_ADD)),
+                _ => return Err("usage: add <seconds>".to_string()),
+            },
+            Some(other) => return Err(format!("unknown command `{}`", other)),
+            None => return Err("empty command".to_string()),
+        };
+
+        match words.next() {
+            Some(extra) => Err(format!("unexpected `{}` after command", extra)),
+            None => Ok(command),
+        }
+    }
+}
+
+impl fmt::Display for Command {
+    /// The same text form, for sending a command on.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Command::Start => write!(f, "start"),
+            Command::Pause => write!(f, "pause"),
+            Command::Toggle => write!(f, "toggle"),
+            Command::Skip => write!(f, "skip"),
+            Command::Add(secs) => write!(f, "add {}", secs),
+            Command::Reset => write!(f, "reset"),
+        }
+    }
+}
+
+/// A phase transition, for integrations that mirror the timer elsewhere.
+#[derive(Clone, Debug)]
+pub enum Event {
+    PhaseStarted {
+        phase: Phase,
+        /// seconds
+        planned: i64,
+        at: DateTime<Utc>,
+    },
+    PhaseEnded {
+        phase: Phase,
+        /// ran out, rather than skipped or reset
+        completed: bool,
+        at: DateTime<Utc>,
+    },
+    Paused {
+        at: DateTime<Utc>,
+    },
+    Resumed {
+        at: DateTime<Utc>,
+    },
+    /// a work session reached one of `cues`
+    Cue {
+        cue: Cue,
+        at: DateTime<Utc>,
+    },
+}
+
+/// How long a skip or reset can be taken back, in seconds.
+pub const UNDO_SECS: i64 = 10;
+
+/// The most one `add` lengthens or shortens a phase by, in seconds: a day.
+pub const MAX_ADD: i64 = 24 * 60 * 60;
+
+/// The timer as it was before a skip or reset, kept briefly in case it was
+/// a slip.
+#[derive(Clone)]
+struct Undo {
+    at: DateTime<Utc>,
+    phase: Phase,
+    planned: i64,
+    /// run time up to the slip
+    run: Duration,
+    paused: bool,
+    /// set up after a phase ran out, and not started yet
+    waiting: bool,
+    started_at: DateTime<Utc>,
+    pauses: u32,
+}
+
+/// What outside observers get to see of the timer.
+#[derive(Clone)]
+pub struct Snapshot {
+    /// `idle`, `running` or `paused`
+    pub state: &'static str,
+    pub phase: Phase,
+    pub remaining: i64,
+    pub round: u32,
+}
+
+/// Work sessions and breaks, one after the other.
+///
+/// Time only moves when the caller says so: every method that can change
+/// state takes `now`, which keeps the timer deterministic and testable.
+pub struct Timer {
+    /// work session length picked in input mode, in seconds
+    pub time: i64,
+    /// length of the current phase, in seconds
+    pub planned: i64,
+    pub elapsed: i64,
+    /// time run before the last pause
+    banked: Duration,
+    /// when the clock was last (re)started, `None` while paused
+    since: Option<DateTime<Utc>>,
+    /// when the current phase began
+    started_at: DateTime<Utc>,
+    /// pauses in the current phase
+    pauses: u32,
+    /// the phase is set up but not started, see `hold`
+    waiting: bool,
+    pub mode: Mode,
+    pub phase: Phase,
+    /// work sessions finished so far
+    pub round: u32,
+    /// every phase that ended, oldest first
+    pub sessions: Vec<Session>,
+    /// given to every session that ends from now on
+    pub tags: Vec<String>,
+    /// the profile in use, recorded the same way
+    pub profile: Option<String>,
+    /// points in work sessions that raise `Event::Cue`
+    pub cues: Vec<Cue>,
+    /// the phase after one that ran out is set up but waits for a start
+    /// or resume, looking paused; it begins, and is seen to, only then
+    pub hold: bool,
+    /// transitions not yet taken by `take_events`
+    events: Vec<Event>,
+    /// the last skip or reset, until something else happens
+    undo: Option<Undo>,
+}
+
+impl Default for Timer {
+    fn default() -> Self {
+        Timer {
+            time: 0,
+            planned: 0,
+            elapsed: 0,
+            banked: Duration::zero(),
+            since: None,
+            started_at: DateTime::<Utc>::MIN_UTC,
+            pauses: 0,
+            waiting: false,
+            mode: Mode::Input,
+            phase: Phase::Work,
+            round: 0,
+            sessions: Vec::new(),
+            tags: Vec::new(),
+            profile: None,
+            cues: Vec::new(),
+            hold: false,
+            events: Vec::new(),
+            undo: None,
+        }
+    }
+}
+
+impl Timer {
+    pub fn apply(&mut self, command: Command, schedule: &Schedule, now: DateTime<Utc>) {
+        // only the very last thing done can be undone
+        self.undo = None;
+        if self.mode == Mode::Running && matches!(command, Command::Skip | Command::Reset) {
+            self.undo = Some(Undo {
+                at: now,
+                phase: self.phase,
+                planned: self.planned,
+                run: self.run_time(now),
+                paused: self.is_paused(),
+                waiting: self.waiting,
+                started_at: self.started_at,
+                pauses: self.pauses,
+            });
+        }
+
+        match (self.mode, command) {
+            (Mode::Input, Command::Start | Command::Toggle) => self.start(now),
+            (Mode::Input, _) => {}
+            (Mode::Running, Command::Start) => self.resume(now),
+            (Mode::Running, Command::Pause) => self.pause(now),
+            (Mode::Running, Command::Toggle) if self.is_paused() => self.resume(now),
+            (Mode::Running, Command::Toggle) => self.pause(now),
+            (Mode::Running, Command::Skip) => self.next_phase(false, schedule, now),
+            (Mode::Running, Command::Add(secs)) => {
+                // never shorten past what already ran
+                let secs = secs.clamp(-MAX_ADD, MAX_ADD);
+                self.planned = self.planned.saturating_add(secs).max(self.elapsed);
+            }
+            (Mode::Running, Command::Reset) => self.reset(now),
+        }
+    }
+
+    /// Take back a skip or reset from the last `UNDO_SECS`, picking the
+    /// phase up where it was left. The session the slip recorded is
+    /// withdrawn and returned, so it can go from the history too.
+    pub fn undo(&mut self, now: DateTime<Utc>) -> Option<Session> {
+        let undo = self
+            .undo
+            .take()
+            .filter(|undo| now - undo.at <= Duration::seconds(UNDO_SECS))?;
+        if self.mode == Mode::Running && !self.waiting {
+            // the phase the skip started never really happened
+            self.events.push(Event::PhaseEnded {
+                phase: self.phase,
+                completed: false,
+                at: now,
+            });
+        }
+
+        self.mode = Mode::Running;
+        self.phase = undo.phase;
+        self.planned = undo.planned;
+        self.banked = undo.run;
+        self.elapsed = undo.run.num_seconds().max(0);
+        self.since = (!undo.paused).then_some(now);
+        self.started_at = undo.started_at;
+        self.pauses = undo.pauses;
+        self.waiting = undo.waiting;
+        if undo.waiting {
+            // it was never started, so recorded nothing
+            return None;
+        }
+        self.started(now);
+        if undo.paused {
+            self.events.push(Event::Paused { at: now });
+        }
+        self.sessions.pop()
+    }
+
+    fn start(&mut self, now: DateTime<Utc>) {
+        self.mode = Mode::Running;
+        self.phase = Phase::Work;
+        self.planned = self.time;
+        self.restart_clock(now);
+        self.started(now);
+    }
+
+    fn pause(&mut self, now: DateTime<Utc>) {
+        if let Some(since) = self.since.take() {
+            self.banked += (now - since).max(Duration::zero());
+            self.pauses += 1;
+            self.events.push(Event::Paused { at: now });
+        }
+    }
+
+    fn resume(&mut self, now: DateTime<Utc>) {
+        if self.since.is_none() {
+            self.since = Some(now);
+            if std::mem::take(&mut self.waiting) {
+                // a phase that waited begins now
+                self.started_at = now;
+                self.started(now);
+            } else {
+                self.events.push(Event::Resumed { at: now });
+            }
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.mode == Mode::Running && self.since.is_none()
+    }
+
+    /// A work session is under way and the clock is running.
+    pub fn is_working(&self) -> bool {
+        self.mode == Mode::Running && self.phase == Phase::Work && !self.is_paused()
+    }
+
+    /// The phase is set up after one that ran out, waiting to be started.
+    pub fn is_waiting(&self) -> bool {
+        self.mode == Mode::Running && self.waiting
+    }
+
+    /// Abandon the current phase and go back to picking a duration.
+    fn reset(&mut self, now: DateTime<Utc>) {
+        self.ended(false, now);
+        self.mode = Mode::Input;
+        self.phase = Phase::Work;
+        self.elapsed = 0;
+        self.banked = Duration::zero();
+        self.since = None;
+        self.waiting = false;
+    }
+
+    fn restart_clock(&mut self, now: DateTime<Utc>) {
+        self.waiting = false;
+        self.elapsed = 0;
+        self.banked = Duration::zero();
+        self.since = Some(now);
+        self.started_at = now;
+        self.pauses = 0;
+    }
+
+    /// Bring `elapsed` up to date, raising any cues passed on the way; true
+    /// when the current phase just ran out.
+    pub fn update(&mut self, now: DateTime<Utc>) -> bool {
+        // a phase waiting to start can't run out, even one of no length
+        if self.mode != Mode::Running || self.waiting {
+            return false;
+        }
+
+        // a clock jumping backwards must not make time run in reverse
+        let before = self.elapsed;
+        self.elapsed = self.run_time(now).num_seconds().max(0);
+        if self.phase == Phase::Work {
+            for &cue in &self.cues {
+                let at = cue.at(self.planned);
+                if before < at && at <= self.elapsed {
+                    self.events.push(Event::Cue { cue, at: now });
+                }
+            }
+        }
+        self.elapsed >= self.planned
+    }
+
+    fn run_time(&self, now: DateTime<Utc>) -> Duration {
+        let running = self.since.map_or(Duration::zero(), |since| now - since);
+        self.banked + running
+    }
+
+    /// The app is going away with a phase running: it ends, not completed,
+    /// for whatever mirrors the timer. It isn't recorded as a session.
+    pub fn quit(&mut self, now: DateTime<Utc>) {
+        if self.mode == Mode::Running {
+            if !self.waiting {
+                self.events.push(Event::PhaseEnded {
+                    phase: self.phase,
+                    completed: false,
+                    at: now,
+                });
+            }
+            self.mode = Mode::Input;
+            self.phase = Phase::Work;
+            self.elapsed = 0;
+            self.banked = Duration::zero();
+            self.since = None;
+            self.waiting = false;
+        }
+    }
+
+    /// The current phase ran out: record it and move on to the next one,
+    /// or with `hold` set it up to wait.
+    pub fn advance(&mut self, schedule: &Schedule, now: DateTime<Utc>) {
+        self.undo = None;
+        self.next_phase(true, schedule, now);
+    }
+
+    fn next_phase(&mut self, completed: bool, schedule: &Schedule, now: DateTime<Utc>) {
+        self.ended(completed, now);
+        if completed && self.phase == Phase::Work {
+            self.round += 1;
+        }
+
+        self.phase = schedule.after(self.phase, completed, self.round);
+        self.planned = schedule.length(self.phase, self.time);
+        self.restart_clock(now);
+        if completed && self.hold {
+            self.since = None;
+            self.waiting = true;
+        } else {
+            self.started(now);
+        }
+    }
+
+    fn started(&mut self, now: DateTime<Utc>) {
+        self.events.push(Event::PhaseStarted {
+            phase: self.phase,
+            planned: self.planned,
+            at: now,
+        });
+    }
+
+    fn ended(&mut self, completed: bool, now: DateTime<Utc>) {
+        if self.waiting {
+            // a phase that never started, skipped or reset as it waited
+            return;
+        }
+        self.sessions.push(Session {
+            phase: self.phase,
+            started: self.started_at,
+            // the clock may have been set back since
+            ended: now.max(self.started_at),
+            planned: self.planned,
+            elapsed: self
+                .run_time(now)
+                .num_seconds()
+                .clamp(0, self.planned.max(0)),
+            completed,
+            pauses: self.pauses,
+            tags: self.tags.clone(),
+            note: None,
+            source: None,
+            profile: self.profile.clone(),
+        });
+        self.events.push(Event::PhaseEnded {
+            phase: self.phase,
+            completed,
+            at: now,
+        });
+    }
+
+    /// Transitions since the last call, oldest first.
+    pub fn take_events(&mut self) -> Vec<Event> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// How far into the current phase, 0.0..=1.0, to the millisecond.
+    pub fn progress(&self, now: DateTime<Utc>) -> f64 {
+        if self.mode != Mode::Running || self.planned <= 0 {
+            return 0.0;
+        }
+        let ms = self.run_time(now).num_milliseconds().max(0);
+        (ms as f64 / self.planned.saturating_mul(1000) as f64).min(1.0)
+    }
+
+    /// Milliseconds left, for displays finer than `remaining`.
+    pub fn remaining_ms(&self, now: DateTime<Utc>) -> i64 {
+        match self.mode {
+            Mode::Input => self.time.saturating_mul(1000),
+            Mode::Running => self
+                .planned
+                .saturating_mul(1000)
+                .saturating_sub(self.run_time(now).num_milliseconds())
+                .max(0),
+        }
+    }
+
+    pub fn remaining(&self) -> i64 {
+        match self.mode {
+            Mode::Input => self.time,
+            Mode::Running => self.planned - self.elapsed,
+        }
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            state: match self.mode {
+                Mode::Input => "idle",
+                Mode::Running if self.is_paused() => "paused",
+                Mode::Running => "running",
+            },
+            phase: self.phase,
+            remaining: self.remaining(),
+            round: self.round,
+        }
+    }
+}
@@ -0,0 +1,310 @@
+use chrono::{DateTime, Utc};
+use tui::{
+    buffer::Buffer,
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans, Text},
+    widgets::{Block, Borders, Gauge, Paragraph, StatefulWidget, Widget},
+};
+
+use crate::{Font, Icons, Mode, Phase, Timer};
+
+/// The timer as pom draws it: a bordered box titled with the phase,
+/// holding the seconds left or a progress gauge.
+///
+/// ```ignore
+/// f.render_stateful_widget(PomodoroWidget::new(Utc::now()).gauge(true), area, &mut timer);
+/// ```
+pub struct PomodoroWidget<'a> {
+    block: Block<'a>,
+    now: DateTime<Utc>,
+    gauge: bool,
+    ringing: bool,
+    cycle: u32,
+    tenths: bool,
+    still: bool,
+    tomato: bool,
+    font: Option<&'a Font>,
+    icons: Icons,
+    picture: bool,
+}
+
+impl<'a> PomodoroWidget<'a> {
+    /// `now` places the gauge between seconds.
+    pub fn new(now: DateTime<Utc>) -> Self {
+        PomodoroWidget {
+            block: Block::default().borders(Borders::ALL),
+            now,
+            gauge: false,
+            ringing: false,
+            cycle: 0,
+            tenths: false,
+            still: false,
+            tomato: false,
+            font: None,
+            icons: Icons::default(),
+            picture: false,
+        }
+    }
+
+    /// Draw inside this block instead; the phase is still added as a title.
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = block;
+        self
+    }
+
+    /// A filling progress bar instead of digits.
+    pub fn gauge(mut self, gauge: bool) -> Self {
+        self.gauge = gauge;
+        self
+    }
+
+    /// Show the round as dots on the bottom border, one per work session
+    /// until the long break; see [`Schedule::cycle`](crate::Schedule::cycle).
+    pub fn cycle(mut self, cycle: u32) -> Self {
+        self.cycle = cycle;
+        self
+    }
+
+    /// A tomato that fills up from the bottom as the phase goes by,
+    /// instead of digits or a gauge; digits again when it doesn't fit.
+    pub fn tomato(mut self, tomato: bool) -> Self {
+        self.tomato = tomato;
+        self
+    }
+
+    /// Glyphs for the phase and the rounds; ASCII unless told otherwise.
+    pub fn icons(mut self, icons: Icons) -> Self {
+        self.icons = icons;
+        self
+    }
+
+    /// Draw the digits in big letters from `font`, as far as they fit.
+    pub fn font(mut self, font: Option<&'a Font>) -> Self {
+        self.font = font;
+        self
+    }
+
+    /// With the tomato, leave room for the caller to put a real image in
+    /// and only draw the time, below it; see [`picture_area`](Self::picture_area).
+    pub fn picture(mut self, picture: bool) -> Self {
+        self.picture = picture;
+        self
+    }
+
+    /// Where the image goes when the widget is drawn in `area`, if there
+    /// is one to draw.
+    pub fn picture_area(&self, area: Rect) -> Option<Rect> {
+        let inner = self.block.inner(area);
+        (self.tomato && self.picture && inner.height > 2)
+            .then(|| Rect::new(inner.x, inner.y, inner.width, inner.height - 1))
+    }
+
+    /// Count the last ten seconds in tenths, `9.4`; the caller has to
+    /// redraw at least ten times a second then.
+    pub fn tenths(mut self, tenths: bool) -> Self {
+        self.tenths = tenths;
+        self
+    }
+
+    /// Nothing that moves on its own: no blinking, and a gauge that steps
+    /// once a second instead of filling smoothly.
+    pub fn still(mut self, still: bool) -> Self {
+        self.still = still;
+        self
+    }
+
+    /// Flag an alarm that is still waiting to be acknowledged.
+    pub fn ringing(mut self, ringing: bool) -> Self {
+        self.ringing = ringing;
+        self
+    }
+}
+
+impl StatefulWidget for PomodoroWidget<'_> {
+    type State = Timer;
+
+    fn render(self, area: Rect, buf: &mut Buffer, timer: &mut Timer) {
+        let time_left = timer.remaining();
+        let ms = timer.remaining_ms(self.now);
+        let label = if self.tenths && timer.mode == Mode::Running && ms < 10_000 {
+            format!("{}.{}", ms / 1000, ms % 1000 / 100)
+        } else {
+            time_left.to_string()
+        };
+        let cycle = self.cycle;
+        let picture = self.picture_area(area);
+
+        let mut block = self.block;
+        if timer.mode == Mode::Running {
+            let paused = if timer.is_paused() { " (paused)" } else { "" };
+            let icon = self.icons.phase(timer.phase);
+            block = block.title(format!(
+                " {}{}{} ",
+                icon,
+                capitalize(timer.phase.name()),
+                paused
+            ));
+        }
+        if self.ringing {
+            block = block.title(Span::styled(
+                " Time's up! ",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ));
+        }
+
+        let ratio = if self.still && timer.planned > 0 {
+            (timer.elapsed as f64 / timer.planned as f64).clamp(0.0, 1.0)
+        } else {
+            timer.progress(self.now)
+        };
+        let inner = block.inner(area);
+        if let Some(picture) = picture {
+            block.render(area, buf);
+            Paragraph::new(label)
+                .alignment(Alignment::Center)
+                .render(Rect::new(inner.x, picture.bottom(), inner.width, 1), buf);
+            dots(area, buf, timer, cycle, self.icons);
+            return;
+        }
+        if self.tomato && inner.width >= TOMATO_WIDTH && inner.height >= TOMATO.len() as u16 {
+            block.render(area, buf);
+            tomato(inner, buf, ratio, &label);
+            dots(area, buf, timer, cycle, self.icons);
+            return;
+        }
+
+        if self.gauge {
+            Gauge::default()
+                .block(block)
+                .gauge_style(Style::default().fg(Color::Green))
+                .ratio(ratio)
+                .label(label)
+                .render(area, buf);
+            dots(area, buf, timer, cycle, self.icons);
+            return;
+        }
+
+        // big letters, when there is a font and room for them
+        let big = self
+            .font
+            .and_then(|font| font.render(&label))
+            .filter(|rows| {
+                rows.len() <= inner.height as usize
+                    && rows
+                        .iter()
+                        .all(|row| row.chars().count() <= inner.width as usize)
+            });
+        let mut text = match big {
+            Some(rows) => {
+                let mut lines = vec![Spans::default(); (inner.height as usize - rows.len()) / 2];
+                lines.extend(rows.into_iter().map(Spans::from));
+                Text::from(lines)
+            }
+            None => Text::raw(label),
+        };
+        if timer.mode == Mode::Running {
+            let color = if time_left == 0 {
+                Color::Red
+            } else {
+                Color::Green
+            };
+            text.patch_style(Style::default().fg(color));
+            if !self.still {
+                text.patch_style(Style::default().add_modifier(Modifier::RAPID_BLINK));
+            }
+        }
+        Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .block(block)
+            .render(area, buf);
+        dots(area, buf, timer, cycle, self.icons);
+    }
+}
+
+const TOMATO: [&str; 10] = [
+    "      \\|/      ",
+    "   .-'\"|\"'-.   ",
+    " .'         '. ",
+    "/             \\",
+    "|             |",
+    "|             |",
+    "|             |",
+    "\\             /",
+    " '.         .' ",
+    "   '-.....-'   ",
+];
+const TOMATO_WIDTH: u16 = 15;
+/// rows with room inside for the fill
+const FILLABLE: std::ops::RangeInclusive<usize> = 2..=8;
+
+/// The tomato, centered in `area`, filled `ratio` of the way up, with the
+/// time across its middle.
+fn tomato(area: Rect, buf: &mut Buffer, ratio: f64, label: &str) {
+    let x = area.x + (area.width - TOMATO_WIDTH) / 2;
+    let y = area.y + (area.height - TOMATO.len() as u16) / 2;
+    let rows = FILLABLE.count();
+    let filled = (ratio * rows as f64).round() as usize;
+    let middle = TOMATO.len() / 2;
+
+    for (row, line) in TOMATO.iter().enumerate() {
+        let outline = if row == 0 { Color::Green } else { Color::Red };
+        let full = FILLABLE.contains(&row) && FILLABLE.end() - row < filled;
+        let (first, last) = (line.find(|c| c != ' '), line.rfind(|c| c != ' '));
+        for (col, c) in line.chars().enumerate() {
+            let inside =
+                first.is_some_and(|first| col > first) && last.is_some_and(|last| col < last);
+            let style = match (c, inside && full) {
+                (' ', true) => Style::default().bg(Color::Red),
+                (' ', false) => Style::default(),
+                _ => Style::default().fg(outline),
+            };
+            buf.set_string(x + col as u16, y + row as u16, c.to_string(), style);
+        }
+        if row == middle {
+            let width = label.len().min(TOMATO_WIDTH as usize - 2) as u16;
+            let x = x + (TOMATO_WIDTH - width) / 2;
+            let style = Style::default().add_modifier(Modifier::BOLD);
+            let style = if full {
+                style.fg(Color::White).bg(Color::Red)
+            } else {
+                style
+            };
+            buf.set_stringn(x, y + row as u16, label, width as usize, style);
+        }
+    }
+}
+
+/// `**--`: work sessions done towards the next long break, centered on
+/// the bottom border.
+fn dots(area: Rect, buf: &mut Buffer, timer: &Timer, cycle: u32, icons: Icons) {
+    if cycle == 0 || area.height < 2 {
+        return;
+    }
+    let mut done = timer.round % cycle;
+    // the long break itself is the cycle complete
+    if done == 0 && timer.round > 0 && timer.phase == Phase::LongBreak {
+        done = cycle;
+    }
+    let (finished, unfinished, columns) = icons.rounds();
+    let dots: String = (0..cycle)
+        .map(|i| if i < done { finished } else { unfinished })
+        .collect();
+    let width = (cycle as u16 * columns + 2).min(area.width);
+    let x = area.x + (area.width - width) / 2;
+    buf.set_stringn(
+        x,
+        area.bottom() - 1,
+        format!(" {} ", dots),
+        width as usize,
+        Style::default().fg(Color::Red),
+    );
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
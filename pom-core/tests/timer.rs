@@ -0,0 +1,240 @@
+//! The timer driven the way pom drives it, with random commands, waits
+//! and clock jumps in between, checking what must hold after every step.
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use pom_core::{Command, Event, Mode, Phase, Schedule, Timer, MAX_ADD};
+use proptest::prelude::*;
+
+#[derive(Clone, Debug)]
+enum Step {
+    /// picking a duration, only heard before the timer starts
+    Pick(i64),
+    Command(Command),
+    Undo,
+    /// phases after one that ran out held for a start, as without
+    /// auto_advance, or not
+    Hold(bool),
+    /// time passing, in seconds
+    Wait(i64),
+    /// the wall clock set forwards or back
+    Jump(i64),
+}
+
+fn command() -> impl Strategy<Value = Command> {
+    prop_oneof![
+        Just(Command::Start),
+        Just(Command::Pause),
+        Just(Command::Toggle),
+        Just(Command::Skip),
+        Just(Command::Reset),
+        (-1800i64..=1800).prop_map(Command::Add),
+        // whatever comes in over gRPC
+        any::<i64>().prop_map(Command::Add),
+    ]
+}
+
+fn step() -> impl Strategy<Value = Step> {
+    prop_oneof![
+        1 => (0i64..=5400).prop_map(Step::Pick),
+        4 => command().prop_map(Step::Command),
+        1 => Just(Step::Undo),
+        1 => any::<bool>().prop_map(Step::Hold),
+        4 => (0i64..=600).prop_map(Step::Wait),
+        1 => (-3600i64..=3600).prop_map(Step::Jump),
+    ]
+}
+
+fn schedule() -> impl Strategy<Value = Schedule> {
+    (0i64..=30, 0i64..=60, 0u32..=6).prop_map(|(short_break, long_break, long_break_every)| {
+        Schedule {
+            short_break,
+            long_break,
+            long_break_every,
+        }
+    })
+}
+
+fn epoch() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap()
+}
+
+/// One turn of the event loop: the step, then the clock looked at, and a
+/// phase that ran out moved on from.
+fn turn(timer: &mut Timer, schedule: &Schedule, now: &mut DateTime<Utc>, step: Step) {
+    match step {
+        Step::Pick(time) if timer.mode == Mode::Input => timer.time = time,
+        Step::Pick(_) => {}
+        Step::Command(command) => timer.apply(command, schedule, *now),
+        Step::Undo => {
+            timer.undo(*now);
+        }
+        Step::Hold(hold) => timer.hold = hold,
+        Step::Wait(secs) | Step::Jump(secs) => *now += Duration::seconds(secs),
+    }
+    if timer.update(*now) {
+        timer.advance(schedule, *now);
+    }
+}
+
+/// Everything that must hold between turns.
+fn check(timer: &Timer, schedule: &Schedule, now: DateTime<Utc>) -> Result<(), TestCaseError> {
+    prop_assert!(timer.elapsed >= 0, "elapsed {}", timer.elapsed);
+    prop_assert!(timer.planned >= 0, "planned {}", timer.planned);
+    match timer.mode {
+        Mode::Input => {
+            prop_assert_eq!(timer.phase, Phase::Work);
+            prop_assert_eq!(timer.elapsed, 0);
+            prop_assert!(!timer.is_paused());
+            prop_assert_eq!(timer.remaining(), timer.time);
+        }
+        Mode::Running => {
+            prop_assert!(
+                timer.elapsed <= timer.planned,
+                "elapsed {} past planned {}",
+                timer.elapsed,
+                timer.planned
+            );
+            prop_assert!(timer.remaining() >= 0);
+        }
+    }
+    let progress = timer.progress(now);
+    prop_assert!((0.0..=1.0).contains(&progress), "progress {}", progress);
+    prop_assert!(timer.remaining_ms(now) >= 0);
+
+    for session in &timer.sessions {
+        prop_assert!(session.ended >= session.started);
+        prop_assert!((0..=session.planned.max(0)).contains(&session.elapsed));
+        // only running out completes a phase
+        if session.completed {
+            prop_assert_eq!(session.elapsed, session.planned);
+        }
+    }
+    let worked = timer
+        .sessions
+        .iter()
+        .filter(|session| session.completed && session.phase == Phase::Work)
+        .count();
+    prop_assert_eq!(timer.round as usize, worked);
+    if timer.mode == Mode::Running && timer.phase == Phase::LongBreak {
+        prop_assert!(timer.round > 0 && timer.round.is_multiple_of(schedule.cycle()));
+    }
+    Ok(())
+}
+
+#[test]
+fn add_takes_at_most_a_day() {
+    assert!(matches!("add -300".parse(), Ok(Command::Add(-300))));
+    assert!(matches!(format!("add {}", MAX_ADD).parse(), Ok(Command::Add(MAX_ADD))));
+    assert!(format!("add {}", MAX_ADD + 1).parse::<Command>().is_err());
+    assert!("add 9223372036854775807".parse::<Command>().is_err());
+}
+
+#[test]
+fn a_held_phase_begins_when_started() {
+    let schedule = Schedule::default();
+    let mut timer = Timer::default();
+    timer.time = 60;
+    timer.hold = true;
+    let mut now = epoch();
+    timer.apply(Command::Start, &schedule, now);
+    now += Duration::seconds(60);
+    assert!(timer.update(now));
+    timer.advance(&schedule, now);
+    assert!(timer.is_waiting());
+    let events = timer.take_events();
+    assert!(matches!(events.last(), Some(Event::PhaseEnded { completed: true, .. })), "{:?}", events);
+
+    // skipped or reset as it waits, it leaves nothing behind
+    timer.apply(Command::Reset, &schedule, now);
+    assert_eq!(timer.sessions.len(), 1);
+    assert!(timer.take_events().is_empty());
+    timer.undo(now);
+    assert!(timer.is_waiting());
+
+    now += Duration::seconds(30);
+    timer.apply(Command::Start, &schedule, now);
+    assert!(!timer.is_waiting());
+    let events = timer.take_events();
+    assert!(matches!(events[..], [Event::PhaseStarted { at, .. }] if at == now), "{:?}", events);
+}
+
+proptest! {
+    #[test]
+    fn stays_valid(schedule in schedule(), steps in prop::collection::vec(step(), 0..64)) {
+        let mut timer = Timer::default();
+        let mut now = epoch();
+        for step in steps {
+            turn(&mut timer, &schedule, &mut now, step);
+            check(&timer, &schedule, now)?;
+        }
+    }
+
+    #[test]
+    fn phases_open_and_close_in_turn(
+        schedule in schedule(),
+        steps in prop::collection::vec(step(), 0..64),
+    ) {
+        let mut timer = Timer::default();
+        let mut now = epoch();
+        let mut open = false;
+        for step in steps {
+            turn(&mut timer, &schedule, &mut now, step);
+            for event in timer.take_events() {
+                match event {
+                    Event::PhaseStarted { .. } => {
+                        prop_assert!(!open, "a phase started inside another");
+                        open = true;
+                    }
+                    Event::PhaseEnded { .. } => {
+                        prop_assert!(open, "a phase ended that never started");
+                        open = false;
+                    }
+                    _ => {}
+                }
+            }
+            prop_assert_eq!(open, timer.mode == Mode::Running && !timer.is_waiting());
+        }
+    }
+
+    #[test]
+    fn never_runs_while_paused(
+        schedule in schedule(),
+        steps in prop::collection::vec(step(), 0..64),
+        wait in 1i64..=7200,
+    ) {
+        let mut timer = Timer::default();
+        let mut now = epoch();
+        for step in steps {
+            turn(&mut timer, &schedule, &mut now, step);
+        }
+        if timer.is_paused() {
+            let (phase, elapsed) = (timer.phase, timer.elapsed);
+            turn(&mut timer, &schedule, &mut now, Step::Wait(wait));
+            prop_assert_eq!(timer.phase, phase);
+            prop_assert_eq!(timer.elapsed, elapsed);
+        }
+    }
+
+    #[test]
+    fn quitting_closes_the_phase_unrecorded(
+        schedule in schedule(),
+        steps in prop::collection::vec(step(), 0..64),
+    ) {
+        let mut timer = Timer::default();
+        let mut now = epoch();
+        for step in steps {
+            turn(&mut timer, &schedule, &mut now, step);
+        }
+        let running = timer.mode == Mode::Running && !timer.is_waiting();
+        let sessions = timer.sessions.len();
+        timer.take_events();
+        timer.quit(now);
+        let ended: Vec<Event> = timer.take_events();
+        prop_assert_eq!(ended.len(), usize::from(running));
+        if let Some(event) = ended.first() {
+            prop_assert!(matches!(event, Event::PhaseEnded { completed: false, .. }), "{:?}", event);
+        }
+        prop_assert_eq!(timer.sessions.len(), sessions);
+        check(&timer, &schedule, now)?;
+    }
+}
@@ -0,0 +1,334 @@
+use chrono::{DateTime, NaiveTime, Utc};
+use std::{fs, time::Instant};
+use tui::layout::Rect;
+
+pub use pom_core::{Command, Event, Mode, Phase, Session, Snapshot, Timer, MAX_ADD, UNDO_SECS};
+use pom_core::{Font, Icons};
+
+use crate::{
+    audio,
+    browser::Browser,
+    config::{Autostart, Config, TimerStyle},
+    degrade::Degrade,
+    diagnostics::Diagnostics,
+    error::Result,
+    goal::Streak,
+    image::{Picture, Protocol},
+    keys::Keymap,
+    link::Link,
+    palette::Palette,
+    picker::Picker,
+    report,
+    stats::Stats,
+    tasks::{Done, Planner, Tasks},
+    template,
+};
+
+/// The timer plus everything the terminal front-end keeps around it.
+pub struct App {
+    pub timer: Timer,
+    /// when one day ends and the next begins, for the count of today's
+    pub day_start: NaiveTime,
+    /// insistent alarm waiting to be acknowledged, with when it last went off
+    pub ringing: Option<Instant>,
+    /// what was last written to the status file
+    pub status: String,
+    /// when the state file was last refreshed
+    pub state_written: Option<Instant>,
+    /// background sound playing during work
+    pub ambient: Option<audio::Loop>,
+    /// master volume, starts out at `sound.volume`
+    pub volume: f32,
+    pub muted: bool,
+    pub keys: Keymap,
+    /// for the big display style
+    pub font: Option<Font>,
+    /// glyphs the terminal can show, see `display.icons`
+    pub icons: Icons,
+    /// where the timer was last drawn, for the mouse
+    pub timer_area: Rect,
+    /// where the planning screen was last drawn, for the mouse
+    pub planner_area: Rect,
+    /// tags drawn in the last frame that point somewhere, when links are on
+    pub links: Option<Vec<Link>>,
+    /// what the terminal can't show, redrawn in what it can
+    pub degrade: Option<Degrade>,
+    /// the tomato as an image, see `display.images`
+    pub picture: Option<Picture>,
+    /// paused for lack of input, and the user hasn't been seen since
+    pub away: bool,
+    /// paused because the screen locked
+    pub lock_paused: bool,
+    /// set by a user script, shown in the status bar
+    pub status_text: Option<String>,
+    /// how many of `timer.sessions` are already in the history
+    pub recorded: usize,
+    /// the stats screen, while it is open
+    pub stats: Option<Stats>,
+    /// the history browser, while it is open
+    pub browser: Option<Browser>,
+    /// choosing a tag to focus on
+    pub picker: Option<Picker>,
+    /// the command palette, while it is open
+    pub palette: Option<Palette>,
+    /// a note being typed
+    pub note: Option<Note>,
+    /// strict mode's confirmation word, as far as it is typed
+    pub confirm: Option<String>,
+    /// the phase ran out, the next one waits for this
+    pub grace: Option<Grace>,
+    /// the break screen was held away for the rest of this break
+    pub unlocked: bool,
+    /// space held on the break screen: since when, and the last repeat
+    pub hold: Option<(Instant, Instant)>,
+    /// opened on the first copy; on X11 what was copied only stays
+    /// pasteable while this is held
+    pub clipboard: Option<arboard::Clipboard>,
+    /// a scheduled work session about to start
+    pub autostart: Option<Countdown>,
+    /// sparing the battery: no animations, no ambient sound
+    pub low_power: bool,
+    /// a profile asked for, switched to on the next turn
+    pub switching: Option<String>,
+    pub tasks: Tasks,
+    /// today's sessions per tag, against the plan
+    pub done: Done,
+    /// today's sessions against the daily goal
+    pub streak: Streak,
+    /// the planning screen is open
+    pub planner: Option<Planner>,
+    pub diagnostics: Diagnostics,
+    /// the debug screen is open
+    pub debug: bool,
+}
+
+/// The countdown between a phase running out and the next one starting.
+pub struct Grace {
+    pub until: DateTime<Utc>,
+    pub next: Phase,
+}
+
+/// An `[[autostart]]` that came due, counting down to its work session.
+pub struct Countdown {
+    pub until: DateTime<Utc>,
+    pub entry: Autostart,
+}
+
+/// A note on one of `timer.sessions`, as far as it is typed.
+pub struct Note {
+    pub index: usize,
+    pub text: String,
+    /// asked for by the journal, which gets a copy; may run over lines
+    pub journal: bool,
+}
+
+impl Note {
+    /// Characters it may grow to: a line, or a few paragraphs for the journal.
+    pub fn max(&self) -> usize {
+        if self.journal {
+            2000
+        } else {
+            200
+        }
+    }
+}
+
+impl App {
+    pub fn new(config: &Config) -> Result<Self> {
+        Ok(App {
+            timer: Timer::default(),
+            day_start: config.day_starts_at,
+            ringing: None,
+            status: String::new(),
+            state_written: None,
+            ambient: None,
+            volume: config.sound.volume.clamp(0.0, 1.0),
+            muted: false,
+            keys: Keymap::new(&config.keys)?,
+            font: font(config),
+            icons: Icons::default(),
+            timer_area: Rect::default(),
+            planner_area: Rect::default(),
+            links: None,
+            degrade: None,
+            picture: picture(config),
+            away: false,
+            lock_paused: false,
+            status_text: None,
+            recorded: 0,
+            stats: None,
+            browser: None,
+            picker: None,
+            palette: None,
+            note: None,
+            confirm: None,
+            grace: None,
+            unlocked: false,
+            hold: None,
+            clipboard: None,
+            autostart: None,
+            low_power: false,
+            switching: None,
+            tasks: Tasks::default(),
+            done: Done::new(&[], report::day(Utc::now(), config.day_starts_at)),
+            streak: Streak::new(&[], report::day(Utc::now(), config.day_starts_at), config.goal.daily, config),
+            planner: None,
+            diagnostics: Diagnostics::new(),
+            debug: false,
+        })
+    }
+
+    /// Step the master volume by `delta`, unmuting.
+    pub fn change_volume(&mut self, delta: f32) {
+        // round so repeated steps land on clean, cacheable levels
+        self.volume = ((self.volume + delta) * 10.0).round().clamp(0.0, 10.0) / 10.0;
+        self.muted = false;
+        // restart the loop at the new level
+        self.ambient = None;
+    }
+
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume.clamp(0.0, 1.0);
+        self.muted = false;
+        self.ambient = None;
+    }
+
+    /// Take on another profile's settings from `from`'s: its sounds, font
+    /// and keys; its durations for what comes next, and for the session
+    /// about to start when it was one of the presets.
+    pub fn switch(&mut self, from: &Config, to: &Config) {
+        self.volume = to.sound.volume.clamp(0.0, 1.0);
+        self.ambient = None;
+        self.day_start = to.day_starts_at;
+        self.font = font(to);
+        self.picture = picture(to);
+        match Keymap::new(&to.keys) {
+            Ok(keys) => self.keys = keys,
+            Err(err) => log::warn!("{}", err.report()),
+        }
+        self.timer.cues = to.cues.at.clone();
+        self.timer.profile = to.profile.clone();
+        self.timer.hold = !to.auto_advance;
+        if self.timer.mode == Mode::Input {
+            let preset = from
+                .presets
+                .iter()
+                .position(|&minutes| minutes * 60 == self.timer.time);
+            if let Some(&minutes) = preset.and_then(|i| to.presets.get(i)) {
+                self.timer.time = minutes.max(0) * 60;
+            }
+        }
+    }
+
+    pub fn toggle_mute(&mut self) {
+        self.muted = !self.muted;
+        self.ambient = None;
+    }
+
+    /// Strict mode holds: the work session can't be paused, skipped or
+    /// changed.
+    pub fn strict(&self, config: &Config) -> bool {
+        config.strict.enabled && self.timer.mode == Mode::Running && self.timer.phase == Phase::Work
+    }
+
+    /// The break screen is up: a break runs and `breaks.enforce` is on.
+    /// One waiting to be started doesn't count yet.
+    pub fn enforcing(&self, config: &Config) -> bool {
+        config.breaks.enforce
+            && self.timer.mode == Mode::Running
+            && self.timer.phase != Phase::Work
+            && !self.timer.is_paused()
+            && self.grace.is_none()
+            && !self.unlocked
+    }
+
+    /// Effective level for a sound with the given per-sound gain.
+    pub fn level(&self, gain: f32) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            (self.volume * gain).clamp(0.0, 1.0)
+        }
+    }
+
+    pub fn context(&self) -> template::Context {
+        let today = report::day(Utc::now(), self.day_start);
+
+        let snapshot = self.timer.snapshot();
+
+        template::Context {
+            state: snapshot.state.to_string(),
+            phase: match (&self.timer.mode, &self.grace) {
+                (Mode::Input, _) => "idle".to_string(),
+                // what the alarm announces
+                (Mode::Running, Some(grace)) => grace.next.name().to_string(),
+                (Mode::Running, None) => self.timer.phase.name().to_string(),
+            },
+            remaining: snapshot.remaining,
+            round: snapshot.round,
+            task: String::new(),
+            count_today: self
+                .timer
+                .sessions
+                .iter()
+                .filter(|s| s.phase == Phase::Work && s.completed)
+                .filter(|s| report::day(s.ended, self.day_start) == today)
+                .count(),
+        }
+    }
+}
+
+/// Images, when asked for and the terminal can show them.
+fn picture(config: &Config) -> Option<Picture> {
+    if !config.display.images {
+        return None;
+    }
+    let Some(protocol) = Protocol::detect() else {
+        log::info!("no image protocol in this terminal, drawing in text");
+        return None;
+    };
+    let logo = config
+        .display
+        .logo
+        .as_ref()
+        .filter(|_| {
+            let png = protocol != Protocol::Sixel;
+            if !png {
+                log::warn!("a logo needs kitty or iTerm2 images, showing the tomato");
+            }
+            png
+        })
+        .and_then(|path| match fs::read(path) {
+            Ok(png) => Some(png),
+            Err(err) => {
+                log::warn!("logo {}: {}, showing the tomato", path.display(), err);
+                None
+            }
+        });
+    Some(Picture {
+        protocol,
+        logo,
+        wanted: None,
+        shown: None,
+    })
+}
+
+/// The big style's font: the configured one, over the bundled digits.
+fn font(config: &Config) -> Option<Font> {
+    if config.display.style != TimerStyle::Big {
+        return None;
+    }
+    let Some(path) = &config.display.font else {
+        return Some(Font::standard());
+    };
+    let font = fs::read_to_string(path)
+        .map_err(|err| err.to_string())
+        .and_then(|text| Font::parse(&text));
+    match font {
+        Ok(font) => Some(font.or(Font::standard())),
+        Err(err) => {
+            log::warn!("font {}: {}, using the block digits", path.display(), err);
+            Some(Font::standard())
+        }
+    }
+}
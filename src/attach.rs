@@ -0,0 +1,135 @@
+//! Poms attached to the running one, over `pom.sock` beside its state: a
+//! desktop, a laptop over SSH and a tmux pane can all show the same timer
+//! and drive it.
+//!
+//! Both ways it is one line at a time: commands in the command pipe's text
+//! form going in, the `pom status` JSON coming out whenever it changes.
+
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use crate::{app::Command, control::Handle, platform, state, template::Context};
+
+/// How often an attached pom hears about the timer.
+const INTERVAL: Duration = Duration::from_millis(250);
+
+fn path() -> Option<PathBuf> {
+    platform::project_dirs().map(|dirs| dirs.data_local_dir().join("pom.sock"))
+}
+
+/// Let other poms attach. Only the pom holding the lock gets here, so a
+/// socket already there was left by one that crashed.
+pub fn serve(handle: Handle) {
+    let Some(path) = path() else {
+        return;
+    };
+    let _ = fs::remove_file(&path);
+    let listener = match UnixListener::bind(&path) {
+        Ok(listener) => listener,
+        Err(err) => {
+            log::warn!("attach socket {} unavailable: {}", path.display(), err);
+            return;
+        }
+    };
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let handle = handle.clone();
+                    thread::spawn(move || attached(stream, handle));
+                }
+                Err(err) => log::warn!("attach socket: {}", err),
+            }
+        }
+    });
+}
+
+/// Called on exit, so nobody attaches to a pom that isn't there.
+pub fn remove() {
+    if let Some(path) = path() {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// One attached pom: its commands to the timer, the timer's state to it,
+/// until it goes away.
+fn attached(mut stream: UnixStream, handle: Handle) {
+    let Ok(reader) = stream.try_clone() else {
+        return;
+    };
+    let commands = handle.clone();
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines().map_while(io::Result::ok) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match line.parse::<Command>() {
+                Ok(command) => commands.send(command),
+                Err(err) => log::warn!("attached pom: {}", err),
+            }
+        }
+    });
+
+    let mut sent = String::new();
+    loop {
+        if let Some(line) = handle
+            .context()
+            .and_then(|context| serde_json::to_string(&context).ok())
+        {
+            if line != sent {
+                if writeln!(stream, "{}", line).is_err() {
+                    return;
+                }
+                sent = line;
+            }
+        }
+        thread::sleep(INTERVAL);
+    }
+}
+
+/// The attaching end.
+pub struct Link {
+    stream: UnixStream,
+    /// the latest state heard, `stopped` once the other pom is gone
+    context: Arc<Mutex<Option<Context>>>,
+}
+
+impl Link {
+    /// Attach to the running pom, if it takes attachments.
+    pub fn connect() -> Option<Link> {
+        let stream = UnixStream::connect(path()?).ok()?;
+        let reader = stream.try_clone().ok()?;
+        let context = Arc::new(Mutex::new(None));
+        let latest = Arc::clone(&context);
+        thread::spawn(move || {
+            for line in BufReader::new(reader).lines().map_while(io::Result::ok) {
+                if let (Ok(context), Ok(mut latest)) = (serde_json::from_str(&line), latest.lock())
+                {
+                    *latest = Some(context);
+                }
+            }
+            if let Ok(mut latest) = latest.lock() {
+                *latest = Some(state::stopped());
+            }
+        });
+        Some(Link { stream, context })
+    }
+
+    pub fn send(&mut self, command: Command) {
+        // a pom that went away shows as stopped, that's enough
+        let _ = writeln!(self.stream, "{}", command);
+    }
+
+    /// None until the first state comes in.
+    pub fn context(&self) -> Option<Context> {
+        self.context.lock().ok().and_then(|context| context.clone())
+    }
+}
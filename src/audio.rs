@@ -0,0 +1,356 @@
+use std::{
+    f32::consts::TAU,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    config::Ambient,
+    error::{PomError, Result},
+    platform::{self, find_executable},
+};
+
+const SAMPLE_RATE: u32 = 22_050;
+
+/// Sounds we synthesize ourselves, so nothing has to be bundled.
+#[derive(Clone, Copy)]
+pub enum Sound {
+    Tick,
+    Tock,
+    Alarm,
+    /// a soft chime for a cue
+    Cue,
+    /// a loop of generated background noise
+    Noise(Ambient),
+}
+
+impl Sound {
+    fn name(self) -> &'static str {
+        match self {
+            Sound::Tick => "tick",
+            Sound::Tock => "tock",
+            Sound::Alarm => "alarm",
+            Sound::Cue => "cue",
+            Sound::Noise(Ambient::Off) => "silence",
+            Sound::Noise(Ambient::White) => "white-noise",
+            Sound::Noise(Ambient::Pink) => "pink-noise",
+            Sound::Noise(Ambient::Brown) => "brown-noise",
+            Sound::Noise(Ambient::Rain) => "rain",
+        }
+    }
+
+    /// Mono samples in -1.0..=1.0.
+    fn samples(self) -> Vec<f32> {
+        match self {
+            Sound::Tick => click(1800.0),
+            Sound::Tock => click(1200.0),
+            Sound::Alarm => {
+                let mut out = Vec::new();
+                for _ in 0..3 {
+                    out.extend(beep(880.0, 0.15));
+                    out.extend(silence(0.1));
+                }
+                out
+            }
+            Sound::Cue => beep(660.0, 0.12),
+            Sound::Noise(kind) => noise(kind),
+        }
+    }
+}
+
+/// External program used to play a WAV file.
+#[derive(Clone, Copy)]
+pub enum Player {
+    PulseAudio,
+    PipeWire,
+    Alsa,
+    Afplay,
+    PowerShell,
+}
+
+impl Player {
+    pub fn detect() -> Option<Self> {
+        let candidates: &[(&str, Player)] = if cfg!(windows) {
+            &[("powershell", Player::PowerShell)]
+        } else if cfg!(target_os = "macos") {
+            &[("afplay", Player::Afplay)]
+        } else {
+            &[
+                ("paplay", Player::PulseAudio),
+                ("pw-play", Player::PipeWire),
+                ("aplay", Player::Alsa),
+            ]
+        };
+
+        candidates
+            .iter()
+            .find(|(exe, _)| find_executable(exe).is_some())
+            .map(|(_, player)| *player)
+    }
+
+    /// Play `sound` at `volume` (0.0..=1.0) without waiting for it to finish.
+    pub fn play(&self, sound: Sound, volume: f32) -> Result<()> {
+        let path = render(sound, volume)?;
+        let mut child = self.command(&path, None).spawn().map_err(PomError::Audio)?;
+        thread::spawn(move || child.wait());
+        Ok(())
+    }
+
+    /// `volume` is applied by the player, for files we can't scale ourselves;
+    /// aplay and the Windows SoundPlayer have no such option.
+    fn command(&self, path: &Path, volume: Option<f32>) -> Command {
+        let mut command = match self {
+            Player::PulseAudio => {
+                let mut c = Command::new("paplay");
+                if let Some(volume) = volume {
+                    c.arg(format!("--volume={}", (volume * 65536.0) as u32));
+                }
+                c
+            }
+            Player::PipeWire => {
+                let mut c = Command::new("pw-play");
+                if let Some(volume) = volume {
+                    c.arg(format!("--volume={}", volume));
+                }
+                c
+            }
+            Player::Alsa => {
+                let mut c = Command::new("aplay");
+                c.arg("-q");
+                c
+            }
+            Player::Afplay => {
+                let mut c = Command::new("afplay");
+                if let Some(volume) = volume {
+                    c.args(["-v", &volume.to_string()]);
+                }
+                c
+            }
+            Player::PowerShell => {
+                let mut c = Command::new("powershell");
+                c.args(["-NoProfile", "-NonInteractive", "-Command"]);
+                c.arg(format!(
+                    "(New-Object Media.SoundPlayer '{}').PlaySync()",
+                    path.display().to_string().replace('\'', "''")
+                ));
+                c
+            }
+        };
+        if !matches!(self, Player::PowerShell) {
+            command.arg(path);
+        }
+        command
+    }
+}
+
+/// A sound file played over and over until stopped or dropped.
+pub struct Loop {
+    stop: Arc<AtomicBool>,
+}
+
+impl Loop {
+    /// Loop a generated noise at `volume`.
+    pub fn noise(player: Player, kind: Ambient, volume: f32) -> Self {
+        Self::spawn(player, None, move || render(Sound::Noise(kind), volume))
+    }
+
+    /// Loop a user-provided file, in whatever formats the player supports.
+    pub fn file(player: Player, path: PathBuf, volume: f32) -> Self {
+        Self::spawn(player, Some(volume), move || Ok(path))
+    }
+
+    fn spawn<F>(player: Player, volume: Option<f32>, source: F) -> Self
+    where
+        F: FnOnce() -> Result<PathBuf> + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::clone(&stop);
+
+        // rendering and playback both stay off the UI thread; failures end
+        // the loop until the next work session instead of retrying per frame
+        thread::spawn(move || {
+            let path = match source() {
+                Ok(path) => path,
+                Err(err) => {
+                    log::warn!("{}", err.report());
+                    return;
+                }
+            };
+
+            while !stopped.load(Ordering::Relaxed) {
+                let mut child = match player.command(&path, volume).spawn() {
+                    Ok(child) => child,
+                    Err(err) => {
+                        log::warn!("ambient sound stopped: {}", err);
+                        return;
+                    }
+                };
+
+                // poll so a stop request can cut the current pass short
+                loop {
+                    match child.try_wait() {
+                        Ok(Some(status)) if !status.success() => {
+                            log::warn!("ambient sound {} failed to play", path.display());
+                            return;
+                        }
+                        Ok(Some(_)) => break,
+                        Ok(None) if stopped.load(Ordering::Relaxed) => {
+                            let _ = child.kill();
+                            let _ = child.wait();
+                            return;
+                        }
+                        Ok(None) => thread::sleep(Duration::from_millis(100)),
+                        Err(_) => return,
+                    }
+                }
+            }
+        });
+
+        Loop { stop }
+    }
+}
+
+impl Drop for Loop {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Write `sound` at `volume` to the cache dir once and return its path.
+fn render(sound: Sound, volume: f32) -> Result<PathBuf> {
+    let dir = platform::project_dirs()
+        .map(|dirs| dirs.cache_dir().to_path_buf())
+        .unwrap_or_else(|| std::env::temp_dir().join("pom"));
+
+    let volume = volume.clamp(0.0, 1.0);
+    let path = dir.join(format!(
+        "{}-{:03}.wav",
+        sound.name(),
+        (volume * 100.0).round()
+    ));
+    if path.is_file() {
+        return Ok(path);
+    }
+
+    let samples: Vec<f32> = sound.samples().into_iter().map(|s| s * volume).collect();
+    fs::create_dir_all(&dir)
+        .and_then(|_| fs::write(&path, wav(&samples)))
+        .map_err(PomError::Audio)?;
+    Ok(path)
+}
+
+/// 16-bit mono PCM WAV.
+fn wav(samples: &[f32]) -> Vec<u8> {
+    let data_len = samples.len() as u32 * 2;
+    let mut out = Vec::with_capacity(44 + data_len as usize);
+
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&(36 + data_len).to_le_bytes());
+    out.extend_from_slice(b"WAVEfmt ");
+    out.extend_from_slice(&16u32.to_le_bytes()); // fmt chunk size
+    out.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    out.extend_from_slice(&1u16.to_le_bytes()); // mono
+    out.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    out.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes()); // byte rate
+    out.extend_from_slice(&2u16.to_le_bytes()); // block align
+    out.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&data_len.to_le_bytes());
+
+    for sample in samples {
+        let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+    out
+}
+
+/// A short, quickly decaying blip: soft enough to play every second.
+fn click(freq: f32) -> Vec<f32> {
+    let len = SAMPLE_RATE as usize * 25 / 1000;
+    (0..len)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            (TAU * freq * t).sin() * (-t * 250.0).exp() * 0.5
+        })
+        .collect()
+}
+
+fn beep(freq: f32, secs: f32) -> Vec<f32> {
+    let len = (SAMPLE_RATE as f32 * secs) as usize;
+    let fade = SAMPLE_RATE as usize / 200;
+    (0..len)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            // 5ms ramps at either end avoid audible pops
+            let envelope = (i.min(len - i) as f32 / fade as f32).min(1.0);
+            (TAU * freq * t).sin() * envelope * 0.8
+        })
+        .collect()
+}
+
+/// Twenty seconds of noise, faded at both ends so the loop seam is soft.
+fn noise(kind: Ambient) -> Vec<f32> {
+    let len = SAMPLE_RATE as usize * 20;
+    let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+    let mut samples = Vec::with_capacity(len);
+
+    // filter state for pink (Paul Kellet's approximation) and brown noise
+    let (mut b0, mut b1, mut b2, mut brown) = (0.0f32, 0.0f32, 0.0f32, 0.0f32);
+    let mut droplet = 0.0f32;
+
+    for _ in 0..len {
+        let white = rng.next();
+        b0 = 0.99765 * b0 + white * 0.099_046;
+        b1 = 0.96300 * b1 + white * 0.296_516_4;
+        b2 = 0.57000 * b2 + white * 1.052_691_3;
+        let pink = (b0 + b1 + b2 + white * 0.1848) * 0.12;
+        brown = (brown + 0.02 * white) / 1.02;
+
+        samples.push(match kind {
+            Ambient::Off => 0.0,
+            Ambient::White => white * 0.3,
+            Ambient::Pink => pink,
+            Ambient::Brown => brown * 3.0,
+            Ambient::Rain => {
+                // a pink-noise wash with sparse, fast-decaying droplets
+                if rng.next() > 0.9994 {
+                    droplet = 0.4 + 0.3 * rng.next().abs();
+                }
+                droplet *= 0.995;
+                pink * 0.7 + white * droplet
+            }
+        });
+    }
+
+    let fade = SAMPLE_RATE as usize / 50;
+    for i in 0..fade {
+        let gain = i as f32 / fade as f32;
+        samples[i] *= gain;
+        samples[len - 1 - i] *= gain;
+    }
+    samples
+}
+
+/// Small deterministic PRNG; noise doesn't need anything better.
+struct XorShift(u64);
+
+impl XorShift {
+    /// Uniform in -1.0..1.0.
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+    }
+}
+
+fn silence(secs: f32) -> Vec<f32> {
+    vec![0.0; (SAMPLE_RATE as f32 * secs) as usize]
+}
@@ -0,0 +1,163 @@
+//! Keeps the screen from blanking while the timer runs, on `keep_awake`:
+//! through the desktop portal or the screensaver service on Linux, which
+//! between them cover GNOME, KDE and the X11 desktops; `caffeinate` on
+//! macOS; a PowerShell holding the display on Windows.
+
+use std::{
+    cell::Cell,
+    sync::mpsc::{self, Sender},
+    thread,
+};
+
+#[cfg(target_os = "linux")]
+const REASON: &str = "a pomodoro is running";
+
+/// Asks for the screen to stay on, off the UI thread since the desktop
+/// can take its time answering.
+pub struct Awake {
+    held: Cell<bool>,
+    hold: Sender<bool>,
+}
+
+impl Awake {
+    pub fn start() -> Self {
+        let (hold, holds) = mpsc::channel::<bool>();
+        thread::spawn(move || {
+            let mut inhibitor = None;
+            for hold in holds {
+                if !hold {
+                    // dropping it lets the screen blank again
+                    inhibitor = None;
+                } else if inhibitor.is_none() {
+                    match inhibit() {
+                        Ok(held) => inhibitor = Some(held),
+                        Err(err) => log::info!("keeping the screen awake unavailable: {}", err),
+                    }
+                }
+            }
+        });
+        Awake {
+            held: Cell::new(false),
+            hold,
+        }
+    }
+
+    /// Keep the screen on, or let it go; cheap to call every frame.
+    pub fn hold(&self, hold: bool) {
+        if self.held.replace(hold) != hold {
+            let _ = self.hold.send(hold);
+        }
+    }
+}
+
+/// Keeps the screen on until dropped.
+enum Inhibitor {
+    #[cfg(target_os = "linux")]
+    Portal {
+        conn: zbus::blocking::Connection,
+        request: zbus::zvariant::OwnedObjectPath,
+    },
+    #[cfg(target_os = "linux")]
+    ScreenSaver {
+        conn: zbus::blocking::Connection,
+        cookie: u32,
+    },
+    /// a program that holds it for as long as it runs
+    #[cfg_attr(not(any(target_os = "macos", windows)), allow(dead_code))]
+    Helper(std::process::Child),
+}
+
+impl Drop for Inhibitor {
+    fn drop(&mut self) {
+        match self {
+            #[cfg(target_os = "linux")]
+            Inhibitor::Portal { conn, request } => {
+                let _ = zbus::blocking::Proxy::new(
+                    conn,
+                    PORTAL,
+                    request.as_ref(),
+                    "org.freedesktop.portal.Request",
+                )
+                .and_then(|request| request.call::<_, _, ()>("Close", &()));
+            }
+            #[cfg(target_os = "linux")]
+            Inhibitor::ScreenSaver { conn, cookie } => {
+                let _ = screensaver(conn)
+                    .and_then(|saver| saver.call::<_, _, ()>("UnInhibit", &(*cookie)));
+            }
+            Inhibitor::Helper(child) => {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+const PORTAL: &str = "org.freedesktop.portal.Desktop";
+
+#[cfg(target_os = "linux")]
+fn screensaver(conn: &zbus::blocking::Connection) -> zbus::Result<zbus::blocking::Proxy<'_>> {
+    zbus::blocking::Proxy::new(
+        conn,
+        "org.freedesktop.ScreenSaver",
+        "/org/freedesktop/ScreenSaver",
+        "org.freedesktop.ScreenSaver",
+    )
+}
+
+/// The portal where there is one that inhibits, otherwise the older
+/// screensaver service; either lets go if pom dies holding it.
+#[cfg(target_os = "linux")]
+fn inhibit() -> zbus::Result<Inhibitor> {
+    use std::collections::HashMap;
+    use zbus::zvariant::Value;
+
+    let conn = zbus::blocking::Connection::session()?;
+    let portal = zbus::blocking::Proxy::new(
+        &conn,
+        PORTAL,
+        "/org/freedesktop/portal/desktop",
+        "org.freedesktop.portal.Inhibit",
+    )?;
+    // 8 is idle, no parent window from a terminal
+    let options = HashMap::from([("reason", Value::from(REASON))]);
+    match portal.call("Inhibit", &("", 8u32, options)) {
+        Ok(request) => return Ok(Inhibitor::Portal { conn, request }),
+        Err(err) => log::debug!("portal can't keep the screen awake: {}", err),
+    }
+    let cookie = screensaver(&conn)?.call("Inhibit", &("pom", REASON))?;
+    Ok(Inhibitor::ScreenSaver { conn, cookie })
+}
+
+/// Display and idle sleep both, until pom exits at the latest.
+#[cfg(target_os = "macos")]
+fn inhibit() -> std::io::Result<Inhibitor> {
+    std::process::Command::new("caffeinate")
+        .args(["-d", "-i", "-w", &std::process::id().to_string()])
+        .spawn()
+        .map(Inhibitor::Helper)
+}
+
+/// The execution state belongs to the thread that set it, so it is a
+/// PowerShell's to hold until it's killed or pom exits.
+#[cfg(windows)]
+fn inhibit() -> std::io::Result<Inhibitor> {
+    let script = format!(
+        "$k = Add-Type -Name Power -Namespace Pom -PassThru -MemberDefinition \
+         '[DllImport(\"kernel32.dll\")] public static extern uint SetThreadExecutionState(uint flags);'; \
+         $k::SetThreadExecutionState(0x80000003) > $null; \
+         Wait-Process -Id {}",
+        std::process::id()
+    );
+    std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .stdin(std::process::Stdio::null())
+        .spawn()
+        .map(Inhibitor::Helper)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn inhibit() -> std::io::Result<Inhibitor> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
@@ -0,0 +1,236 @@
+//! `pom backup` and `pom restore`: the history and the config in one file,
+//! sealed with the history's passphrase when the history is encrypted.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    app::Session,
+    cipher::{Cipher, Header},
+    config::Config,
+    error::{PomError, Result},
+    history::Store,
+    instance, platform,
+};
+
+/// Bumped whenever the bundle layout changes; restores refuse anything
+/// newer than they know.
+const FORMAT: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct Bundle {
+    format: u32,
+    /// the pom that wrote it, so a refused restore can say what is needed
+    version: String,
+    created: DateTime<Utc>,
+    /// kept as text so comments survive
+    config: Option<String>,
+    history: Vec<Session>,
+}
+
+pub fn backup(path: &Path) -> Result<()> {
+    let config = match Config::path() {
+        Some(config_path) => {
+            read_optional(&config_path).map_err(|source| PomError::ConfigRead {
+                path: config_path,
+                source,
+            })?
+        }
+        None => None,
+    };
+    let store = Store::configured(&Config::load()?.history)?;
+    let bundle = Bundle {
+        format: FORMAT,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        created: Utc::now(),
+        config,
+        history: store.load()?,
+    };
+
+    let write_failed = |source| PomError::BackupWrite {
+        path: path.to_path_buf(),
+        source,
+    };
+    let mut json = serde_json::to_vec_pretty(&bundle)
+        .map_err(io::Error::from)
+        .map_err(write_failed)?;
+    if store.is_encrypted() {
+        json = store.seal(&json).ok_or_else(|| PomError::BackupEncrypt {
+            path: path.to_path_buf(),
+        })?;
+    }
+    write_atomic(path, &json).map_err(write_failed)?;
+    println!("backed up {} to {}", contents(&bundle), path.display());
+    Ok(())
+}
+
+pub fn restore(path: &Path) -> Result<()> {
+    let _lock = instance::hold("restoring")?;
+
+    let text = fs::read_to_string(path).map_err(|source| PomError::BackupRead {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let text = unseal(text, path)?;
+    // look at the format on its own first, so a newer bundle gets a clear
+    // message rather than a parse error about some field it added
+    #[derive(Deserialize)]
+    struct Header {
+        format: u32,
+        version: String,
+    }
+    let parse = |source| PomError::BackupParse {
+        path: path.to_path_buf(),
+        source,
+    };
+    let header: Header = serde_json::from_str(&text).map_err(parse)?;
+    if header.format > FORMAT {
+        return Err(PomError::BackupVersion {
+            path: path.to_path_buf(),
+            version: header.version,
+        });
+    }
+    let bundle: Bundle = serde_json::from_str(&text).map_err(parse)?;
+
+    // validate everything before touching anything; the history goes to
+    // whichever backend the restored config uses
+    let config = match &bundle.config {
+        Some(config) => toml::from_str(config).map_err(|source| PomError::ConfigParse {
+            path: path.to_path_buf(),
+            source,
+        })?,
+        None => Config::load()?,
+    };
+    let store = Store::configured(&config.history)?;
+
+    // stage both files next to their targets, then swap them in together
+    let mut staged = Vec::new();
+    if let (Some(config), Some(target)) = (&bundle.config, Config::path()) {
+        staged.push((stage(&target, config.as_bytes())?, target));
+    }
+    // logged first, or rebuild-stats would replay the old history back
+    store.log_changes(&bundle.history)?;
+    staged.extend(store.stage(&bundle.history)?);
+    for (tmp, target) in staged {
+        fs::rename(&tmp, &target).map_err(|source| PomError::BackupWrite {
+            path: target,
+            source,
+        })?;
+    }
+
+    println!("restored {} from {}", contents(&bundle), path.display());
+    Ok(())
+}
+
+/// The bundle in a backup of an encrypted history, opened with the
+/// passphrase the config gives; any other backup as it is.
+fn unseal(text: String, path: &Path) -> Result<String> {
+    let mut lines = text.lines();
+    let Some(header) = lines.next().and_then(|line| serde_json::from_str::<Header>(line).ok())
+    else {
+        return Ok(text);
+    };
+    let Some(command) = Config::load()?.history.passphrase_command else {
+        return Err(PomError::BackupEncrypted {
+            path: path.to_path_buf(),
+        });
+    };
+    let passphrase = platform::run_secret_command(&command)?;
+    let cipher = Cipher::open(&passphrase, &header).ok_or_else(|| PomError::BackupPassphrase {
+        path: path.to_path_buf(),
+    })?;
+    lines
+        .next()
+        .and_then(|line| cipher.unseal(line))
+        .and_then(|json| String::from_utf8(json).ok())
+        .ok_or_else(|| PomError::BackupDecrypt {
+            path: path.to_path_buf(),
+        })
+}
+
+/// `12 sessions and the config`, for the closing message.
+fn contents(bundle: &Bundle) -> String {
+    let config = if bundle.config.is_some() {
+        " and the config"
+    } else {
+        ""
+    };
+    format!("{} sessions{}", bundle.history.len(), config)
+}
+
+fn read_optional(path: &Path) -> io::Result<Option<String>> {
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(Some(text)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Write `contents` beside `target`, returning the temporary file.
+fn stage(target: &Path, contents: &[u8]) -> Result<PathBuf> {
+    let tmp = target.with_extension("restore.tmp");
+    let write = || {
+        if let Some(dir) = target.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&tmp, contents)
+    };
+    write().map_err(|source| PomError::BackupWrite {
+        path: target.to_path_buf(),
+        source,
+    })?;
+    Ok(tmp)
+}
+
+fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{app::Phase, headless::Scratch, platform};
+    use chrono::Duration;
+
+    #[test]
+    fn seals_the_backup_of_an_encrypted_history() {
+        let scratch = Scratch::new().unwrap();
+        let _here = platform::locate_here(scratch.path().to_path_buf());
+        let config = "[history]\npassphrase_command = \"echo secret\"\n";
+        fs::write(scratch.path().join("config.toml"), config).unwrap();
+        let store = Store::configured(&Config::load().unwrap().history).unwrap();
+        let started = Utc::now() - Duration::minutes(30);
+        store
+            .append(&Session {
+                phase: Phase::Work,
+                started,
+                ended: started + Duration::minutes(25),
+                planned: 1500,
+                elapsed: 1500,
+                completed: true,
+                pauses: 0,
+                tags: vec!["acme".to_string()],
+                note: None,
+                source: None,
+                profile: None,
+            })
+            .unwrap();
+
+        let path = scratch.path().join("backup.json");
+        backup(&path).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(!text.contains("acme") && !text.contains("passphrase_command"));
+        restore(&path).unwrap();
+        assert_eq!(store.load().unwrap()[0].tags, ["acme"]);
+
+        let config = "[history]\npassphrase_command = \"echo guess\"\n";
+        fs::write(scratch.path().join("config.toml"), config).unwrap();
+        assert!(matches!(restore(&path), Err(PomError::BackupPassphrase { .. })));
+    }
+}
@@ -0,0 +1,250 @@
+//! The history browser: past sessions, newest first, narrowed down by
+//! period, tag, outcome and a search.
+
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Paragraph},
+    Frame,
+};
+
+use crate::{
+    app::Session,
+    complete::Completion,
+    error::Result,
+    history::{Query, Store},
+    picker,
+    report::{self, Period},
+};
+
+pub struct Browser {
+    pub period: Period,
+    /// only sessions with this tag
+    tag: Option<String>,
+    /// every tag in the history, to pick from
+    tags: Vec<String>,
+    /// only those that ran out, or only those that didn't
+    outcome: Option<bool>,
+    pub search: String,
+    /// keys go to the search while it is being typed
+    pub searching: bool,
+    /// the selected session being corrected, as typed: `25m #tag`
+    pub editing: Option<String>,
+    /// tab on a `#tag` in the edit line
+    pub completion: Completion,
+    /// asked to delete the selected session once, waiting for the second
+    pub deleting: bool,
+    /// what the filters let through, newest first
+    sessions: Vec<Session>,
+    selected: usize,
+    /// when one day ends and the next begins
+    day_start: NaiveTime,
+}
+
+impl Browser {
+    pub fn new(history: &Store, day_start: NaiveTime) -> Result<Self> {
+        let sessions = history.load()?;
+        let completion = Completion::new(picker::used(&sessions, Utc::now()));
+        let mut tags: Vec<String> = sessions
+            .into_iter()
+            .flat_map(|session| session.tags)
+            .flat_map(|tag| report::levels(&tag).map(str::to_string).collect::<Vec<_>>())
+            .collect();
+        tags.sort();
+        tags.dedup();
+        let mut browser = Browser {
+            period: Period::Week,
+            tag: None,
+            tags,
+            outcome: None,
+            search: String::new(),
+            searching: false,
+            editing: None,
+            completion,
+            deleting: false,
+            sessions: Vec::new(),
+            selected: 0,
+            day_start,
+        };
+        browser.refresh(history)?;
+        Ok(browser)
+    }
+
+    /// Ask the history again, after a filter changed or a session ended.
+    pub fn refresh(&mut self, history: &Store) -> Result<()> {
+        self.sessions = history.query(&self.query())?;
+        self.selected = self.selected.min(self.sessions.len().saturating_sub(1));
+        Ok(())
+    }
+
+    fn query(&self) -> Query {
+        let today = report::day(Utc::now(), self.day_start);
+        let range = self.period.range(today, false);
+        Query {
+            from: range.and_then(|(from, _)| self.start_of(from)),
+            to: range.and_then(|(_, to)| self.start_of(to + Duration::days(1))),
+            tag: self.tag.clone(),
+            completed: self.outcome,
+            text: self.search.clone(),
+        }
+    }
+
+    /// When `day` begins, going by `day_starts_at`.
+    fn start_of(&self, day: NaiveDate) -> Option<DateTime<Utc>> {
+        Local
+            .from_local_datetime(&day.and_time(self.day_start))
+            .earliest()
+            .map(|at| at.with_timezone(&Utc))
+    }
+
+    /// The next tag to filter by, then none again.
+    pub fn cycle_tag(&mut self) {
+        let at = self
+            .tag
+            .as_ref()
+            .and_then(|tag| self.tags.iter().position(|t| t == tag));
+        self.tag = match at {
+            Some(at) => self.tags.get(at + 1).cloned(),
+            None => self.tags.first().cloned(),
+        };
+    }
+
+    /// All, completed, stopped early.
+    pub fn cycle_outcome(&mut self) {
+        self.outcome = match self.outcome {
+            None => Some(true),
+            Some(true) => Some(false),
+            Some(false) => None,
+        };
+    }
+
+    pub fn selected(&self) -> Option<&Session> {
+        self.sessions.get(self.selected)
+    }
+
+    pub fn select(&mut self, by: isize) {
+        let last = self.sessions.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(by).min(last);
+    }
+}
+
+/// What the edit line starts out as: the minutes run and the tags.
+pub fn editable(session: &Session) -> String {
+    let mut text = format!("{}m", session.elapsed / 60);
+    for tag in &session.tags {
+        text += &format!(" #{}", tag);
+    }
+    text
+}
+
+/// `session` as the edit line says it should be: `#tag`s replace the
+/// tags, and minutes (`25m` or `25`) the time run, moving its end.
+pub fn edited(session: &Session, text: &str) -> std::result::Result<Session, String> {
+    let mut edited = session.clone();
+    edited.tags.clear();
+    for word in text.split_whitespace() {
+        if let Some(tag) = word.strip_prefix('#').filter(|tag| !tag.is_empty()) {
+            edited.tags.push(tag.to_string());
+            continue;
+        }
+        let minutes: i64 = word
+            .strip_suffix('m')
+            .unwrap_or(word)
+            .parse()
+            .map_err(|_| format!("`{}` is neither a #tag nor minutes like 25m", word))?;
+        // the same minutes keep the seconds
+        if minutes != session.elapsed / 60 {
+            edited.elapsed = minutes.max(0) * 60;
+            edited.ended = session.ended + Duration::seconds(edited.elapsed - session.elapsed);
+        }
+    }
+    Ok(edited)
+}
+
+pub fn draw<B: Backend>(f: &mut Frame<B>, browser: &Browser, area: Rect) {
+    let mut title = format!(
+        "History: {}, {} session{}",
+        browser.period.name(),
+        browser.sessions.len(),
+        if browser.sessions.len() == 1 { "" } else { "s" }
+    );
+    if let Some(tag) = &browser.tag {
+        title += &format!(", #{}", tag);
+    }
+    match browser.outcome {
+        Some(true) => title += ", completed",
+        Some(false) => title += ", stopped early",
+        None => {}
+    }
+    let block = Block::default().title(title);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let search = browser.searching || !browser.search.is_empty() || browser.editing.is_some();
+    let rows = (inner.height as usize).saturating_sub(usize::from(search));
+    // keep the selection in view
+    let offset = (browser.selected + 1).saturating_sub(rows);
+    let mut lines: Vec<Spans> = browser
+        .sessions
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .take(rows)
+        .map(|(i, session)| {
+            let line = entry(session);
+            if i == browser.selected {
+                Spans::from(Span::styled(
+                    format!("{:<width$}", line, width = inner.width as usize),
+                    Style::default().add_modifier(Modifier::REVERSED),
+                ))
+            } else {
+                Spans::from(line)
+            }
+        })
+        .collect();
+    if browser.sessions.is_empty() {
+        lines.push(Spans::from(Span::styled(
+            "Nothing recorded that fits.",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    lines.resize(rows, Spans::default());
+    if let Some(text) = &browser.editing {
+        lines.push(Spans::from(vec![
+            Span::styled("edit: ", Style::default().fg(Color::Yellow)),
+            Span::raw(format!("{}_", text)),
+        ]));
+    } else if search {
+        let cursor = if browser.searching { "_" } else { "" };
+        lines.push(Spans::from(vec![
+            Span::styled("/", Style::default().fg(Color::Yellow)),
+            Span::raw(format!("{}{}", browser.search, cursor)),
+        ]));
+    }
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+/// `Tue 10-14 09:00  work         25m of 25m  done     #a  the note`
+fn entry(session: &Session) -> String {
+    let started = session.started.with_timezone(&Local);
+    let outcome = if session.completed { "done" } else { "stopped" };
+    let tags: Vec<String> = session.tags.iter().map(|tag| format!("#{}", tag)).collect();
+    let note = session
+        .note
+        .as_deref()
+        .map(|note| note.lines().next().unwrap_or_default())
+        .unwrap_or_default();
+    format!(
+        "{}  {:<11}  {:>6} of {:<6}  {:<7}  {}  {}",
+        started.format("%a %m-%d %H:%M"),
+        session.phase.name(),
+        report::duration(session.elapsed),
+        report::duration(session.planned),
+        outcome,
+        tags.join(" "),
+        note
+    )
+}
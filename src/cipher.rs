@@ -0,0 +1,112 @@
+//! At-rest encryption for the history, one line at a time so appending a
+//! session never means rewriting the file.
+
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+    XChaCha20Poly1305, XNonce,
+};
+use serde::{Deserialize, Serialize};
+
+const SCHEME: &str = "argon2id-xchacha20poly1305";
+const NONCE_LEN: usize = 24;
+
+/// Sealed into the header, so a wrong passphrase is told apart from a
+/// damaged line.
+const CHECK: &[u8] = b"pom history";
+
+/// First line of an encrypted history: how to get the key back.
+#[derive(Serialize, Deserialize)]
+pub struct Header {
+    encryption: String,
+    /// base64
+    salt: String,
+    /// `CHECK`, sealed
+    check: String,
+}
+
+pub struct Cipher {
+    aead: XChaCha20Poly1305,
+}
+
+impl Cipher {
+    /// A key for a new history, and the header to start it with.
+    pub fn create(passphrase: &str) -> Option<(Self, Header)> {
+        let mut salt = [0; 16];
+        OsRng.fill_bytes(&mut salt);
+        let cipher = Cipher::derive(passphrase, &salt)?;
+        let header = Header {
+            encryption: SCHEME.to_string(),
+            salt: STANDARD.encode(salt),
+            check: cipher.seal(CHECK)?,
+        };
+        Some((cipher, header))
+    }
+
+    /// The key for an existing history; `None` when the passphrase is wrong.
+    pub fn open(passphrase: &str, header: &Header) -> Option<Self> {
+        if header.encryption != SCHEME {
+            return None;
+        }
+        let salt = STANDARD.decode(&header.salt).ok()?;
+        let cipher = Cipher::derive(passphrase, &salt)?;
+        (cipher.unseal(&header.check)? == CHECK).then_some(cipher)
+    }
+
+    fn derive(passphrase: &str, salt: &[u8]) -> Option<Self> {
+        let mut key = [0; 32];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .ok()?;
+        Some(Cipher {
+            aead: XChaCha20Poly1305::new(&key.into()),
+        })
+    }
+
+    /// base64 of a fresh nonce followed by the ciphertext.
+    pub fn seal(&self, plaintext: &[u8]) -> Option<String> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let sealed = self.aead.encrypt(&nonce, plaintext).ok()?;
+        Some(STANDARD.encode([nonce.as_slice(), &sealed].concat()))
+    }
+
+    pub fn unseal(&self, line: &str) -> Option<Vec<u8>> {
+        let bytes = STANDARD.decode(line.trim()).ok()?;
+        if bytes.len() < NONCE_LEN {
+            return None;
+        }
+        let (nonce, sealed) = bytes.split_at(NONCE_LEN);
+        self.aead.decrypt(XNonce::from_slice(nonce), sealed).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unseals_what_it_sealed() {
+        let (cipher, header) = Cipher::create("correct horse").unwrap();
+        let sealed = cipher.seal(b"a session").unwrap();
+        assert_ne!(sealed.as_bytes(), b"a session");
+        let opened = Cipher::open("correct horse", &header).unwrap();
+        assert_eq!(opened.unseal(&sealed).as_deref(), Some(&b"a session"[..]));
+    }
+
+    #[test]
+    fn refuses_a_wrong_passphrase() {
+        let (_, header) = Cipher::create("correct horse").unwrap();
+        assert!(Cipher::open("battery staple", &header).is_none());
+    }
+
+    #[test]
+    fn refuses_a_tampered_line() {
+        let (cipher, _) = Cipher::create("correct horse").unwrap();
+        let mut bytes = STANDARD.decode(cipher.seal(b"a session").unwrap()).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        assert!(cipher.unseal(&STANDARD.encode(&bytes)).is_none());
+        assert!(cipher.unseal("not base64").is_none());
+        assert!(cipher.unseal("").is_none());
+    }
+}
@@ -0,0 +1,226 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, TimeZone};
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+use crate::{history::Backend, import::Source, invoice::Format, report::Period};
+
+/// A pomodoro timer for the terminal.
+#[derive(Parser)]
+#[command(version)]
+pub struct Cli {
+    /// Tag the sessions of this run, e.g. with a project; repeatable.
+    /// Without it, the last run's tags carry over
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+    /// Use the settings under [profiles.NAME] in the config
+    #[arg(long, value_name = "NAME")]
+    pub profile: Option<String>,
+    /// Keep the history and state here rather than in the usual data
+    /// directory
+    #[arg(long, value_name = "DIR", global = true)]
+    pub data_dir: Option<PathBuf>,
+    /// Keep everything, config included, in pom-data beside the binary,
+    /// e.g. on a USB stick; on by itself once that directory exists
+    #[arg(long, global = true)]
+    pub portable: bool,
+    #[command(subcommand)]
+    pub command: Option<Cmd>,
+}
+
+#[derive(Subcommand)]
+pub enum Cmd {
+    /// Print the running timer's state and exit
+    Status {
+        /// Template instead of JSON, e.g. '{phase} {remaining}'
+        #[arg(long, short)]
+        format: Option<String>,
+    },
+    /// Show the running timer here too and drive it, e.g. from a laptop
+    /// over SSH or another tmux pane
+    Attach,
+    /// Summarize the recorded sessions
+    Report {
+        #[arg(long, short, value_enum, default_value_t = Period::Week)]
+        period: Period,
+        /// The period before this one, e.g. last week
+        #[arg(long)]
+        previous: bool,
+        /// Jinja template to render the report with, instead of the built-in one
+        #[arg(long, short)]
+        template: Option<PathBuf>,
+        /// Mail the report as set up under [email] in the config
+        #[arg(long)]
+        email: bool,
+    },
+    /// Record focus time spent away from the timer, e.g. in a meeting
+    Log {
+        /// How long it went: 25m, 1h30m, 90s, or plain minutes
+        #[arg(long, short, value_parser = duration)]
+        duration: i64,
+        /// When it started: 14:00, yesterday 14:00 or 2026-10-13 14:00;
+        /// without it, it ends now
+        #[arg(long, value_parser = at)]
+        at: Option<DateTime<Local>>,
+        /// Tag it, e.g. with a project; repeatable
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+        /// What came of it
+        #[arg(long, short)]
+        note: Option<String>,
+    },
+    /// Sum up billable focus time per project, at the [billing] rates
+    Invoice {
+        /// First day, e.g. 2026-10-01; the start of this month by default
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        /// Last day; today by default
+        #[arg(long)]
+        to: Option<NaiveDate>,
+        #[arg(long, short, value_enum, default_value_t = Format::Markdown)]
+        format: Format,
+    },
+    /// Add the day's work sessions to its Obsidian daily note, skipping
+    /// those already there
+    Obsidian {
+        /// e.g. 2026-10-13; today by default
+        #[arg(long)]
+        date: Option<NaiveDate>,
+    },
+    /// Bring in the history kept by another pomodoro app
+    Import {
+        #[arg(long, value_enum)]
+        from: Source,
+        path: PathBuf,
+    },
+    /// Go over today's sessions and note what to carry over to tomorrow
+    Review,
+    /// Save the history and config to one file
+    Backup { path: PathBuf },
+    /// Replace the history and config with a backup's
+    Restore { path: PathBuf },
+    /// Rebuild the history from the event log, undoing anything that
+    /// changed it behind pom's back
+    RebuildStats,
+    /// Print the history as JSONL
+    Export {
+        /// Hash tags, notes, profiles and sources, keeping only the timing,
+        /// e.g. to share it
+        #[arg(long)]
+        anonymize: bool,
+    },
+    /// Copy the history to another storage backend, dropping duplicates
+    Migrate {
+        #[arg(long, value_enum)]
+        from: Backend,
+        #[arg(long, value_enum)]
+        to: Backend,
+    },
+}
+
+/// The longest time `pom log` takes, in seconds: a day.
+const MAX_DURATION: i64 = 24 * 60 * 60;
+
+/// `1h30m`, `25m`, `90s` or `25` in seconds, up to a day.
+fn duration(text: &str) -> Result<i64, String> {
+    let invalid = || format!("`{}` is not a duration like 25m or 1h30m", text);
+    let secs = match text.parse::<i64>() {
+        Ok(minutes) => minutes.checked_mul(60),
+        Err(_) => {
+            let mut secs = Some(0i64);
+            let mut number = String::new();
+            for c in text.chars() {
+                if c.is_ascii_digit() {
+                    number.push(c);
+                    continue;
+                }
+                let unit = match c {
+                    'h' => 3600,
+                    'm' => 60,
+                    's' => 1,
+                    _ => return Err(invalid()),
+                };
+                let part = number.parse::<i64>().map_err(|_| invalid())?;
+                secs = part
+                    .checked_mul(unit)
+                    .and_then(|part| secs?.checked_add(part));
+                number.clear();
+            }
+            if !number.is_empty() {
+                return Err(invalid());
+            }
+            secs
+        }
+    };
+    match secs {
+        Some(secs) if (1..=MAX_DURATION).contains(&secs) => Ok(secs),
+        Some(secs) if secs <= 0 => Err(invalid()),
+        _ => Err(format!("`{}` is longer than a day", text)),
+    }
+}
+
+/// `14:00` today, `yesterday 14:00` or `2026-10-13 14:00`, local time.
+fn at(text: &str) -> Result<DateTime<Local>, String> {
+    let invalid = || format!("`{}` is not a time like 14:00 or yesterday 14:00", text);
+    let today = Local::now().date_naive();
+    let (day, time) = match text.trim().rsplit_once(' ') {
+        Some((day, time)) => {
+            let day = match day.trim() {
+                "today" => today,
+                "yesterday" => today - Duration::days(1),
+                day => NaiveDate::parse_from_str(day, "%Y-%m-%d").map_err(|_| invalid())?,
+            };
+            (day, time)
+        }
+        None => (today, text.trim()),
+    };
+    let time = NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| invalid())?;
+    Local
+        .from_local_datetime(&day.and_time(time))
+        .earliest()
+        .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_durations() {
+        assert_eq!(duration("25"), Ok(25 * 60));
+        assert_eq!(duration("1h30m"), Ok(90 * 60));
+        assert_eq!(duration("90s"), Ok(90));
+        assert_eq!(duration("24h"), Ok(MAX_DURATION));
+    }
+
+    #[test]
+    fn refuses_odd_durations() {
+        for text in ["", "0", "-5", "0m", "25x", "1h30", "h"] {
+            assert!(duration(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn refuses_durations_past_a_day() {
+        for text in ["25h", "1441", "9999999999999h", "153722867280912930", "99999999999999999999m"] {
+            assert!(duration(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn reads_times() {
+        let today = Local::now().date_naive();
+        let at = |text| at(text).map(|at| at.naive_local());
+        assert_eq!(at("14:00"), Ok(today.and_hms_opt(14, 0, 0).unwrap()));
+        assert_eq!(
+            at("yesterday 09:30"),
+            Ok((today - Duration::days(1)).and_hms_opt(9, 30, 0).unwrap())
+        );
+        assert_eq!(
+            at("2026-10-13 14:00"),
+            Ok(NaiveDate::from_ymd_opt(2026, 10, 13).unwrap().and_hms_opt(14, 0, 0).unwrap())
+        );
+        for text in ["", "25:00", "tomorrow 14:00", "2026-13-01 14:00", "14"] {
+            assert!(at(text).is_err(), "{}", text);
+        }
+    }
+}
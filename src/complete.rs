@@ -0,0 +1,87 @@
+//! Tab completion of `#tags` on a line being typed, against the tags used
+//! so far, so a tag is typed the same way every time.
+
+/// The tags to complete from, and a completion under way.
+pub struct Completion {
+    /// most used first
+    tags: Vec<String>,
+    cycle: Option<Cycle>,
+}
+
+/// Tab pressed on a `#tag`: where it starts, what was typed of it, and the
+/// tags it went through.
+struct Cycle {
+    start: usize,
+    typed: String,
+    candidates: Vec<String>,
+    /// the one on the line, or `candidates.len()` for what was typed
+    at: usize,
+}
+
+impl Completion {
+    /// `used` is each tag with how much it has been used, as the picker
+    /// weighs them.
+    pub fn new(mut used: Vec<(String, f64)>) -> Self {
+        used.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        Completion {
+            tags: used.into_iter().map(|(tag, _)| tag).collect(),
+            cycle: None,
+        }
+    }
+
+    /// Complete the `#tag` at the end of `text` to the most used one it
+    /// could be, or on the next tab, to the one after (`by` -1 for the one
+    /// before), coming back round to what was typed.
+    pub fn tab(&mut self, text: &mut String, by: isize) {
+        if self.cycle.is_none() {
+            self.cycle = self.start(text);
+        }
+        let Some(cycle) = &mut self.cycle else {
+            return;
+        };
+        let stops = cycle.candidates.len() + 1;
+        cycle.at = (cycle.at as isize + by).rem_euclid(stops as isize) as usize;
+        text.truncate(cycle.start);
+        text.push_str(cycle.candidates.get(cycle.at).unwrap_or(&cycle.typed));
+    }
+
+    /// Anything but a tab: the next one completes afresh.
+    pub fn typed(&mut self) {
+        self.cycle = None;
+    }
+
+    fn start(&self, text: &str) -> Option<Cycle> {
+        let word = text.rsplit(char::is_whitespace).next()?;
+        let typed = word.strip_prefix('#')?;
+        let start = text.len() - typed.len();
+        let others: Vec<&str> = text[..start - 1]
+            .split_whitespace()
+            .filter_map(|word| word.strip_prefix('#'))
+            .collect();
+        let fresh = |tag: &&String| !others.contains(&tag.as_str());
+        let lower = typed.to_lowercase();
+        let mut candidates: Vec<String> = self
+            .tags
+            .iter()
+            .filter(fresh)
+            .filter(|tag| tag.to_lowercase().starts_with(&lower))
+            .cloned()
+            .collect();
+        // nothing starts that way: a typo, maybe, so anything close
+        if candidates.is_empty() {
+            candidates = self
+                .tags
+                .iter()
+                .filter(fresh)
+                .filter(|tag| crate::picker::score(typed, tag).is_some())
+                .cloned()
+                .collect();
+        }
+        (!candidates.is_empty()).then(|| Cycle {
+            start,
+            typed: typed.to_string(),
+            at: candidates.len(),
+            candidates,
+        })
+    }
+}
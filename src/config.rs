@@ -0,0 +1,1044 @@
+use chrono::{DateTime, Datelike, Local, NaiveTime, TimeZone, Utc, Weekday};
+use log::LevelFilter;
+pub use pom_core::{Cue, Icons, Schedule};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    error::{PomError, Result},
+    history::Backend,
+    keys::{Action, Binding},
+    platform, report,
+};
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// send a desktop notification when the timer runs out
+    pub notifications: bool,
+    /// minimum level shown in the message panel and written to `log_file`
+    pub log_level: LevelFilter,
+    /// also append log messages to this file
+    pub log_file: Option<PathBuf>,
+    /// what goes in `log_file` instead of `log_level`, e.g.
+    /// `pom::integrations=trace,info`; `POM_LOG` overrides it
+    pub log_filter: Option<String>,
+    /// start a new log file `hourly` or `daily`, keeping the last seven;
+    /// `never` by default
+    pub log_rotate: LogRotate,
+    /// expose the timer on the session bus as org.pomtui.Timer (Linux)
+    pub dbus: bool,
+    /// FIFO to read text commands from (`echo skip > ~/.pom.fifo`, unix)
+    pub command_pipe: Option<PathBuf>,
+    /// serve the gRPC control API (proto/pom.proto) here, e.g.
+    /// `127.0.0.1:7170`; builds with the grpc feature only
+    pub grpc: Option<String>,
+    /// serve the timer to browsers here, e.g. `127.0.0.1:7171`, or
+    /// `0.0.0.0:7171` for phones on the LAN: a dashboard at `/`, `/events`
+    /// for live widgets and OBS overlays
+    pub http: Option<String>,
+    /// the one other origin whose pages may read `/events`, e.g.
+    /// `https://overlay.example`, or `*` for any; none by default, so a
+    /// site open in the browser can't watch the timer
+    pub http_origin: Option<String>,
+    pub schedule: Schedule,
+    /// start the next phase as soon as one runs out; otherwise it is set
+    /// up paused, and waits for a start
+    pub auto_advance: bool,
+    /// seconds of "break starts in..." between phases; 0 goes straight on
+    pub grace: u64,
+    /// keep the screen from blanking while the timer runs
+    pub keep_awake: bool,
+    /// a redraw a second at most, no animations and no ambient sound:
+    /// `auto` while on battery, `always` or `never`
+    pub low_power: LowPower,
+    /// minutes the number keys pick for the next work session, 1 to 5
+    pub presets: Vec<i64>,
+    /// start with the length, tags and volume of the last work session
+    pub remember: bool,
+    /// open the planning screen on the day's first run, until it is planned
+    pub plan: bool,
+    pub goal: Goal,
+    pub billing: Billing,
+    /// weekly focus minutes for a project and everything under it:
+    /// `"work/backend" = 600`
+    pub budgets: HashMap<String, u32>,
+    /// local time the day rolls over at for goals and stats, `03:00` for
+    /// night owls
+    pub day_starts_at: NaiveTime,
+    /// where the history and state live, instead of the usual data
+    /// directory; relative to this file's directory
+    pub data_dir: Option<PathBuf>,
+    pub history: History,
+    pub alarm: Alarm,
+    pub auto_pause: AutoPause,
+    pub sound: Sounds,
+    pub speech: Speech,
+    pub cues: Cues,
+    /// keep this file updated with the `templates.status` text, for bars/prompts
+    pub status_file: Option<PathBuf>,
+    pub templates: Templates,
+    pub display: Display,
+    /// rebind actions: `toggle = ["p", "space p"]`, chords space-separated
+    pub keys: HashMap<Action, Binding>,
+    /// Lua script that hooks into the timer, see script.rs
+    pub script: Option<PathBuf>,
+    /// block out focus time on a CalDAV calendar
+    pub caldav: Option<CalDav>,
+    /// report work sessions to a WakaTime-compatible API
+    pub heartbeat: Option<Heartbeat>,
+    /// a row in a Notion database for each completed work session
+    pub notion: Option<Notion>,
+    /// sandboxed WebAssembly integrations, one `[[plugins]]` each
+    pub plugins: Vec<Plugin>,
+    /// where `pom report --email` sends the report
+    pub email: Option<Email>,
+    /// ask what came of each work session, and keep the answers
+    pub journal: Option<Journal>,
+    /// a line for each work session in an Obsidian vault's daily notes
+    pub obsidian: Option<Obsidian>,
+    /// no pausing, skipping or stretching work sessions
+    pub strict: Strict,
+    pub breaks: Breaks,
+    /// profile for some days when none is picked: `fri = "short"`,
+    /// `weekends = "off"`; a single day wins over a group
+    pub days: HashMap<Days, String>,
+    /// work sessions that start by themselves, one `[[autostart]]` each
+    pub autostart: Vec<Autostart>,
+    pub hours: Hours,
+    pub links: Links,
+    pub hotkeys: Hotkeys,
+    /// the profile laid over the rest, picked or `[days]`'s
+    #[serde(skip)]
+    pub profile: Option<String>,
+    /// `goal.daily` on each day of the week, Monday first, going by the
+    /// profile `[days]` names for it
+    #[serde(skip)]
+    pub goals: [u32; 7],
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct Goal {
+    /// pomodoros to complete each day; 0 sets none
+    pub daily: u32,
+}
+
+/// What focus time on a project is billed at, for `pom invoice`.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct Billing {
+    /// shown with the amounts, e.g. `EUR`
+    pub currency: String,
+    /// hourly rate per project: `"acme" = 90`; sub-projects take the rate
+    /// of the nearest project above them that has one
+    pub rates: HashMap<String, f64>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct History {
+    /// `jsonl`, `sqlite` or `synced`; `pom migrate` moves the sessions over
+    pub backend: Backend,
+    /// prints the passphrase to encrypt the history with (jsonl only);
+    /// setting it encrypts an existing history in place
+    pub passphrase_command: Option<String>,
+    /// this machine's name in a synced history; the host name when unset
+    pub device: Option<String>,
+    /// keep the history in a git repository in the data directory
+    pub git: Option<Git>,
+}
+
+/// A commit a day, the first time the history changes on a new day, with
+/// everything up to then.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct Git {
+    /// push each commit, for a copy somewhere else
+    pub push: bool,
+    pub remote: String,
+}
+
+impl Default for Git {
+    fn default() -> Self {
+        Git {
+            push: false,
+            remote: "origin".to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Alarm {
+    /// keep alerting until a key is pressed, for when you walked away
+    pub insistent: bool,
+    /// seconds between repeated alerts in insistent mode
+    pub repeat_every: u64,
+}
+
+impl Default for Alarm {
+    fn default() -> Self {
+        Alarm {
+            insistent: false,
+            repeat_every: 30,
+        }
+    }
+}
+
+/// System-wide hotkeys, as the desktop portal writes them: `CTRL+ALT+p`
+/// (Linux); the desktop has the last word on which keys they get.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct Hotkeys {
+    pub start: Option<String>,
+    pub pause: Option<String>,
+    pub toggle: Option<String>,
+    pub skip: Option<String>,
+}
+
+impl Hotkeys {
+    /// `(id, description, keys)` of each one that's set.
+    pub fn bindings(&self) -> Vec<(&'static str, &'static str, String)> {
+        [
+            ("start", "Start a pomodoro", &self.start),
+            ("pause", "Pause the timer", &self.pause),
+            ("toggle", "Pause or resume the timer", &self.toggle),
+            ("skip", "Skip to the next phase", &self.skip),
+        ]
+        .into_iter()
+        .filter_map(|(id, description, keys)| Some((id, description, keys.clone()?)))
+        .collect()
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct AutoPause {
+    /// pause a work session after this many minutes without keyboard or
+    /// mouse input anywhere on the desktop; 0 never does
+    pub idle: u64,
+    /// pause a work session while the screen is locked (Linux, via logind)
+    pub on_lock: bool,
+    /// what unlocking does to a session the lock paused
+    pub on_unlock: OnUnlock,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum OnUnlock {
+    /// leave it paused and say so, the user picks up with `p`
+    #[default]
+    Prompt,
+    Resume,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum LogRotate {
+    #[default]
+    Never,
+    Hourly,
+    Daily,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum LowPower {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            notifications: true,
+            log_level: LevelFilter::Info,
+            log_file: None,
+            log_filter: None,
+            log_rotate: LogRotate::Never,
+            dbus: true,
+            command_pipe: None,
+            grpc: None,
+            http: None,
+            http_origin: None,
+            schedule: Schedule::default(),
+            auto_advance: false,
+            grace: 0,
+            keep_awake: false,
+            low_power: LowPower::Auto,
+            presets: vec![15, 25, 45, 50, 90],
+            remember: true,
+            plan: false,
+            goal: Goal::default(),
+            billing: Billing::default(),
+            budgets: HashMap::new(),
+            day_starts_at: NaiveTime::MIN,
+            data_dir: None,
+            history: History::default(),
+            alarm: Alarm::default(),
+            auto_pause: AutoPause::default(),
+            sound: Sounds::default(),
+            speech: Speech::default(),
+            cues: Cues::default(),
+            status_file: None,
+            templates: Templates::default(),
+            display: Display::default(),
+            keys: HashMap::new(),
+            script: None,
+            caldav: None,
+            heartbeat: None,
+            notion: None,
+            plugins: Vec::new(),
+            email: None,
+            journal: None,
+            obsidian: None,
+            strict: Strict::default(),
+            breaks: Breaks::default(),
+            days: HashMap::new(),
+            autostart: Vec::new(),
+            hours: Hours::default(),
+            links: Links::default(),
+            hotkeys: Hotkeys::default(),
+            profile: None,
+            goals: [0; 7],
+        }
+    }
+}
+
+impl Config {
+    pub fn path() -> Option<PathBuf> {
+        platform::project_dirs().map(|dirs| dirs.config_dir().join("config.toml"))
+    }
+
+    /// The config at `path` if it reads, for the little that is needed
+    /// before the rest: a broken one is reported when it's loaded.
+    pub fn peek(path: &Path) -> Option<Config> {
+        toml::from_str(&fs::read_to_string(path).ok()?).ok()
+    }
+
+    /// Load the user config, falling back to defaults when there is none.
+    pub fn load() -> Result<Self> {
+        Config::load_profile(None)
+    }
+
+    /// Load the user config with a profile's settings laid over it:
+    /// `[profiles.deep-work]` holds any of the usual settings, and
+    /// `pom --profile deep-work` uses them instead.
+    pub fn load_profile(profile: Option<&str>) -> Result<Self> {
+        Config::load_with(profile, false)
+    }
+
+    /// Like `load_profile`, but without one picked `[days]` may name a
+    /// profile for today: for the timer and the day's review, where the
+    /// day's goal matters.
+    pub fn load_today(profile: Option<&str>) -> Result<Self> {
+        Config::load_with(profile, true)
+    }
+
+    fn load_with(profile: Option<&str>, by_day: bool) -> Result<Self> {
+        let unknown = |name: &str| PomError::UnknownProfile {
+            name: name.to_string(),
+        };
+        let Some(path) = Self::path() else {
+            return match profile {
+                Some(name) => Err(unknown(name)),
+                None => Ok(Config::default()),
+            };
+        };
+
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => match profile {
+                Some(name) => return Err(unknown(name)),
+                None => return Ok(Config::default()),
+            },
+            Err(source) => return Err(PomError::ConfigRead { path, source }),
+        };
+        let parse = |source| PomError::ConfigParse {
+            path: path.clone(),
+            source,
+        };
+        let mut table: toml::Table = toml::from_str(&text).map_err(parse)?;
+        let goals = goals(&table).map_err(parse)?;
+        let today = match profile {
+            Some(_) => None,
+            None if by_day => day_profile(&table, weekday(&table)).map_err(parse)?,
+            None => None,
+        };
+        let name = profile.or(today.as_deref());
+        if let Some(name) = name {
+            let overlay = table
+                .get("profiles")
+                .and_then(|profiles| profiles.get(name))
+                .and_then(toml::Value::as_table)
+                .cloned()
+                .ok_or_else(|| unknown(name))?;
+            overlay_table(&mut table, overlay);
+        }
+        let mut config: Config = toml::Value::Table(table).try_into().map_err(parse)?;
+        config.profile = name.map(str::to_string);
+        config.goals = goals;
+        Ok(config)
+    }
+
+    /// The names under `[profiles]`, in order.
+    pub fn profiles() -> Vec<String> {
+        let Some(text) = Self::path().and_then(|path| fs::read_to_string(path).ok()) else {
+            return Vec::new();
+        };
+        let Ok(table) = toml::from_str::<toml::Table>(&text) else {
+            return Vec::new();
+        };
+        table
+            .get("profiles")
+            .and_then(toml::Value::as_table)
+            .map(|profiles| profiles.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// The day of the week it is, going by `day_starts_at`.
+fn weekday(table: &toml::Table) -> Weekday {
+    let day_start = table
+        .get("day_starts_at")
+        .and_then(toml::Value::as_str)
+        .and_then(|time| time.parse().ok())
+        .unwrap_or(NaiveTime::MIN);
+    report::day(Utc::now(), day_start).weekday()
+}
+
+/// What `[days]` has for `day`.
+fn day_profile(table: &toml::Table, day: Weekday) -> std::result::Result<Option<String>, toml::de::Error> {
+    let Some(days) = table.get("days") else {
+        return Ok(None);
+    };
+    let days: HashMap<Days, String> = days.clone().try_into()?;
+    let mut matching: Vec<(Days, String)> = days
+        .into_iter()
+        .filter(|(days, _)| days.include(day))
+        .collect();
+    matching.sort_by_key(|(days, _)| matches!(days, Days::Weekdays | Days::Weekends));
+    Ok(matching.into_iter().next().map(|(_, profile)| profile))
+}
+
+/// `goal.daily` for each day of the week, Monday first: the profile
+/// `[days]` names for it has the say over the one at the top.
+fn goals(table: &toml::Table) -> std::result::Result<[u32; 7], toml::de::Error> {
+    let daily = |table: &toml::Table| -> Option<u32> {
+        table.get("goal")?.get("daily")?.as_integer()?.try_into().ok()
+    };
+    let mut goals = [daily(table).unwrap_or(0); 7];
+    for (day, goal) in goals.iter_mut().enumerate() {
+        let Some(profile) = day_profile(table, Weekday::try_from(day as u8).unwrap())? else {
+            continue;
+        };
+        let profile = table
+            .get("profiles")
+            .and_then(|profiles| profiles.get(&profile))
+            .and_then(toml::Value::as_table);
+        if let Some(daily) = profile.and_then(daily) {
+            *goal = daily;
+        }
+    }
+    Ok(goals)
+}
+
+/// Lay `overlay` over `base`, table by table, so a profile only has to
+/// name what it changes.
+fn overlay_table(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overlay)) => {
+                overlay_table(base, overlay)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Cues {
+    /// points in each work session: `at = ["50%", "5m left"]`
+    pub at: Vec<Cue>,
+    /// a soft chime at each
+    pub sound: bool,
+    pub volume: f32,
+    /// a desktop notification at each
+    pub notify: bool,
+}
+
+impl Default for Cues {
+    fn default() -> Self {
+        Cues {
+            at: Vec::new(),
+            sound: true,
+            volume: 0.5,
+            notify: false,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Speech {
+    /// say out loud when a phase starts (espeak, say or SAPI)
+    pub enabled: bool,
+    /// minutes left in a phase to say so at; 0 never does
+    pub warn_at: i64,
+}
+
+impl Default for Speech {
+    fn default() -> Self {
+        Speech {
+            enabled: false,
+            warn_at: 5,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Sounds {
+    /// master volume, 0.0..=1.0; the `*_volume` gains below are relative to it
+    pub volume: f32,
+    /// play a chime when time is up
+    pub alarm: bool,
+    pub alarm_volume: f32,
+    /// tick every second while a work session runs
+    pub tick: Ticking,
+    pub tick_volume: f32,
+    /// background noise looped during work sessions, silent on breaks
+    pub ambient: Ambient,
+    /// loop this file instead of generated noise (cafe chatter, ...)
+    pub ambient_file: Option<PathBuf>,
+    pub ambient_volume: f32,
+}
+
+impl Default for Sounds {
+    fn default() -> Self {
+        Sounds {
+            volume: 1.0,
+            alarm: true,
+            alarm_volume: 0.8,
+            tick: Ticking::Off,
+            tick_volume: 0.3,
+            ambient: Ambient::Off,
+            ambient_file: None,
+            ambient_volume: 0.5,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Ticking {
+    Off,
+    Tick,
+    TickTock,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Ambient {
+    Off,
+    White,
+    Pink,
+    Brown,
+    Rain,
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Display {
+    pub style: TimerStyle,
+    /// frame rate cap while the gauge animates
+    pub fps: u32,
+    /// FIGlet font (`.flf`) for the big style; block digits otherwise, and
+    /// for anything the font lacks
+    pub font: Option<PathBuf>,
+    /// count the last ten seconds of a phase in tenths
+    pub tenths: bool,
+    /// `ascii`, `emoji` or `nerd`; unset picks emoji where the terminal
+    /// takes UTF-8 and ASCII elsewhere
+    pub icons: Option<Icons>,
+    /// no blinking or animation, for motion sensitivity and screen
+    /// recordings; colors and labels stay
+    pub reduced_motion: bool,
+    /// with the tomato style, draw a real image on terminals that speak
+    /// the kitty or iTerm2 image protocols
+    pub images: bool,
+    /// PNG to show there instead of the tomato
+    pub logo: Option<PathBuf>,
+}
+
+impl Default for Display {
+    fn default() -> Self {
+        Display {
+            style: TimerStyle::Digits,
+            fps: 30,
+            font: None,
+            tenths: false,
+            icons: None,
+            reduced_motion: false,
+            images: false,
+            logo: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TimerStyle {
+    /// seconds left, redrawn once a second
+    Digits,
+    /// a progress bar that fills smoothly between seconds
+    Gauge,
+    /// an ASCII tomato that fills up as the phase goes by
+    Tomato,
+    /// the digits in big letters, see `font`
+    Big,
+}
+
+/// Text templates, see `template.rs` for the placeholders.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Templates {
+    pub notification_title: String,
+    pub notification_body: String,
+    pub status: String,
+}
+
+impl Default for Templates {
+    fn default() -> Self {
+        Templates {
+            notification_title: "pom".to_string(),
+            notification_body: "Time's up! Next: {phase} ({count_today} today)".to_string(),
+            status: "{phase} {remaining}".to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct CalDav {
+    /// calendar collection, e.g. https://dav.example.com/calendars/me/work/
+    pub url: String,
+    pub username: String,
+    pub password: Option<String>,
+    /// prints the password, instead of keeping it in this file
+    pub password_command: Option<String>,
+    #[serde(default = "CalDav::default_summary")]
+    pub summary: String,
+    /// what happens to the event when a session is skipped or reset
+    #[serde(default)]
+    pub on_abandon: OnAbandon,
+}
+
+impl CalDav {
+    fn default_summary() -> String {
+        "Busy \u{2014} focus".to_string()
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum OnAbandon {
+    #[default]
+    Delete,
+    /// end the event when the session was abandoned
+    Shorten,
+}
+
+/// The database's columns are named here; each is left out when empty.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct Notion {
+    /// an internal integration's secret, shared with the database
+    pub token: Option<String>,
+    /// prints the token, instead of keeping it in this file
+    pub token_command: Option<String>,
+    /// the database's ID, from its URL
+    pub database: String,
+    pub url: String,
+    /// the title column, set to the note or the tags
+    pub title: String,
+    /// a date column, with the start and end
+    pub date: String,
+    /// a number column, minutes worked
+    pub duration: String,
+    /// a multi-select column
+    pub tags: String,
+}
+
+impl Default for Notion {
+    fn default() -> Self {
+        Notion {
+            token: None,
+            token_command: None,
+            database: String::new(),
+            url: "https://api.notion.com/v1".to_string(),
+            title: "Name".to_string(),
+            date: "Date".to_string(),
+            duration: "Minutes".to_string(),
+            tags: "Tags".to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct Heartbeat {
+    /// API base, e.g. https://api.wakatime.com/api/v1 or a Wakapi instance
+    pub url: String,
+    pub api_key: Option<String>,
+    /// prints the API key, instead of keeping it in this file
+    pub api_key_command: Option<String>,
+    pub project: String,
+    /// what the time is logged against until tasks exist
+    pub entity: String,
+    pub category: String,
+    /// seconds between heartbeats
+    pub interval: u64,
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Heartbeat {
+            url: "https://api.wakatime.com/api/v1".to_string(),
+            api_key: None,
+            api_key_command: None,
+            project: "pom".to_string(),
+            entity: "pomodoro".to_string(),
+            category: "coding".to_string(),
+            interval: 120,
+        }
+    }
+}
+
+/// A WebAssembly component built against wit/plugin.wit.
+#[derive(Deserialize, Clone)]
+#[cfg_attr(not(feature = "plugins"), allow(dead_code))]
+pub struct Plugin {
+    pub path: PathBuf,
+    /// hosts it may send HTTP requests to, e.g. `hooks.slack.com`
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// handed to the plugin's `init` as is
+    #[serde(default)]
+    pub config: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+pub struct Email {
+    /// `me@example.com` or `Me <me@example.com>`
+    pub to: String,
+    /// defaults to `to`
+    pub from: Option<String>,
+    /// SMTP server
+    pub server: String,
+    /// defaults to the usual port for `security`
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub security: Security,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// prints the password, instead of keeping it in this file
+    pub password_command: Option<String>,
+    /// Jinja, with the same values as the report template
+    #[serde(default = "Email::default_subject")]
+    pub subject: String,
+}
+
+impl Email {
+    fn default_subject() -> String {
+        "Focus report, {{ from }} to {{ to }}".to_string()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Strict {
+    pub enabled: bool,
+    /// typed to abandon a work session anyway
+    pub confirm: String,
+}
+
+impl Default for Strict {
+    fn default() -> Self {
+        Strict {
+            enabled: false,
+            confirm: "abandon".to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Breaks {
+    /// take the whole screen during breaks and ignore every key, so the
+    /// break is a break
+    pub enforce: bool,
+    /// seconds space has to be held to get the screen back anyway
+    pub unlock_hold: u64,
+}
+
+impl Default for Breaks {
+    fn default() -> Self {
+        Breaks {
+            enforce: false,
+            unlock_hold: 3,
+        }
+    }
+}
+
+/// Working hours, one `[[hours.shifts]]` per stretch of the week.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct Hours {
+    /// what starting a work session outside them does
+    pub outside: Outside,
+    /// any time is working time when there are none
+    pub shifts: Vec<Shift>,
+}
+
+impl Hours {
+    /// `at` is working time.
+    pub fn contain(&self, at: DateTime<Local>) -> bool {
+        self.shifts.is_empty() || self.shifts.iter().any(|shift| shift.contains(at))
+    }
+}
+
+/// Tags that point somewhere, shown as links in terminals that take them.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Links {
+    pub enabled: bool,
+    /// where `owner/repo#123` tags go
+    pub github: String,
+    /// Jira site for `PROJ-123` tags, e.g. `https://acme.atlassian.net`
+    pub jira: Option<String>,
+}
+
+impl Default for Links {
+    fn default() -> Self {
+        Links {
+            enabled: true,
+            github: "https://github.com".to_string(),
+            jira: None,
+        }
+    }
+}
+
+impl Links {
+    /// Where `tag` leads: itself when it's a web address, the issue when
+    /// it's a GitHub or Jira reference.
+    pub fn url(&self, tag: &str) -> Option<String> {
+        if tag.starts_with("https://") || tag.starts_with("http://") {
+            return Some(tag.to_string());
+        }
+        if let Some((repo, number)) = tag.split_once('#') {
+            let repo_ok =
+                repo.split('/').count() == 2 && !repo.starts_with('/') && !repo.ends_with('/');
+            if repo_ok && !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) {
+                let base = self.github.trim_end_matches('/');
+                return Some(format!("{}/{}/issues/{}", base, repo, number));
+            }
+        }
+        let jira = self.jira.as_deref()?;
+        let (project, number) = tag.split_once('-')?;
+        let key = !project.is_empty()
+            && project.starts_with(|c: char| c.is_ascii_uppercase())
+            && project
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+            && !number.is_empty()
+            && number.chars().all(|c| c.is_ascii_digit());
+        key.then(|| format!("{}/browse/{}", jira.trim_end_matches('/'), tag))
+    }
+}
+
+/// `days = ["weekdays"]`, `from = "09:00"`, `to = "17:30"`.
+#[derive(Deserialize)]
+pub struct Shift {
+    /// every day when empty
+    #[serde(default)]
+    pub days: Vec<Days>,
+    /// local time
+    pub from: NaiveTime,
+    pub to: NaiveTime,
+}
+
+impl Shift {
+    fn contains(&self, at: DateTime<Local>) -> bool {
+        Days::any(&self.days, at.weekday()) && (self.from..self.to).contains(&at.time())
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Outside {
+    /// start anyway, with a warning
+    #[default]
+    Warn,
+    Refuse,
+}
+
+/// `days = ["weekdays"]`, `at = "09:00"`, `profile = "deep-work"`: begin
+/// a work session then, if pom is running and idle.
+#[derive(Deserialize, Clone)]
+pub struct Autostart {
+    /// every day when empty
+    #[serde(default)]
+    pub days: Vec<Days>,
+    /// local time
+    pub at: NaiveTime,
+    /// switch to the settings under `[profiles.NAME]` for the rest of the run
+    pub profile: Option<String>,
+    /// minutes; whatever was picked when unset
+    pub minutes: Option<i64>,
+    /// seconds between the notification and the start, to opt out in
+    #[serde(default = "Autostart::default_notice")]
+    pub notice: u64,
+}
+
+impl Autostart {
+    fn default_notice() -> u64 {
+        60
+    }
+
+    /// It was time for this after `since` and by `now`.
+    pub fn due(&self, since: DateTime<Local>, now: DateTime<Local>) -> bool {
+        let today = now.date_naive();
+        let on_day = Days::any(&self.days, today.weekday());
+        match Local.from_local_datetime(&today.and_time(self.at)).single() {
+            Some(at) => on_day && since < at && at <= now,
+            // skipped or doubled by a clock change
+            None => false,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Days {
+    Mon,
+    Tue,
+    Wed,
+    Thu,
+    Fri,
+    Sat,
+    Sun,
+    Weekdays,
+    Weekends,
+}
+
+impl Days {
+    /// `day` is one of `days`, or `days` is empty and so means all of them.
+    pub fn any(days: &[Days], day: Weekday) -> bool {
+        days.is_empty() || days.iter().any(|days| days.include(day))
+    }
+
+    fn include(self, day: Weekday) -> bool {
+        let weekend = matches!(day, Weekday::Sat | Weekday::Sun);
+        match self {
+            Days::Weekdays => !weekend,
+            Days::Weekends => weekend,
+            Days::Mon => day == Weekday::Mon,
+            Days::Tue => day == Weekday::Tue,
+            Days::Wed => day == Weekday::Wed,
+            Days::Thu => day == Weekday::Thu,
+            Days::Fri => day == Weekday::Fri,
+            Days::Sat => day == Weekday::Sat,
+            Days::Sun => day == Weekday::Sun,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Journal {
+    /// one Markdown file per day, `2026-10-14.md`; `journal` in the data
+    /// directory by default
+    pub dir: Option<PathBuf>,
+    /// asked when a work session completes
+    pub question: String,
+}
+
+impl Default for Journal {
+    fn default() -> Self {
+        Journal {
+            dir: None,
+            question: "What did you accomplish?".to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct Obsidian {
+    pub vault: PathBuf,
+    /// where the daily notes go within the vault, as set up in Obsidian
+    #[serde(default)]
+    pub folder: PathBuf,
+    /// daily note names, strftime style; Obsidian's `YYYY-MM-DD` is the default
+    #[serde(default = "Obsidian::default_format")]
+    pub format: String,
+    /// the line added, with `{start}`, `{end}`, `{date}`, `{duration}`,
+    /// `{tags}`, `{note}` and `{phase}`
+    #[serde(default = "Obsidian::default_line")]
+    pub line: String,
+    /// after every work session; otherwise only with `pom obsidian`
+    #[serde(default = "Obsidian::default_automatic")]
+    pub automatic: bool,
+}
+
+impl Obsidian {
+    fn default_format() -> String {
+        "%Y-%m-%d".to_string()
+    }
+
+    fn default_line() -> String {
+        "- \u{1f345} {start}\u{2013}{end} {tags} {note}".to_string()
+    }
+
+    fn default_automatic() -> bool {
+        true
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Security {
+    /// plain connection upgraded with STARTTLS, port 587
+    #[default]
+    Starttls,
+    /// TLS from the start, port 465
+    Tls,
+    /// unencrypted, port 25; only for a relay on localhost
+    None,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn goals_follow_the_days() {
+        let table: toml::Table = toml::from_str(
+            "[goal]\ndaily = 8\n\
+             [days]\nfri = \"short\"\nweekends = \"off\"\n\
+             [profiles.short.goal]\ndaily = 4\n\
+             [profiles.off.goal]\ndaily = 0\n",
+        )
+        .unwrap();
+        assert_eq!(goals(&table).unwrap(), [8, 8, 8, 8, 4, 0, 0]);
+    }
+}
@@ -0,0 +1,88 @@
+use std::sync::{
+    mpsc::{self, Receiver, Sender, TryIter},
+    Arc, Mutex,
+};
+
+use crate::{
+    app::{Command, Phase, Snapshot},
+    template::Context,
+};
+
+/// Lets things outside the UI (D-Bus, pipes, sockets) drive the timer:
+/// they send commands in and read the state the event loop publishes.
+pub struct Remote {
+    commands: Receiver<Command>,
+    handle: Handle,
+}
+
+/// The sending end of a [`Remote`], cheap to clone into other threads.
+#[derive(Clone)]
+pub struct Handle {
+    commands: Sender<Command>,
+    state: Arc<Mutex<Snapshot>>,
+    /// what `pom status` would print, for attached poms
+    context: Arc<Mutex<Option<Context>>>,
+}
+
+impl Remote {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Remote {
+            commands: rx,
+            handle: Handle {
+                commands: tx,
+                state: Arc::new(Mutex::new(Snapshot {
+                    state: "idle",
+                    phase: Phase::Work,
+                    remaining: 0,
+                    round: 0,
+                })),
+                context: Arc::new(Mutex::new(None)),
+            },
+        }
+    }
+
+    pub fn handle(&self) -> Handle {
+        self.handle.clone()
+    }
+
+    /// Commands received since the last call.
+    pub fn commands(&self) -> TryIter<'_, Command> {
+        self.commands.try_iter()
+    }
+
+    pub fn publish(&self, snapshot: Snapshot) {
+        if let Ok(mut state) = self.handle.state.lock() {
+            *state = snapshot;
+        }
+    }
+
+    pub fn share(&self, context: Context) {
+        if let Ok(mut shared) = self.handle.context.lock() {
+            *shared = Some(context);
+        }
+    }
+}
+
+impl Handle {
+    pub fn send(&self, command: Command) {
+        // the receiver only goes away when the app is exiting anyway
+        let _ = self.commands.send(command);
+    }
+
+    pub fn state(&self) -> Option<Snapshot> {
+        self.state.lock().ok().map(|state| state.clone())
+    }
+
+    pub fn context(&self) -> Option<Context> {
+        self.context.lock().ok().and_then(|context| context.clone())
+    }
+
+    /// Like [`Handle::context`], but never waits for the lock.
+    pub fn try_context(&self) -> Option<Context> {
+        self.context
+            .try_lock()
+            .ok()
+            .and_then(|context| context.clone())
+    }
+}
@@ -0,0 +1,103 @@
+//! A report when pom panics: the terminal goes back to the shell first so
+//! the message can be read, then the panic, a backtrace, the timer's state
+//! and the version go into `crashes/` beside the state, and the path is
+//! printed, for attaching to a bug report.
+
+use chrono::Local;
+use std::{
+    backtrace::Backtrace,
+    env, fs,
+    io::{self, Write},
+    panic,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        OnceLock,
+    },
+    thread,
+};
+
+use crate::{control::Handle, platform};
+
+/// The alternate screen and raw mode are on.
+static TERMINAL: AtomicBool = AtomicBool::new(false);
+
+/// Where the timer's state comes from, once the timer runs.
+static TIMER: OnceLock<Handle> = OnceLock::new();
+
+pub fn install() {
+    panic::set_hook(Box::new(|info| {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "(no message)".to_string());
+        let location = info
+            .location()
+            .map_or_else(String::new, |location| format!(" at {}", location));
+        let thread = thread::current();
+        let name = thread.name().unwrap_or("unnamed");
+        let panicked = format!("thread '{}' panicked{}:\n{}", name, location, message);
+
+        // another thread's panic leaves the UI running
+        let main = name == "main";
+        if main && TERMINAL.swap(false, Ordering::Relaxed) {
+            let _ = crate::restore_terminal();
+            let _ = crossterm::execute!(io::stdout(), crossterm::cursor::Show);
+        }
+
+        let report = report(&panicked);
+        match save(&report) {
+            Ok(path) if main => eprintln!(
+                "pom crashed, sorry.\n{}\n\nThe crash report is in {}",
+                panicked,
+                path.display()
+            ),
+            Ok(path) => log::error!("{}, crash report in {}", panicked, path.display()),
+            Err(_) => eprintln!("{}", report),
+        }
+    }));
+}
+
+/// The terminal was taken over, or given back.
+pub fn terminal(taken: bool) {
+    TERMINAL.store(taken, Ordering::Relaxed);
+}
+
+/// The timer runs; its state goes into reports from now on.
+pub fn watch(handle: Handle) {
+    let _ = TIMER.set(handle);
+}
+
+fn report(panicked: &str) -> String {
+    // the panicking thread may be the one holding the state
+    let state = TIMER
+        .get()
+        .and_then(Handle::try_context)
+        .and_then(|context| serde_json::to_string(&context).ok())
+        .unwrap_or_else(|| "(not running)".to_string());
+    format!(
+        "pom {} on {} {}, {}\n\n{}\n\nstate: {}\n\nbacktrace:\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        env::consts::OS,
+        env::consts::ARCH,
+        Local::now().format("%Y-%m-%d %H:%M:%S %:z"),
+        panicked,
+        state,
+        Backtrace::force_capture()
+    )
+}
+
+fn save(report: &str) -> io::Result<PathBuf> {
+    let dir = platform::project_dirs()
+        .map(|dirs| dirs.data_local_dir().join("crashes"))
+        .unwrap_or_else(env::temp_dir);
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!(
+        "crash-{}.txt",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    fs::File::create(&path)?.write_all(report.as_bytes())?;
+    Ok(path)
+}
@@ -0,0 +1,95 @@
+use std::{thread, time::Duration};
+use zbus::{blocking::connection, interface, object_server::SignalEmitter};
+
+use crate::{app::Command, control::Handle};
+
+const NAME: &str = "org.pomtui.Timer";
+const PATH: &str = "/org/pomtui/Timer";
+
+struct Timer {
+    handle: Handle,
+}
+
+#[interface(name = "org.pomtui.Timer")]
+impl Timer {
+    /// Start a work session, or resume a paused one.
+    fn start(&self) {
+        self.handle.send(Command::Start);
+    }
+
+    fn pause(&self) {
+        self.handle.send(Command::Pause);
+    }
+
+    /// End the current phase early and move on to the next.
+    fn skip(&self) {
+        self.handle.send(Command::Skip);
+    }
+
+    /// (phase, state, seconds remaining, completed work sessions)
+    fn get_state(&self) -> (String, String, i64, u32) {
+        match self.handle.state() {
+            Some(s) => (
+                s.phase.name().to_string(),
+                s.state.to_string(),
+                s.remaining,
+                s.round,
+            ),
+            None => (String::new(), "idle".to_string(), 0, 0),
+        }
+    }
+
+    #[zbus(signal)]
+    async fn phase_changed(
+        emitter: &SignalEmitter<'_>,
+        phase: &str,
+        round: u32,
+    ) -> zbus::Result<()>;
+}
+
+/// Serve the timer on the session bus from a background thread.
+///
+/// There is not always a session bus (ssh, containers, other OSes), so
+/// failing to connect is only worth a log line.
+pub fn serve(handle: Handle) {
+    thread::spawn(move || {
+        let timer = Timer {
+            handle: handle.clone(),
+        };
+        let conn = match connection::Builder::session()
+            .and_then(|b| b.name(NAME))
+            .and_then(|b| b.serve_at(PATH, timer))
+            .and_then(|b| b.build())
+        {
+            Ok(conn) => conn,
+            Err(err) => {
+                log::info!("D-Bus service unavailable: {}", err);
+                return;
+            }
+        };
+        let Ok(iface) = conn.object_server().interface::<_, Timer>(PATH) else {
+            return;
+        };
+
+        // method calls are handled by zbus' own executor; this thread only
+        // watches for phase changes to signal them
+        // compare rounds too, so work -> break -> work within one poll
+        // still counts as a change
+        let mut last = handle.state().map(|s| (s.phase, s.round));
+        loop {
+            thread::sleep(Duration::from_millis(250));
+            let Some(state) = handle.state() else {
+                return;
+            };
+
+            if last != Some((state.phase, state.round)) {
+                last = Some((state.phase, state.round));
+                let signal =
+                    Timer::phase_changed(iface.signal_emitter(), state.phase.name(), state.round);
+                if let Err(err) = zbus::block_on(signal) {
+                    log::warn!("could not emit PhaseChanged: {}", err);
+                }
+            }
+        }
+    });
+}
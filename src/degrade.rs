@@ -0,0 +1,149 @@
+//! Making a drawn frame fit a terminal that can't show all of it: ASCII for
+//! the line and block characters, fewer colors, no blinking.
+
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier},
+    widgets::Widget,
+};
+
+use crate::platform::{Capabilities, Colors};
+
+/// xterm's defaults for the 16 ANSI colors.
+const BASIC: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+/// Steps of the 6x6x6 color cube in the 256 color palette.
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Drawn over the whole frame last, rewriting what the terminal lacks.
+#[derive(Clone, Copy)]
+pub struct Degrade {
+    unicode: bool,
+    colors: Colors,
+    blink: bool,
+}
+
+impl Degrade {
+    /// Nothing when the terminal takes everything as it is.
+    pub fn new(caps: &Capabilities) -> Option<Self> {
+        let degrade = Degrade {
+            unicode: caps.unicode,
+            colors: caps.colors,
+            blink: caps.blink,
+        };
+        (!caps.unicode || caps.colors < Colors::True || !caps.blink).then_some(degrade)
+    }
+
+    pub fn unicode(&self) -> bool {
+        self.unicode
+    }
+
+    /// `color`, or the nearest the terminal has.
+    pub fn color(&self, color: Color) -> Color {
+        match (self.colors, color) {
+            (Colors::Mono, _) => Color::Reset,
+            (Colors::True, _) => color,
+            (Colors::Indexed, Color::Rgb(r, g, b)) => Color::Indexed(indexed((r, g, b))),
+            (Colors::Basic, Color::Rgb(r, g, b)) => basic((r, g, b)),
+            (Colors::Basic, Color::Indexed(i)) => basic(rgb(i)),
+            _ => color,
+        }
+    }
+}
+
+impl Widget for Degrade {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                let cell = buf.get_mut(x, y);
+                if !self.unicode && !cell.symbol.is_ascii() {
+                    let c = cell.symbol.chars().next().map_or(' ', ascii);
+                    cell.set_char(c);
+                }
+                if !self.blink {
+                    cell.modifier
+                        .remove(Modifier::SLOW_BLINK | Modifier::RAPID_BLINK);
+                }
+                // without colors a filled background still shows, reversed
+                if self.colors == Colors::Mono && cell.bg != Color::Reset {
+                    cell.modifier.insert(Modifier::REVERSED);
+                }
+                cell.fg = self.color(cell.fg);
+                cell.bg = self.color(cell.bg);
+            }
+        }
+    }
+}
+
+/// The nearest ASCII look-alike: lines and corners, block fills, a few
+/// punctuation marks, and `?` for the rest.
+fn ascii(c: char) -> char {
+    match c {
+        '─' | '━' | '═' | '┄' | '┅' | '╌' | '╍' | '—' | '–' => '-',
+        '│' | '┃' | '║' | '┆' | '┇' | '╎' | '╏' => '|',
+        '\u{2500}'..='\u{257f}' => '+',
+        '\u{2580}'..='\u{259f}' => '#',
+        '•' | '·' | '●' | '○' => '*',
+        '…' => '.',
+        '\u{2018}' | '\u{2019}' => '\'',
+        '\u{201c}' | '\u{201d}' => '"',
+        c if c.is_ascii() => c,
+        _ => '?',
+    }
+}
+
+/// A 256 palette entry as RGB.
+fn rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => BASIC[index as usize].1,
+        16..=231 => {
+            let i = index - 16;
+            (
+                CUBE[(i / 36) as usize],
+                CUBE[(i / 6 % 6) as usize],
+                CUBE[(i % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (index - 232);
+            (level, level, level)
+        }
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> i32 {
+    let d = |x: u8, y: u8| (i32::from(x) - i32::from(y)).pow(2);
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+fn basic(color: (u8, u8, u8)) -> Color {
+    BASIC
+        .iter()
+        .min_by_key(|(_, rgb)| distance(color, *rgb))
+        .map_or(Color::Reset, |(basic, _)| *basic)
+}
+
+/// The nearest of the cube and the grays; the first 16 are left out since
+/// terminals change them with the theme.
+fn indexed(color: (u8, u8, u8)) -> u8 {
+    (16..=255)
+        .min_by_key(|&index| distance(color, rgb(index)))
+        .unwrap_or(16)
+}
@@ -0,0 +1,115 @@
+//! What the debug screen (f12) shows: how well the event loop keeps up,
+//! for bugs like a timer drifting on one machine only.
+
+use chrono::{DateTime, Utc};
+use std::time::{Duration, Instant};
+
+use crate::integrations::Failure;
+
+pub struct Diagnostics {
+    /// both clocks at startup, to tell how far apart they went
+    started: (Instant, DateTime<Utc>),
+    draws: u64,
+    /// how long the last frame took to draw, and the longest
+    draw: (Duration, Duration),
+    /// the longest the loop woke up after a frame was due
+    late: Duration,
+    /// from an input event to the frame showing it: the last and longest
+    latency: (Duration, Duration),
+    /// an input event waiting for its frame
+    pending: Option<Instant>,
+    /// seconds the timer jumped over between two looks at it
+    dropped: i64,
+    /// the integrations' latest, copied in while the screen is open
+    pub failures: Vec<Failure>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Diagnostics {
+            started: (Instant::now(), Utc::now()),
+            draws: 0,
+            draw: (Duration::ZERO, Duration::ZERO),
+            late: Duration::ZERO,
+            latency: (Duration::ZERO, Duration::ZERO),
+            pending: None,
+            dropped: 0,
+            failures: Vec::new(),
+        }
+    }
+
+    /// A frame drawn from `start` until now; `due` when it was drawn for
+    /// the display's pace rather than because something happened.
+    pub fn drawn(&mut self, start: Instant, due: Option<Instant>) {
+        let now = Instant::now();
+        self.draws += 1;
+        let took = now - start;
+        self.draw = (took, self.draw.1.max(took));
+        if let Some(due) = due {
+            self.late = self.late.max(start.saturating_duration_since(due));
+        }
+        if let Some(since) = self.pending.take() {
+            let latency = now - since;
+            self.latency = (latency, self.latency.1.max(latency));
+        }
+    }
+
+    pub fn input(&mut self) {
+        self.pending.get_or_insert_with(Instant::now);
+    }
+
+    /// The timer's elapsed seconds before and after an update.
+    pub fn ticked(&mut self, before: i64, after: i64) {
+        if after > before + 1 {
+            self.dropped += after - before - 1;
+        }
+    }
+
+    /// How far the wall clock went from the monotonic one since startup:
+    /// a suspend, an NTP step, a clock set by hand.
+    fn drift(&self) -> chrono::Duration {
+        let (instant, wall) = self.started;
+        let monotonic = chrono::Duration::from_std(instant.elapsed()).unwrap_or_default();
+        (Utc::now() - wall) - monotonic
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        let ms = |duration: Duration| format!("{:.1}ms", duration.as_secs_f64() * 1000.0);
+        let up = self.started.0.elapsed().as_secs_f64().max(1.0);
+        let mut lines = vec![
+            format!(
+                "draws            {} ({:.1}/s)",
+                self.draws,
+                self.draws as f64 / up
+            ),
+            format!(
+                "draw time        {} last, {} max",
+                ms(self.draw.0),
+                ms(self.draw.1)
+            ),
+            format!("late frames      {} max", ms(self.late)),
+            format!(
+                "event latency    {} last, {} max",
+                ms(self.latency.0),
+                ms(self.latency.1)
+            ),
+            format!("dropped ticks    {}", self.dropped),
+            format!(
+                "clock drift      {:+}ms wall vs monotonic",
+                self.drift().num_milliseconds()
+            ),
+            String::new(),
+        ];
+        if self.failures.is_empty() {
+            lines.push("no integration errors".to_string());
+        }
+        for failure in &self.failures {
+            lines.push(format!(
+                "{} {}",
+                failure.at.format("%H:%M:%S"),
+                failure.message
+            ));
+        }
+        lines
+    }
+}
@@ -0,0 +1,58 @@
+use lettre::{
+    message::Mailbox,
+    transport::smtp::{authentication::Credentials, SmtpTransport},
+    Message, Transport,
+};
+
+use crate::{
+    config::{self, Security},
+    error::{PomError, Result},
+    platform,
+};
+
+/// Send a plain text mail through the configured SMTP server.
+pub fn send(config: &config::Email, subject: &str, body: String) -> Result<()> {
+    let to = mailbox(&config.to)?;
+    let from = match &config.from {
+        Some(from) => mailbox(from)?,
+        None => to.clone(),
+    };
+    let message = Message::builder()
+        .from(from)
+        .to(to)
+        .subject(subject)
+        .body(body)
+        .map_err(|err| PomError::Email(err.into()))?;
+
+    let builder = match config.security {
+        Security::Starttls => SmtpTransport::starttls_relay(&config.server),
+        Security::Tls => SmtpTransport::relay(&config.server),
+        Security::None => Ok(SmtpTransport::builder_dangerous(&config.server)),
+    }
+    .map_err(|err| PomError::Email(err.into()))?;
+    let mut builder = match config.port {
+        Some(port) => builder.port(port),
+        None => builder,
+    };
+
+    if let Some(username) = &config.username {
+        let password = match &config.password_command {
+            Some(command) => platform::run_secret_command(command)?,
+            None => config.password.clone().unwrap_or_default(),
+        };
+        builder = builder.credentials(Credentials::new(username.clone(), password));
+    }
+
+    builder
+        .build()
+        .send(&message)
+        .map_err(|err| PomError::Email(err.into()))?;
+    Ok(())
+}
+
+fn mailbox(address: &str) -> Result<Mailbox> {
+    address.parse().map_err(|source| PomError::EmailAddress {
+        address: address.to_string(),
+        source,
+    })
+}
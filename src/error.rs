@@ -0,0 +1,293 @@
+use std::{error::Error, fmt::Write, io, path::PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum PomError {
+    #[error("could not install signal handlers")]
+    Signals(#[source] io::Error),
+
+    #[error("could not read config file {}", path.display())]
+    ConfigRead {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("invalid config file {}", path.display())]
+    ConfigParse {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+
+    #[error("no [profiles.{name}] in the config")]
+    UnknownProfile { name: String },
+
+    #[error("invalid key binding `{sequence}` for {action}")]
+    KeyBinding {
+        action: &'static str,
+        sequence: String,
+    },
+
+    #[error("could not open log file {}", path.display())]
+    LogFile {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("log filter `{filter}`: {reason}")]
+    LogFilter { filter: String, reason: String },
+
+    #[error("could not lock {}", path.display())]
+    Lock {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("another pom is already running with this data directory")]
+    AlreadyRunning,
+
+    #[error("no pom is running with this data directory")]
+    NotRunning,
+
+    #[error("pom {pid} did not quit, so it can't be taken over")]
+    TakeOver { pid: u32 },
+
+    #[error("could not read state file {}", path.display())]
+    StateRead {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("could not read history {}", path.display())]
+    HistoryRead {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("invalid history {}, line {line}", path.display())]
+    HistoryParse {
+        path: PathBuf,
+        line: usize,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    #[error("could not read backup {}", path.display())]
+    BackupRead {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("invalid backup {}", path.display())]
+    BackupParse {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    #[error("backup {} needs pom {version} or newer", path.display())]
+    BackupVersion { path: PathBuf, version: String },
+
+    #[error("backup {} is encrypted, set passphrase_command under [history]", path.display())]
+    BackupEncrypted { path: PathBuf },
+
+    #[error("wrong passphrase for backup {}", path.display())]
+    BackupPassphrase { path: PathBuf },
+
+    #[error("could not decrypt backup {}", path.display())]
+    BackupDecrypt { path: PathBuf },
+
+    #[error("could not encrypt backup {}", path.display())]
+    BackupEncrypt { path: PathBuf },
+
+    #[error("could not write {}", path.display())]
+    BackupWrite {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("pom is running, quit it before {doing}")]
+    Running { doing: &'static str },
+
+    #[error("could not write history {}", path.display())]
+    HistoryWrite {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("history database {} failed", path.display())]
+    Sqlite {
+        path: PathBuf,
+        #[source]
+        source: rusqlite::Error,
+    },
+
+    #[error("history database {} has schema {version}, made by a newer pom", path.display())]
+    HistorySchema { path: PathBuf, version: i32 },
+
+    #[error("invalid session in history {}, {at}: {reason}", path.display())]
+    HistoryInvalid {
+        path: PathBuf,
+        at: String,
+        reason: &'static str,
+    },
+
+    #[error("history {} has a session of format {version} at line {line}, made by a newer pom", path.display())]
+    HistoryFormat {
+        path: PathBuf,
+        line: usize,
+        version: u64,
+    },
+
+    #[error("history {} is encrypted, set passphrase_command under [history]", path.display())]
+    HistoryEncrypted { path: PathBuf },
+
+    #[error("wrong passphrase for history {}", path.display())]
+    WrongPassphrase { path: PathBuf },
+
+    #[error("could not encrypt history {}", path.display())]
+    HistoryEncrypt { path: PathBuf },
+
+    #[error("could not decrypt history {}, line {line}", path.display())]
+    HistoryDecrypt { path: PathBuf, line: usize },
+
+    #[error("only the jsonl history can be encrypted")]
+    EncryptionUnsupported,
+
+    #[error("--from and --to must be different backends")]
+    MigrateSame,
+
+    #[error("could not read {}", path.display())]
+    ImportRead {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("{} is not a {format} export: {reason}", path.display())]
+    ImportFormat {
+        path: PathBuf,
+        format: &'static str,
+        reason: String,
+    },
+
+    #[error("the logged session would end in the future, at {at}")]
+    LogFuture { at: String },
+
+    #[error("the logged session overlaps the {phase} session from {at}")]
+    LogOverlap { phase: &'static str, at: String },
+
+    #[error("could not read report template {}", path.display())]
+    TemplateRead {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("could not render report")]
+    Report(#[source] minijinja::Error),
+
+    #[error("`--email` needs an [email] section in the config")]
+    NoEmail,
+
+    #[error("no hourly rates under [billing.rates] in the config")]
+    NoRates,
+
+    #[error("invalid email address `{address}`")]
+    EmailAddress {
+        address: String,
+        #[source]
+        source: lettre::address::AddressError,
+    },
+
+    #[error("could not send email")]
+    Email(#[source] Box<dyn Error + Send + Sync>),
+
+    #[error("could not write journal {}", path.display())]
+    JournalWrite {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("could not write daily note {}", path.display())]
+    ObsidianWrite {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("`pom obsidian` needs an [obsidian] section in the config")]
+    NoObsidian,
+
+    #[error("`{command}` failed")]
+    SecretCommand {
+        command: String,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error(
+        "the history in {} has unencrypted git commits, which encrypting it would leave behind; \
+         move .git aside first",
+        dir.display()
+    )]
+    GitPlaintext { dir: PathBuf },
+
+    #[error("`git {args}` in {} failed", dir.display())]
+    Git {
+        args: String,
+        dir: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("HTTP request failed")]
+    Http(#[source] ureq::Error),
+
+    #[error("could not keep unsent Notion rows in {}", path.display())]
+    NotionQueue {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[cfg(feature = "plugins")]
+    #[error("plugin {}: {message}", path.display())]
+    Plugin { path: PathBuf, message: String },
+
+    #[error("could not send notification")]
+    Notify(#[source] io::Error),
+
+    #[error("could not speak")]
+    Speech(#[source] io::Error),
+
+    #[error("could not play sound")]
+    Audio(#[source] io::Error),
+
+    #[error("terminal I/O failed")]
+    Terminal(#[from] io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, PomError>;
+
+impl PomError {
+    /// The error plus every underlying cause, one per line.
+    pub fn report(&self) -> String {
+        let mut report = self.to_string();
+        let mut source = self.source();
+        while let Some(cause) = source {
+            let _ = write!(report, "\n  caused by: {}", cause);
+            source = cause.source();
+        }
+        report
+    }
+}
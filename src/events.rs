@@ -0,0 +1,175 @@
+//! The event log, `events.jsonl` beside the history: everything that
+//! happened to the timer and to the history, one event per line, never
+//! rewritten. The history is what the log adds up to, so it can be rebuilt
+//! from the log with `pom rebuild-stats` after a bad sync, a botched edit
+//! or a bug.
+//!
+//! A log started on a history that was already there begins with a
+//! `recorded` event for each session in it.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs};
+
+use crate::{
+    app::{self, Phase, Session},
+    config::Config,
+    error::{PomError, Result},
+    history::Store,
+    instance,
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum Entry {
+    Started {
+        at: DateTime<Utc>,
+        phase: Phase,
+        /// seconds
+        planned: i64,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tags: Vec<String>,
+    },
+    Paused {
+        at: DateTime<Utc>,
+    },
+    Resumed {
+        at: DateTime<Utc>,
+    },
+    Ended {
+        at: DateTime<Utc>,
+        phase: Phase,
+        completed: bool,
+    },
+    /// the sessions from now on get these tags
+    Tagged {
+        at: DateTime<Utc>,
+        tags: Vec<String>,
+    },
+    /// a session added to the history: timed, logged or imported
+    Recorded {
+        at: DateTime<Utc>,
+        session: Session,
+    },
+    /// a recorded session corrected, by hand or with a note
+    Edited {
+        at: DateTime<Utc>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        before: Option<Session>,
+        after: Session,
+    },
+    Deleted {
+        at: DateTime<Utc>,
+        session: Session,
+    },
+}
+
+impl Entry {
+    /// A timer event as it goes in the log; cues don't.
+    pub fn of(event: &app::Event, tags: &[String]) -> Option<Entry> {
+        Some(match *event {
+            app::Event::PhaseStarted { phase, planned, at } => Entry::Started {
+                at,
+                phase,
+                planned,
+                tags: if phase == Phase::Work {
+                    tags.to_vec()
+                } else {
+                    Vec::new()
+                },
+            },
+            app::Event::PhaseEnded {
+                phase,
+                completed,
+                at,
+            } => Entry::Ended {
+                at,
+                phase,
+                completed,
+            },
+            app::Event::Paused { at } => Entry::Paused { at },
+            app::Event::Resumed { at } => Entry::Resumed { at },
+            app::Event::Cue { .. } => return None,
+        })
+    }
+
+    pub fn at(&self) -> DateTime<Utc> {
+        match *self {
+            Entry::Started { at, .. }
+            | Entry::Paused { at }
+            | Entry::Resumed { at }
+            | Entry::Ended { at, .. }
+            | Entry::Tagged { at, .. }
+            | Entry::Recorded { at, .. }
+            | Entry::Edited { at, .. }
+            | Entry::Deleted { at, .. } => at,
+        }
+    }
+}
+
+/// The history the log adds up to, oldest first.
+pub fn replay(entries: &[Entry]) -> Vec<Session> {
+    let key = |session: &Session| (session.started, session.phase.name());
+    let mut sessions = BTreeMap::new();
+    for entry in entries {
+        match entry {
+            // the same session twice, e.g. imported again, counts once
+            Entry::Recorded { session, .. } => {
+                sessions.entry(key(session)).or_insert(session.clone());
+            }
+            Entry::Edited { after, .. } => {
+                sessions.insert(key(after), after.clone());
+            }
+            Entry::Deleted { session, .. } => {
+                sessions.remove(&key(session));
+            }
+            _ => {}
+        }
+    }
+    sessions.into_values().collect()
+}
+
+/// `pom rebuild-stats`: put the history the log adds up to in place of the
+/// recorded one, and say what that changed.
+pub fn rebuild() -> Result<()> {
+    let _lock = instance::hold("rebuilding the history")?;
+    let config = Config::load()?;
+    let store = Store::configured(&config.history)?;
+    let entries = store.events()?;
+    let rebuilt = replay(&entries);
+    let recorded = store.load()?;
+
+    let key = |session: &Session| (session.started, session.phase.name());
+    let before: BTreeMap<_, _> = recorded.iter().map(|s| (key(s), s)).collect();
+    let after: BTreeMap<_, _> = rebuilt.iter().map(|s| (key(s), s)).collect();
+    let added = after.keys().filter(|k| !before.contains_key(k)).count();
+    let removed = before.keys().filter(|k| !after.contains_key(k)).count();
+    let changed = after
+        .iter()
+        .filter(|(k, s)| before.get(k).is_some_and(|b| b != *s))
+        .count();
+
+    if added + removed + changed == 0 && recorded.len() == rebuilt.len() {
+        println!(
+            "the history matches its {} events, {} sessions",
+            entries.len(),
+            rebuilt.len()
+        );
+        return Ok(());
+    }
+    if let Some((tmp, target)) = store.stage(&rebuilt)? {
+        fs::rename(&tmp, &target).map_err(|source| PomError::HistoryWrite {
+            path: target,
+            source,
+        })?;
+    }
+    println!(
+        "history rebuilt from {} events: {} sessions, {} added, {} removed, {} changed",
+        entries.len(),
+        rebuilt.len(),
+        added,
+        removed,
+        changed
+    );
+    Ok(())
+}
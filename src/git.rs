@@ -0,0 +1,142 @@
+//! The history in a git repository, for its versions and, pushed, a copy
+//! somewhere else. The data directory becomes the repository; only the
+//! history and the event log go in it.
+//!
+//! There is one commit a day: the first time the history changes on a new
+//! day, what changed before is committed, so each commit ends with a whole
+//! day and nothing is ever amended or force-pushed.
+
+use chrono::{DateTime, Local};
+use std::{
+    io,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+};
+
+use crate::{
+    config,
+    error::{PomError, Result},
+};
+
+/// Commit `paths` in `dir`, unless they were already committed today.
+pub fn commit(config: &config::Git, dir: &Path, paths: &[PathBuf]) -> Result<()> {
+    let paths: Vec<&PathBuf> = paths.iter().filter(|path| path.exists()).collect();
+    if paths.is_empty() {
+        return Ok(());
+    }
+    if !dir.join(".git").exists() {
+        git(dir, &["init", "--quiet"])?;
+    }
+    let today = Local::now().format("%Y-%m-%d").to_string();
+    // fails before the first commit
+    if git(
+        dir,
+        &["log", "-1", "--format=%cd", "--date=format-local:%Y-%m-%d"],
+    )
+    .is_ok_and(|last| last.trim() == today)
+    {
+        return Ok(());
+    }
+
+    let mut add = vec!["add", "--all", "--"];
+    add.extend(paths.iter().filter_map(|path| path.to_str()));
+    git(dir, &add)?;
+    if git(dir, &["diff", "--cached", "--name-only"])?
+        .trim()
+        .is_empty()
+    {
+        return Ok(());
+    }
+    // the day the last of it changed
+    let day = paths
+        .iter()
+        .filter_map(|path| path.metadata().and_then(|meta| meta.modified()).ok())
+        .max()
+        .map_or(today, |at| {
+            DateTime::<Local>::from(at).format("%Y-%m-%d").to_string()
+        });
+    let message = format!("history up to {}", day);
+    let mut commit = vec!["commit", "--quiet", "-m", &message];
+    // without an identity of the user's own, pom's
+    if git(dir, &["config", "user.email"]).is_err() {
+        commit.splice(
+            0..0,
+            ["-c", "user.name=pom", "-c", "user.email=pom@localhost"],
+        );
+    }
+    git(dir, &commit)?;
+
+    if config.push {
+        push(dir, &config.remote)?;
+    }
+    Ok(())
+}
+
+/// `dir` is a repository with something committed.
+pub fn has_commits(dir: &Path) -> bool {
+    dir.join(".git").exists() && git(dir, &["rev-parse", "--verify", "--quiet", "HEAD"]).is_ok()
+}
+
+/// In the background, the network can take its time; the push carries on
+/// after a quick `pom log` has exited.
+fn push(dir: &Path, remote: &str) -> Result<()> {
+    let args = ["push", "--quiet", remote, "HEAD"];
+    let failed = |source| PomError::Git {
+        args: args.join(" "),
+        dir: dir.to_path_buf(),
+        source,
+    };
+    let child = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(failed)?;
+    let (dir, args) = (dir.to_path_buf(), args.join(" "));
+    thread::spawn(move || match child.wait_with_output() {
+        Ok(output) if output.status.success() => {}
+        result => {
+            let reason = match result {
+                Ok(output) => reason(&output.stderr),
+                Err(err) => err.to_string(),
+            };
+            let err = PomError::Git {
+                args,
+                dir,
+                source: io::Error::other(reason),
+            };
+            log::warn!("{}", err.report());
+        }
+    });
+    Ok(())
+}
+
+/// The `fatal:` line of what git printed on failing.
+fn reason(stderr: &[u8]) -> String {
+    let stderr = String::from_utf8_lossy(stderr);
+    let line = stderr.lines().rfind(|line| !line.trim().is_empty());
+    line.unwrap_or_default().to_string()
+}
+
+/// Run git in `dir`, returning what it printed.
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let failed = |source| PomError::Git {
+        args: args.join(" "),
+        dir: dir.to_path_buf(),
+        source,
+    };
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(failed)?;
+    if !output.status.success() {
+        return Err(failed(io::Error::other(reason(&output.stderr))));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,167 @@
+//! The daily goal, and the streak of days in a row that met theirs. Each
+//! day of the week may have its own goal, by way of the profile `[days]`
+//! names for it; a day without one neither adds to a streak nor breaks it.
+
+use chrono::{Datelike, NaiveDate};
+use std::collections::HashMap;
+
+use crate::{
+    app::{Phase, Session},
+    config::Config,
+    report,
+};
+
+/// Today's work sessions against today's goal, and the days before it
+/// that met theirs, kept up as more end.
+pub struct Streak {
+    pub day: NaiveDate,
+    /// work sessions that ran out on `day`
+    pub done: u32,
+    /// `day`'s goal; 0 sets none
+    pub goal: u32,
+    /// days in a row up to `day` that met their goal
+    before: u32,
+}
+
+impl Streak {
+    /// Where `sessions` leave things on `today`, with `goal` for it.
+    pub fn new(sessions: &[Session], today: NaiveDate, goal: u32, config: &Config) -> Self {
+        let mut done: HashMap<NaiveDate, u32> = HashMap::new();
+        for session in sessions.iter().filter(|s| s.phase == Phase::Work && s.completed) {
+            *done.entry(report::local_date(session, config.day_starts_at)).or_default() += 1;
+        }
+        let first = done.keys().min().copied().unwrap_or(today);
+        let mut before = 0;
+        let mut day = today;
+        while let Some(prev) = day.pred_opt().filter(|prev| *prev >= first) {
+            day = prev;
+            match goal_on(config, day) {
+                0 => continue,
+                goal if done.get(&day).copied().unwrap_or(0) >= goal => before += 1,
+                _ => break,
+            }
+        }
+        Streak {
+            day: today,
+            done: done.get(&today).copied().unwrap_or(0),
+            goal,
+            before,
+        }
+    }
+
+    /// Count in a session that just ended on `day`.
+    pub fn add(&mut self, session: &Session, day: NaiveDate, config: &Config) {
+        if session.phase != Phase::Work || !session.completed {
+            return;
+        }
+        self.roll(day, config);
+        if day == self.day {
+            self.done += 1;
+        }
+    }
+
+    /// On to `day`: the one that ended counts if it met its goal, and any
+    /// with a goal but no sessions since break the streak.
+    pub fn roll(&mut self, day: NaiveDate, config: &Config) {
+        if day <= self.day {
+            return;
+        }
+        match self.goal {
+            0 => {}
+            goal if self.done >= goal => self.before += 1,
+            _ => self.before = 0,
+        }
+        let mut missed = self.day;
+        while let Some(next) = missed.succ_opt().filter(|next| *next < day) {
+            missed = next;
+            if goal_on(config, missed) > 0 {
+                self.before = 0;
+            }
+        }
+        self.day = day;
+        self.done = 0;
+        self.goal = goal_on(config, day);
+    }
+
+    /// Days in a row that met their goal, today's too once it has.
+    pub fn days(&self) -> u32 {
+        self.before + u32::from(self.goal > 0 && self.done >= self.goal)
+    }
+}
+
+fn goal_on(config: &Config, day: NaiveDate) -> u32 {
+    config.goals[day.weekday().num_days_from_monday() as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, NaiveTime, TimeZone, Utc};
+
+    /// A completed work session at noon on `day`.
+    fn on(day: NaiveDate) -> Session {
+        let started = Local
+            .from_local_datetime(&day.and_time(NaiveTime::from_hms_opt(12, 0, 0).unwrap()))
+            .unwrap()
+            .with_timezone(&Utc);
+        Session {
+            phase: Phase::Work,
+            started,
+            ended: started,
+            planned: 1500,
+            elapsed: 1500,
+            completed: true,
+            pauses: 0,
+            tags: Vec::new(),
+            note: None,
+            source: None,
+            profile: None,
+        }
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        // 2026-10-05 is a Monday
+        NaiveDate::from_ymd_opt(2026, 10, day).unwrap()
+    }
+
+    /// Two a day on weekdays, none on weekends.
+    fn config() -> Config {
+        Config {
+            goals: [2, 2, 2, 2, 2, 0, 0],
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn counts_the_days_that_met_their_goal() {
+        let config = config();
+        // Thursday and Friday met theirs, the weekend had none, and
+        // Wednesday fell short
+        let sessions: Vec<Session> = [7, 8, 8, 9, 9, 10]
+            .into_iter()
+            .map(|day| on(date(day)))
+            .collect();
+        let streak = Streak::new(&sessions, date(12), 2, &config);
+        assert_eq!(streak.days(), 2);
+        assert_eq!(streak.done, 0);
+    }
+
+    #[test]
+    fn keeps_up_as_days_go_by() {
+        let config = config();
+        let mut streak = Streak::new(&[on(date(5)), on(date(5))], date(5), 2, &config);
+        assert_eq!(streak.days(), 1);
+        // Tuesday falls one short, which ends the streak come Wednesday
+        streak.add(&on(date(6)), date(6), &config);
+        assert_eq!((streak.done, streak.days()), (1, 1));
+        streak.add(&on(date(7)), date(7), &config);
+        streak.add(&on(date(7)), date(7), &config);
+        assert_eq!((streak.done, streak.days()), (2, 1));
+        // a weekday gone by without sessions breaks it, the weekend doesn't
+        streak.roll(date(9), &config);
+        assert_eq!(streak.days(), 0);
+        let mut streak = Streak::new(&[on(date(9)), on(date(9))], date(10), 0, &config);
+        streak.roll(date(12), &config);
+        assert_eq!((streak.goal, streak.days()), (2, 1));
+    }
+}
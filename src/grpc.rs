@@ -0,0 +1,134 @@
+//! The attach socket's commands and state as a gRPC service, see
+//! `proto/pom.proto`, for strongly-typed clients generated from it: a phone
+//! app, a Stream Deck plugin. There is no authentication, so anyone who can
+//! reach the address can drive the timer; keep it on localhost or a trusted
+//! network.
+
+use std::{net::SocketAddr, pin::Pin, thread, time::Duration};
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::ReceiverStream, Stream};
+use tonic::{transport::Server, Request, Response, Status};
+
+use crate::{app, control::Handle, template::Context};
+
+mod proto {
+    tonic::include_proto!("pom.v1");
+}
+
+use proto::{
+    command::Kind,
+    control_server::{Control, ControlServer},
+    Command, GetStateRequest, Sent, State, WatchRequest,
+};
+
+/// How often a watching client hears about the timer.
+const INTERVAL: Duration = Duration::from_millis(250);
+
+struct Service {
+    handle: Handle,
+}
+
+impl From<Context> for State {
+    fn from(context: Context) -> Self {
+        State {
+            state: context.state,
+            phase: context.phase,
+            remaining: context.remaining,
+            round: context.round,
+            task: context.task,
+            count_today: context.count_today as u64,
+        }
+    }
+}
+
+impl Service {
+    fn state(&self) -> Result<State, Status> {
+        self.handle
+            .context()
+            .map(State::from)
+            .ok_or_else(|| Status::unavailable("the timer hasn't started up yet"))
+    }
+}
+
+#[tonic::async_trait]
+impl Control for Service {
+    async fn send(&self, request: Request<Command>) -> Result<Response<Sent>, Status> {
+        let command = request.into_inner();
+        let command = match command.kind() {
+            Kind::Start => app::Command::Start,
+            Kind::Pause => app::Command::Pause,
+            Kind::Toggle => app::Command::Toggle,
+            Kind::Skip => app::Command::Skip,
+            Kind::Add if command.seconds.abs() > app::MAX_ADD => {
+                return Err(Status::invalid_argument("add takes at most a day"))
+            }
+            Kind::Add => app::Command::Add(command.seconds),
+            Kind::Reset => app::Command::Reset,
+            Kind::Unspecified => return Err(Status::invalid_argument("no command kind")),
+        };
+        self.handle.send(command);
+        Ok(Response::new(Sent {}))
+    }
+
+    async fn get_state(
+        &self,
+        _request: Request<GetStateRequest>,
+    ) -> Result<Response<State>, Status> {
+        self.state().map(Response::new)
+    }
+
+    type WatchStream = Pin<Box<dyn Stream<Item = Result<State, Status>> + Send>>;
+
+    async fn watch(
+        &self,
+        _request: Request<WatchRequest>,
+    ) -> Result<Response<Self::WatchStream>, Status> {
+        let (tx, rx) = mpsc::channel(4);
+        let handle = self.handle.clone();
+        tokio::spawn(async move {
+            let mut sent = None;
+            loop {
+                if let Some(state) = handle.context().map(State::from) {
+                    if sent.as_ref() != Some(&state) {
+                        // the client hung up
+                        if tx.send(Ok(state.clone())).await.is_err() {
+                            return;
+                        }
+                        sent = Some(state);
+                    }
+                }
+                tokio::time::sleep(INTERVAL).await;
+            }
+        });
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
+}
+
+/// Serve the API on `address` from a thread of its own.
+pub fn serve(address: &str, handle: Handle) {
+    let address: SocketAddr = match address.parse() {
+        Ok(address) => address,
+        Err(err) => {
+            log::warn!("grpc address `{}`: {}", address, err);
+            return;
+        }
+    };
+    thread::spawn(move || {
+        let runtime = match tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(runtime) => runtime,
+            Err(err) => {
+                log::warn!("grpc unavailable: {}", err);
+                return;
+            }
+        };
+        let server = Server::builder()
+            .add_service(ControlServer::new(Service { handle }))
+            .serve(address);
+        if let Err(err) = runtime.block_on(server) {
+            log::warn!("grpc on {} unavailable: {}", address, err);
+        }
+    });
+}
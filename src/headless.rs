@@ -0,0 +1,223 @@
+//! The UI without a terminal: keys and commands from a script go into the
+//! event loop, and what it draws lands in a `TestBackend` to be looked at,
+//! so tests can go through whole flows the way a user would.
+
+use crossterm::event::{Event, KeyEvent};
+use std::{
+    collections::VecDeque,
+    env, fmt, fs, io,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+use tui::{backend::TestBackend, buffer::Buffer, Terminal};
+
+use crate::{
+    app::Command,
+    config::Config,
+    control::{Handle, Remote},
+    error::{PomError, Result},
+    history::Store,
+    keys::Key,
+    notify::Notifier,
+    platform::{self, Capabilities, Colors},
+    signals::Signals,
+    Icons, Input, Services,
+};
+
+/// Scratch directories made so far by this process, to name the next.
+static MADE: AtomicUsize = AtomicUsize::new(0);
+
+/// A run of the UI on a screen of its own: set up, then [`Headless::run`].
+///
+/// History and state go into a scratch directory of the run's own, unless
+/// it is given one to share with other runs. Nothing notifies or plays,
+/// and no services start.
+pub struct Headless {
+    width: u16,
+    height: u16,
+    config: Config,
+    steps: VecDeque<Step>,
+    dir: Option<PathBuf>,
+}
+
+/// An empty directory in the temp dir, removed when dropped.
+pub struct Scratch(PathBuf);
+
+impl Scratch {
+    pub fn new() -> io::Result<Self> {
+        let n = MADE.fetch_add(1, Ordering::Relaxed);
+        let dir = env::temp_dir().join(format!("pom-headless-{}-{}", process::id(), n));
+        // what a process with the same id left
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir)?;
+        Ok(Scratch(dir))
+    }
+
+    /// Where pom's files go, to seed or inspect them.
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+enum Step {
+    Event(Event),
+    Command(Command),
+}
+
+impl Headless {
+    pub fn new(width: u16, height: u16) -> Self {
+        Headless {
+            width,
+            height,
+            config: Config::default(),
+            steps: VecDeque::new(),
+            dir: None,
+        }
+    }
+
+    /// History and state in `scratch`, to pick up what an earlier run left.
+    pub fn scratch(mut self, scratch: &Scratch) -> Self {
+        self.dir = Some(scratch.0.clone());
+        self
+    }
+
+    /// Settings as they would be written in `config.toml`.
+    pub fn config(mut self, text: &str) -> Result<Self> {
+        self.config = toml::from_str(text).map_err(|source| PomError::ConfigParse {
+            path: PathBuf::from("config.toml"),
+            source,
+        })?;
+        Ok(self)
+    }
+
+    /// Keys pressed one after the other, named as in the config: `"enter p"`.
+    ///
+    /// Panics on a name that isn't a key.
+    pub fn keys(mut self, keys: &str) -> Self {
+        for name in keys.split_whitespace() {
+            let key = Key::parse(name).unwrap_or_else(|| panic!("not a key: {}", name));
+            self.steps
+                .push_back(Step::Event(Event::Key(KeyEvent::from(key))));
+        }
+        self
+    }
+
+    /// Anything else the terminal might send: a resize, the mouse.
+    pub fn event(mut self, event: Event) -> Self {
+        self.steps.push_back(Step::Event(event));
+        self
+    }
+
+    /// A command as it would come from the pipe or D-Bus.
+    pub fn command(mut self, command: Command) -> Self {
+        self.steps.push_back(Step::Command(command));
+        self
+    }
+
+    /// Everything in order, then the screen as it is after the last step.
+    pub fn run(self) -> Result<Screen> {
+        let own;
+        let dir = match self.dir {
+            Some(dir) => dir,
+            None => {
+                own = Scratch::new()?;
+                own.0.clone()
+            }
+        };
+        let _here = platform::locate_here(dir);
+
+        let mut config = self.config;
+        // the same glyphs on any machine
+        config.display.icons.get_or_insert(Icons::Ascii);
+        let caps = Capabilities {
+            job_control: false,
+            notifier: Notifier::silent(),
+            player: None,
+            speaker: None,
+            unicode: true,
+            colors: Colors::True,
+            blink: true,
+            hyperlinks: false,
+        };
+        let app = crate::prepare(&config, &caps, Vec::new())?;
+        let history = Store::configured(&config.history)?;
+        let services = Services::new(&config, &caps, Remote::new(), history, false);
+        let mut script = Script {
+            steps: self.steps,
+            handle: services.remote.handle(),
+        };
+
+        let mut terminal = Terminal::new(TestBackend::new(self.width, self.height))?;
+        crate::run_app(
+            &mut terminal,
+            &mut script,
+            app,
+            &Signals::none(),
+            &config,
+            &caps,
+            &services,
+        )?;
+        Ok(Screen(terminal.backend().buffer().clone()))
+    }
+}
+
+/// Steps handed to the event loop one per turn, commands through the
+/// remote like any other.
+struct Script {
+    steps: VecDeque<Step>,
+    handle: Handle,
+}
+
+impl Input for Script {
+    fn next(&mut self, _timeout: Duration) -> io::Result<Option<Event>> {
+        match self.steps.pop_front() {
+            Some(Step::Event(event)) => Ok(Some(event)),
+            Some(Step::Command(command)) => {
+                self.handle.send(command);
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn finished(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+/// The last frame drawn.
+pub struct Screen(pub Buffer);
+
+impl Screen {
+    /// Row by row, without the blanks at the end.
+    pub fn lines(&self) -> Vec<String> {
+        let area = self.0.area;
+        (area.top()..area.bottom())
+            .map(|y| {
+                let row: String = (area.left()..area.right())
+                    .map(|x| self.0.get(x, y).symbol.as_str())
+                    .collect();
+                row.trim_end().to_string()
+            })
+            .collect()
+    }
+
+    /// Whether `text` shows on one of the rows.
+    pub fn contains(&self, text: &str) -> bool {
+        self.lines().iter().any(|line| line.contains(text))
+    }
+}
+
+impl fmt::Display for Screen {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.lines().join("\n"))
+    }
+}
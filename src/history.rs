@@ -0,0 +1,1358 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, Utc};
+use clap::ValueEnum;
+use rusqlite::{params, params_from_iter, types::Value, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as Json};
+use std::{
+    collections::{btree_map, BTreeMap},
+    fs::{self, OpenOptions},
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    app::{Phase, Session},
+    cipher::{Cipher, Header},
+    config::{self, Config},
+    error::{PomError, Result},
+    events::{self, Entry},
+    git, instance, platform, report,
+};
+
+/// `user_version` of the SQLite database this pom writes.
+const SCHEMA: i32 = 6;
+
+/// What takes a database from each `user_version` to the next.
+const UPGRADES: [&str; SCHEMA as usize] = [
+    "CREATE TABLE sessions (
+        phase TEXT NOT NULL,
+        started TEXT NOT NULL,
+        ended TEXT NOT NULL,
+        planned INTEGER NOT NULL,
+        elapsed INTEGER NOT NULL,
+        completed INTEGER NOT NULL,
+        PRIMARY KEY (started, phase)
+    )",
+    // a JSON array, so `json_each` works in ad-hoc queries
+    "ALTER TABLE sessions ADD COLUMN tags TEXT NOT NULL DEFAULT '[]'",
+    "ALTER TABLE sessions ADD COLUMN note TEXT",
+    "ALTER TABLE sessions ADD COLUMN pauses INTEGER NOT NULL DEFAULT 0",
+    "ALTER TABLE sessions ADD COLUMN source TEXT",
+    "ALTER TABLE sessions ADD COLUMN profile TEXT",
+];
+
+/// Version of the sessions this pom writes to `history.jsonl`, as `v` on
+/// each line. Lines without one are version 1.
+const FORMAT: u64 = 2;
+
+/// What takes a JSONL session from each version to the next, before it is
+/// read.
+const MIGRATIONS: [fn(&mut Map<String, Json>); FORMAT as usize - 1] = [
+    // only the version is new, the fields are the same
+    |_| {},
+];
+
+/// Where every phase that ended is kept, oldest first.
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Backend {
+    /// `history.jsonl`, one JSON object per line: easy to grep and sync
+    #[default]
+    Jsonl,
+    /// `history.sqlite3`, for big histories and ad-hoc SQL
+    Sqlite,
+    /// `history/`, a file per device that only it appends to, merged on
+    /// load: safe to sync between machines with Syncthing or Dropbox
+    Synced,
+}
+
+impl Backend {
+    fn name(self) -> &'static str {
+        match self {
+            Backend::Jsonl => "jsonl",
+            Backend::Sqlite => "sqlite",
+            Backend::Synced => "synced",
+        }
+    }
+
+    pub fn path(self) -> Option<PathBuf> {
+        let file = match self {
+            Backend::Jsonl => "history.jsonl",
+            Backend::Sqlite => "history.sqlite3",
+            Backend::Synced => "history",
+        };
+        platform::project_dirs().map(|dirs| dirs.data_dir().join(file))
+    }
+}
+
+/// Which recorded sessions to list; everything when left empty.
+#[derive(Clone, Default, PartialEq)]
+pub struct Query {
+    /// started at or after
+    pub from: Option<DateTime<Utc>>,
+    /// started before
+    pub to: Option<DateTime<Utc>>,
+    /// with this tag, or one within it as a project
+    pub tag: Option<String>,
+    /// ran out, or was skipped or reset
+    pub completed: Option<bool>,
+    /// in a tag or the note, ignoring case
+    pub text: String,
+}
+
+impl Query {
+    fn matches(&self, session: &Session) -> bool {
+        let text = self.text.to_lowercase();
+        self.from.is_none_or(|from| session.started >= from)
+            && self.to.is_none_or(|to| session.started < to)
+            && self
+                .tag
+                .as_ref()
+                .is_none_or(|tag| session.tags.iter().any(|t| report::within(t, tag)))
+            && self
+                .completed
+                .is_none_or(|completed| session.completed == completed)
+            && (text.is_empty()
+                || session
+                    .tags
+                    .iter()
+                    .any(|tag| tag.to_lowercase().contains(&text))
+                || session
+                    .note
+                    .as_ref()
+                    .is_some_and(|note| note.to_lowercase().contains(&text)))
+    }
+
+    /// The same as SQL conditions, with their parameters.
+    fn sql(&self) -> (String, Vec<Value>) {
+        let mut conditions = vec!["1".to_string()];
+        let mut values = Vec::new();
+        let mut condition = |sql: &str, value: Value| {
+            values.push(value);
+            conditions.push(sql.replace('?', &format!("?{}", values.len())));
+        };
+        // stored as text the chrono way, which sorts like the times
+        let time = |at: DateTime<Utc>| Value::Text(at.format("%F %T%.f%:z").to_string());
+        if let Some(from) = self.from {
+            condition("started >= ?", time(from));
+        }
+        if let Some(to) = self.to {
+            condition("started < ?", time(to));
+        }
+        // the tag or a sub-project of it
+        if let Some(tag) = &self.tag {
+            condition(
+                "EXISTS (SELECT 1 FROM json_each(tags)
+                 WHERE value = ? OR substr(value, 1, length(?) + 1) = ? || '/')",
+                Value::Text(tag.clone()),
+            );
+        }
+        if let Some(completed) = self.completed {
+            condition("completed = ?", Value::Integer(completed.into()));
+        }
+        if !self.text.is_empty() {
+            condition(
+                "(instr(lower(tags), ?) > 0 OR instr(lower(coalesce(note, '')), ?) > 0)",
+                Value::Text(self.text.to_lowercase()),
+            );
+        }
+        (conditions.join(" AND "), values)
+    }
+}
+
+/// One tag's work sessions, added up.
+pub struct Tally {
+    pub tag: String,
+    /// when the last one ended
+    pub last: DateTime<Utc>,
+    /// those that ran out
+    pub completed: usize,
+    /// of those, today's
+    pub today: usize,
+    /// seconds worked, all of them
+    pub worked: i64,
+}
+
+impl Tally {
+    pub fn new(tag: &str, last: DateTime<Utc>) -> Self {
+        Tally {
+            tag: tag.to_string(),
+            last,
+            completed: 0,
+            today: 0,
+            worked: 0,
+        }
+    }
+
+    /// Count in one of the tag's work sessions.
+    pub fn add(&mut self, session: &Session, today: NaiveDate, day_start: NaiveTime) {
+        self.last = self.last.max(session.ended);
+        if session.completed {
+            self.completed += 1;
+            self.today += usize::from(report::day(session.started, day_start) == today);
+        }
+        self.worked += session.elapsed;
+    }
+}
+
+/// Every tag's work sessions in `sessions` added up, the tag worked on last
+/// first; `today` as `day_start` has it.
+pub fn tally(sessions: &[Session], today: NaiveDate, day_start: NaiveTime) -> Vec<Tally> {
+    let mut tallies: BTreeMap<&str, Tally> = BTreeMap::new();
+    for session in sessions
+        .iter()
+        .filter(|session| session.phase == Phase::Work)
+    {
+        for tag in &session.tags {
+            tallies
+                .entry(tag)
+                .or_insert_with(|| Tally::new(tag, session.ended))
+                .add(session, today, day_start);
+        }
+    }
+    let mut tallies: Vec<Tally> = tallies.into_values().collect();
+    tallies.sort_by_key(|tally| std::cmp::Reverse(tally.last));
+    tallies
+}
+
+/// A session as a JSONL line.
+#[derive(Serialize)]
+pub struct Line<'a> {
+    v: u64,
+    #[serde(flatten)]
+    session: &'a Session,
+}
+
+impl<'a> Line<'a> {
+    pub fn new(session: &'a Session) -> Self {
+        Line { v: FORMAT, session }
+    }
+}
+
+/// A history backend, plus the key when its lines are encrypted.
+pub struct Store {
+    backend: Backend,
+    crypt: Option<Crypt>,
+    /// names this device's files in a synced history
+    device: String,
+    git: Option<config::Git>,
+}
+
+struct Crypt {
+    cipher: Cipher,
+    /// first line of the file, written before the first session
+    header: String,
+}
+
+impl Store {
+    /// The history the config points at.
+    pub fn configured(config: &config::History) -> Result<Self> {
+        if config.backend != Backend::Jsonl && config.passphrase_command.is_some() {
+            return Err(PomError::EncryptionUnsupported);
+        }
+        Store::open(config.backend, config)
+    }
+
+    /// A backend's history, unlocked with the configured passphrase when it
+    /// is encrypted. Only JSONL histories are; a plain one gets encrypted
+    /// here, the first time a passphrase is set.
+    pub fn open(backend: Backend, config: &config::History) -> Result<Self> {
+        let device = config
+            .device
+            .clone()
+            .unwrap_or_else(platform::hostname)
+            .replace(|c: char| !c.is_alphanumeric() && c != '-' && c != '_', "-");
+        let plain = Store {
+            backend,
+            crypt: None,
+            device: device.clone(),
+            git: config.git.clone(),
+        };
+        let Some(path) = backend.path().filter(|_| backend == Backend::Jsonl) else {
+            return Ok(plain);
+        };
+        let header = read_header(&path)?;
+
+        let Some(command) = &config.passphrase_command else {
+            return match header {
+                Some(_) => Err(PomError::HistoryEncrypted { path }),
+                None => Ok(plain),
+            };
+        };
+        let passphrase = platform::run_secret_command(command)?;
+
+        let failed = || PomError::HistoryEncrypt { path: path.clone() };
+        let (cipher, header) = match header {
+            Some(header) => {
+                let cipher = Cipher::open(&passphrase, &header)
+                    .ok_or_else(|| PomError::WrongPassphrase { path: path.clone() })?;
+                (cipher, header)
+            }
+            None => Cipher::create(&passphrase).ok_or_else(failed)?,
+        };
+        let sealing = path.exists() && read_header(&path)?.is_none();
+        if let (true, Some(_), Some(dir)) = (sealing, &config.git, path.parent()) {
+            if git::has_commits(dir) {
+                return Err(PomError::GitPlaintext {
+                    dir: dir.to_path_buf(),
+                });
+            }
+        }
+        let mut store = Store {
+            backend,
+            crypt: Some(Crypt {
+                cipher,
+                header: serde_json::to_string(&header).map_err(|_| failed())?,
+            }),
+            device,
+            // nothing is committed until it is sealed
+            git: None,
+        };
+
+        // sessions from before encryption was turned on get sealed too, and
+        // the event log with them
+        if let Some(events) = store.events_path().filter(|events| events.exists()) {
+            if read_header(&events)?.is_none() {
+                store.write_events(&plain.events()?, &events)?;
+            }
+        }
+        if sealing {
+            let sessions = plain.load()?;
+            if let Some((tmp, target)) = store.stage(&sessions)? {
+                fs::rename(&tmp, &target).map_err(|source| PomError::HistoryWrite {
+                    path: target,
+                    source,
+                })?;
+            }
+        }
+        store.git = config.git.clone();
+        if sealing {
+            store.commit();
+        }
+        Ok(store)
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.crypt.is_some()
+    }
+
+    /// A file that goes with an encrypted history, e.g. a backup: its
+    /// header, then `contents` sealed like one of its lines. `None` when
+    /// the history isn't encrypted.
+    pub fn seal(&self, contents: &[u8]) -> Option<Vec<u8>> {
+        let crypt = self.crypt.as_ref()?;
+        let sealed = crypt.cipher.seal(contents)?;
+        Some(format!("{}\n{}\n", crypt.header, sealed).into_bytes())
+    }
+
+    /// Before the history changes, the day's commit if it is kept in git.
+    /// A failed one is tried again with the next change.
+    fn commit(&self) {
+        let Some(config) = &self.git else {
+            return;
+        };
+        let (Some(history), Some(events)) = (self.backend.path(), self.events_path()) else {
+            return;
+        };
+        let events = match self.backend {
+            // every device's log
+            Backend::Synced => events.parent().map_or(events.clone(), Path::to_path_buf),
+            _ => events,
+        };
+        if let Some(dir) = history.parent() {
+            if let Err(err) = git::commit(config, dir, &[history.clone(), events]) {
+                log::warn!("{}", err.report());
+            }
+        }
+    }
+
+    /// The file this pom writes to; in a synced history, this device's.
+    fn path(&self) -> Option<PathBuf> {
+        let path = self.backend.path()?;
+        Some(match self.backend {
+            Backend::Synced => path.join(format!("{}.jsonl", self.device)),
+            _ => path,
+        })
+    }
+
+    /// Add a finished session to the end of the history.
+    #[tracing::instrument(level = "debug", skip_all, fields(backend = self.backend.name()))]
+    pub fn append(&self, session: &Session) -> Result<()> {
+        let Some(path) = self.path() else {
+            return Ok(());
+        };
+        check(session, &path, || started(session))?;
+        let recorded = Entry::Recorded {
+            at: Utc::now(),
+            session: session.clone(),
+        };
+        self.log(&recorded)?;
+        create_parent(&path)?;
+        match self.backend {
+            Backend::Jsonl => self.append_lines(&[Line::new(session)], path),
+            Backend::Sqlite => {
+                let conn = open(&path)?;
+                insert(&conn, session).map_err(|source| PomError::Sqlite { path, source })
+            }
+            Backend::Synced => self.append_lines(&[recorded], path),
+        }
+    }
+
+    /// Put an edited session in place of the recorded one that started at
+    /// the same time.
+    pub fn replace(&self, session: &Session) -> Result<()> {
+        self.log(&Entry::Edited {
+            at: Utc::now(),
+            before: None,
+            after: session.clone(),
+        })?;
+        self.put(session)
+    }
+
+    /// Drop the recorded session that started at the same time as this one.
+    pub fn remove(&self, session: &Session) -> Result<()> {
+        self.log(&Entry::Deleted {
+            at: Utc::now(),
+            session: session.clone(),
+        })?;
+        self.take(session)
+    }
+
+    /// Correct a recorded session by hand, or with `None` drop it.
+    #[tracing::instrument(level = "debug", skip_all, fields(backend = self.backend.name()))]
+    pub fn amend(&self, before: &Session, after: Option<&Session>) -> Result<()> {
+        let at = Utc::now();
+        match after {
+            Some(after) => {
+                self.log(&Entry::Edited {
+                    at,
+                    before: Some(before.clone()),
+                    after: after.clone(),
+                })?;
+                self.put(after)
+            }
+            None => {
+                self.log(&Entry::Deleted {
+                    at,
+                    session: before.clone(),
+                })?;
+                self.take(before)
+            }
+        }
+    }
+
+    fn put(&self, session: &Session) -> Result<()> {
+        let Some(path) = self.path() else {
+            return Ok(());
+        };
+        match self.backend {
+            Backend::Jsonl => {
+                let mut sessions = self.load()?;
+                for recorded in &mut sessions {
+                    if recorded.started == session.started && recorded.phase == session.phase {
+                        *recorded = session.clone();
+                    }
+                }
+                if let Some((tmp, target)) = self.stage(&sessions)? {
+                    fs::rename(&tmp, &target).map_err(|source| PomError::HistoryWrite {
+                        path: target,
+                        source,
+                    })?;
+                }
+                Ok(())
+            }
+            Backend::Sqlite => {
+                let conn = open(&path)?;
+                update(&conn, session).map_err(|source| PomError::Sqlite { path, source })
+            }
+            Backend::Synced => {
+                let edited = Entry::Edited {
+                    at: Utc::now(),
+                    before: None,
+                    after: session.clone(),
+                };
+                create_parent(&path)?;
+                self.append_lines(&[edited], path)
+            }
+        }
+    }
+
+    fn take(&self, session: &Session) -> Result<()> {
+        let Some(path) = self.path() else {
+            return Ok(());
+        };
+        match self.backend {
+            Backend::Jsonl => {
+                let mut sessions = self.load()?;
+                sessions.retain(|s| s.started != session.started || s.phase != session.phase);
+                if let Some((tmp, target)) = self.stage(&sessions)? {
+                    fs::rename(&tmp, &target).map_err(|source| PomError::HistoryWrite {
+                        path: target,
+                        source,
+                    })?;
+                }
+                Ok(())
+            }
+            Backend::Sqlite => {
+                let conn = open(&path)?;
+                conn.execute(
+                    "DELETE FROM sessions WHERE phase = ?1 AND started = ?2",
+                    params![phase_id(session.phase), session.started],
+                )
+                .map(|_| ())
+                .map_err(|source| PomError::Sqlite { path, source })
+            }
+            Backend::Synced => {
+                let deleted = Entry::Deleted {
+                    at: Utc::now(),
+                    session: session.clone(),
+                };
+                create_parent(&path)?;
+                self.append_lines(&[deleted], path)
+            }
+        }
+    }
+
+    /// Add `entry` to the end of the event log, starting the log with the
+    /// sessions already recorded if it isn't there yet.
+    #[tracing::instrument(level = "debug", skip_all, fields(backend = self.backend.name()))]
+    pub fn log(&self, entry: &Entry) -> Result<()> {
+        self.log_all(std::slice::from_ref(entry))
+    }
+
+    fn log_all(&self, entries: &[Entry]) -> Result<()> {
+        let Some(path) = self.events_path() else {
+            return Ok(());
+        };
+        self.commit();
+        if !path.exists() {
+            self.seed(&path)?;
+        }
+        if entries.is_empty() {
+            return Ok(());
+        }
+        self.append_lines(entries, path)
+    }
+
+    /// Log what turning the recorded history into `sessions` changes, so
+    /// the log still adds up to the history once they are swapped in.
+    pub fn log_changes(&self, sessions: &[Session]) -> Result<()> {
+        self.log_all(&changes(&self.load()?, sessions, Utc::now()))
+    }
+
+    /// Every event in the log, oldest first.
+    pub fn events(&self) -> Result<Vec<Entry>> {
+        let Some(path) = self.events_path() else {
+            return Ok(Vec::new());
+        };
+        if !path.exists() {
+            self.seed(&path)?;
+        }
+        match (self.backend, path.parent()) {
+            (Backend::Synced, Some(dir)) => self.read_entries(dir),
+            _ => self.read_entries_in(&path),
+        }
+    }
+
+    /// `events.jsonl` beside the history; in a synced one, this device's
+    /// file under `events/`.
+    fn events_path(&self) -> Option<PathBuf> {
+        let dir = self.backend.path()?.parent()?.to_path_buf();
+        Some(match self.backend {
+            Backend::Synced => dir.join("events").join(format!("{}.jsonl", self.device)),
+            _ => dir.join("events.jsonl"),
+        })
+    }
+
+    fn read_entries_in(&self, path: &Path) -> Result<Vec<Entry>> {
+        self.read_lines(path)?
+            .into_iter()
+            .map(|(line, json)| {
+                serde_json::from_slice(&json).map_err(|source| PomError::HistoryParse {
+                    path: path.to_path_buf(),
+                    line,
+                    source,
+                })
+            })
+            .collect()
+    }
+
+    /// The entries in every device's file in `dir`, the copies a sync
+    /// conflict leaves included, oldest first.
+    fn read_entries(&self, dir: &Path) -> Result<Vec<Entry>> {
+        let files = match fs::read_dir(dir) {
+            Ok(files) => files,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(source) => {
+                return Err(PomError::HistoryRead {
+                    path: dir.to_path_buf(),
+                    source,
+                })
+            }
+        };
+        let mut paths: Vec<PathBuf> = files
+            .filter_map(|file| Some(file.ok()?.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+            .collect();
+        paths.sort();
+
+        let mut entries = Vec::new();
+        for path in paths {
+            entries.extend(self.read_entries_in(&path)?);
+        }
+        // stable, so each device's own order stands on a tie
+        entries.sort_by_key(Entry::at);
+        Ok(entries)
+    }
+
+    /// A new log, with a `recorded` event for each session in the history.
+    fn seed(&self, path: &Path) -> Result<()> {
+        let entries: Vec<Entry> = self
+            .load()?
+            .into_iter()
+            .map(|session| Entry::Recorded {
+                at: session.ended,
+                session,
+            })
+            .collect();
+        self.write_events(&entries, path)
+    }
+
+    /// The whole log, in place of what is there.
+    fn write_events(&self, entries: &[Entry], path: &Path) -> Result<()> {
+        let mut lines = Vec::new();
+        if let Some(crypt) = &self.crypt {
+            lines.extend_from_slice(crypt.header.as_bytes());
+            lines.push(b'\n');
+        }
+        for entry in entries {
+            lines.extend(self.line(entry, path)?);
+        }
+        create_parent(path)?;
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, lines)
+            .and_then(|()| fs::rename(&tmp, path))
+            .map_err(|source| PomError::HistoryWrite {
+                path: path.to_path_buf(),
+                source,
+            })
+    }
+
+    /// Add `sessions` to the history, in order, leaving out any that starts
+    /// at the same time as one already there. Returns the total and how
+    /// many were added.
+    #[tracing::instrument(level = "debug", skip_all, fields(backend = self.backend.name()))]
+    pub fn merge(&self, sessions: Vec<Session>) -> Result<(usize, usize)> {
+        let mut merged = BTreeMap::new();
+        for session in self.load()? {
+            merged.insert((session.started, phase_id(session.phase)), session);
+        }
+        let at = Utc::now();
+        let mut added = Vec::new();
+        for session in sessions {
+            if let btree_map::Entry::Vacant(slot) =
+                merged.entry((session.started, phase_id(session.phase)))
+            {
+                added.push(Entry::Recorded {
+                    at,
+                    session: session.clone(),
+                });
+                slot.insert(session);
+            }
+        }
+        self.log_all(&added)?;
+        let merged: Vec<Session> = merged.into_values().collect();
+
+        if let Some((tmp, target)) = self.stage(&merged)? {
+            fs::rename(&tmp, &target).map_err(|source| PomError::HistoryWrite {
+                path: target,
+                source,
+            })?;
+        }
+        Ok((merged.len(), added.len()))
+    }
+
+    /// Lines at the end of a JSONL file, after the encryption header when
+    /// they are the first.
+    fn append_lines<T: Serialize>(&self, values: &[T], path: PathBuf) -> Result<()> {
+        let mut lines = Vec::new();
+        if let Some(crypt) = &self.crypt {
+            if fs::metadata(&path).map_or(true, |meta| meta.len() == 0) {
+                lines.extend_from_slice(crypt.header.as_bytes());
+                lines.push(b'\n');
+            }
+        }
+        for value in values {
+            lines.extend(self.line(value, &path)?);
+        }
+
+        // one write, so an append never interleaves with another pom's
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(&lines))
+            .map_err(|source| PomError::HistoryWrite { path, source })
+    }
+
+    /// A session (or event) as a JSONL line, sealed if encrypted, newline
+    /// included.
+    fn line(&self, value: &impl Serialize, path: &Path) -> Result<Vec<u8>> {
+        let json = serde_json::to_vec(value).map_err(|source| PomError::HistoryWrite {
+            path: path.to_path_buf(),
+            source: source.into(),
+        })?;
+        let mut line = match &self.crypt {
+            Some(crypt) => crypt
+                .cipher
+                .seal(&json)
+                .ok_or_else(|| PomError::HistoryEncrypt {
+                    path: path.to_path_buf(),
+                })?
+                .into_bytes(),
+            None => json,
+        };
+        line.push(b'\n');
+        Ok(line)
+    }
+
+    /// All recorded sessions; none before the first one ends.
+    #[tracing::instrument(level = "debug", skip_all, fields(backend = self.backend.name()))]
+    pub fn load(&self) -> Result<Vec<Session>> {
+        let Some(path) = self.backend.path() else {
+            return Ok(Vec::new());
+        };
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        match self.backend {
+            Backend::Jsonl => self.load_jsonl(&path),
+            Backend::Sqlite => {
+                let conn = open(&path)?;
+                let mut sessions =
+                    load_sqlite(&conn, "1", Vec::new()).map_err(|source| PomError::Sqlite {
+                        path: path.clone(),
+                        source,
+                    })?;
+                sessions.retain(|session| keep(session, &path, || started(session)));
+                Ok(sessions)
+            }
+            Backend::Synced => {
+                let mut sessions = events::replay(&self.read_entries(&path)?);
+                sessions.retain(|session| keep(session, &path, || started(session)));
+                Ok(sessions)
+            }
+        }
+    }
+
+    /// Every tag's work sessions added up, the tag worked on last first.
+    pub fn tallies(&self, day_start: NaiveTime) -> Result<Vec<Tally>> {
+        let today = report::day(Utc::now(), day_start);
+        Ok(tally(&self.load()?, today, day_start))
+    }
+
+    /// The recorded sessions `query` picks out, newest first.
+    #[tracing::instrument(level = "debug", skip_all, fields(backend = self.backend.name()))]
+    pub fn query(&self, query: &Query) -> Result<Vec<Session>> {
+        let mut sessions = match self.backend.path() {
+            Some(path) if self.backend == Backend::Sqlite && path.exists() => {
+                let conn = open(&path)?;
+                let (filter, values) = query.sql();
+                let mut sessions =
+                    load_sqlite(&conn, &filter, values).map_err(|source| PomError::Sqlite {
+                        path: path.clone(),
+                        source,
+                    })?;
+                sessions.retain(|session| keep(session, &path, || started(session)));
+                sessions
+            }
+            _ => {
+                let mut sessions = self.load()?;
+                sessions.retain(|session| query.matches(session));
+                sessions
+            }
+        };
+        sessions.sort_by_key(|session| std::cmp::Reverse(session.started));
+        Ok(sessions)
+    }
+
+    /// The JSON on each line of a JSONL file, unsealed if encrypted, with
+    /// its line number.
+    fn read_lines(&self, path: &Path) -> Result<Vec<(usize, Vec<u8>)>> {
+        let text = fs::read_to_string(path).map_err(|source| PomError::HistoryRead {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        let mut lines = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || (i == 0 && parse_header(line).is_some()) {
+                continue;
+            }
+            let json = match &self.crypt {
+                Some(crypt) => {
+                    crypt
+                        .cipher
+                        .unseal(line)
+                        .ok_or_else(|| PomError::HistoryDecrypt {
+                            path: path.to_path_buf(),
+                            line: i + 1,
+                        })?
+                }
+                None => line.as_bytes().to_vec(),
+            };
+            lines.push((i + 1, json));
+        }
+        Ok(lines)
+    }
+
+    fn load_jsonl(&self, path: &Path) -> Result<Vec<Session>> {
+        let mut sessions = Vec::new();
+        for (line, json) in self.read_lines(path)? {
+            let parse = |source| PomError::HistoryParse {
+                path: path.to_path_buf(),
+                line,
+                source,
+            };
+            let mut record: Map<String, Json> = serde_json::from_slice(&json).map_err(parse)?;
+            let version = match record.remove("v") {
+                Some(v) => serde_json::from_value(v).map_err(parse)?,
+                None => 1,
+            };
+            if !(1..=FORMAT).contains(&version) {
+                return Err(PomError::HistoryFormat {
+                    path: path.to_path_buf(),
+                    line,
+                    version,
+                });
+            }
+            for migrate in &MIGRATIONS[version as usize - 1..] {
+                migrate(&mut record);
+            }
+            let session = serde_json::from_value(Json::Object(record)).map_err(parse)?;
+            if keep(&session, path, || format!("line {}", line)) {
+                sessions.push(session);
+            }
+        }
+        Ok(sessions)
+    }
+
+    /// Write a complete history beside the real one, returning the
+    /// temporary file and the file it should be renamed over.
+    #[tracing::instrument(level = "debug", skip_all, fields(backend = self.backend.name()))]
+    pub fn stage(&self, sessions: &[Session]) -> Result<Option<(PathBuf, PathBuf)>> {
+        let Some(target) = self.path() else {
+            return Ok(None);
+        };
+        self.commit();
+        create_parent(&target)?;
+        let tmp = target.with_extension("tmp");
+        // a leftover from an interrupted run would be appended to
+        let _ = fs::remove_file(&tmp);
+
+        match self.backend {
+            Backend::Jsonl => {
+                let mut lines = Vec::new();
+                if let Some(crypt) = &self.crypt {
+                    lines.extend_from_slice(crypt.header.as_bytes());
+                    lines.push(b'\n');
+                }
+                for session in sessions {
+                    lines.extend(self.line(&Line::new(session), &tmp)?);
+                }
+                fs::write(&tmp, lines).map_err(|source| PomError::HistoryWrite {
+                    path: tmp.clone(),
+                    source,
+                })?;
+            }
+            Backend::Sqlite => {
+                let mut conn = open(&tmp)?;
+                let result = conn.transaction().and_then(|tx| {
+                    for session in sessions {
+                        insert(&tx, session)?;
+                    }
+                    tx.commit()
+                });
+                result.map_err(|source| PomError::Sqlite {
+                    path: tmp.clone(),
+                    source,
+                })?;
+            }
+            Backend::Synced => {
+                // what changed, after this device's file; the other devices'
+                // files are theirs to write
+                let mut lines = match fs::read(&target) {
+                    Ok(lines) => lines,
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+                    Err(source) => {
+                        return Err(PomError::HistoryRead {
+                            path: target,
+                            source,
+                        })
+                    }
+                };
+                let changes = changes(&self.load()?, sessions, Utc::now());
+                for change in &changes {
+                    lines.extend(self.line(change, &tmp)?);
+                }
+                fs::write(&tmp, lines).map_err(|source| PomError::HistoryWrite {
+                    path: tmp.clone(),
+                    source,
+                })?;
+            }
+        }
+        Ok(Some((tmp, target)))
+    }
+}
+
+/// The entries that turn `before` into `after`: what went, what is new,
+/// and what differs.
+fn changes(before: &[Session], after: &[Session], at: DateTime<Utc>) -> Vec<Entry> {
+    let key = |session: &Session| (session.started, phase_id(session.phase));
+    let before: BTreeMap<_, _> = before.iter().map(|s| (key(s), s)).collect();
+    let after: BTreeMap<_, _> = after.iter().map(|s| (key(s), s)).collect();
+    let mut changes = Vec::new();
+    for (key, session) in &before {
+        if !after.contains_key(key) {
+            changes.push(Entry::Deleted {
+                at,
+                session: (*session).clone(),
+            });
+        }
+    }
+    for (key, session) in &after {
+        match before.get(key) {
+            None => changes.push(Entry::Recorded {
+                at,
+                session: (*session).clone(),
+            }),
+            Some(recorded) if recorded != session => changes.push(Entry::Edited {
+                at,
+                before: Some((*recorded).clone()),
+                after: (*session).clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    changes
+}
+
+/// The encryption header, if the file starts with one.
+fn read_header(path: &Path) -> Result<Option<Header>> {
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(source) => {
+            return Err(PomError::HistoryRead {
+                path: path.to_path_buf(),
+                source,
+            })
+        }
+    };
+    let mut first = String::new();
+    io::BufReader::new(file)
+        .read_line(&mut first)
+        .map_err(|source| PomError::HistoryRead {
+            path: path.to_path_buf(),
+            source,
+        })?;
+    Ok(parse_header(first.trim()))
+}
+
+fn parse_header(line: &str) -> Option<Header> {
+    serde_json::from_str(line).ok()
+}
+
+/// `pom migrate`: copy the history from one backend into another, merged
+/// with whatever the destination already has.
+pub fn migrate(from: Backend, to: Backend) -> Result<()> {
+    if from == to {
+        return Err(PomError::MigrateSame);
+    }
+    let _lock = instance::hold("migrating the history")?;
+    let config = Config::load()?;
+    // checked as it loads
+    let source = Store::open(from, &config.history)?.load()?;
+
+    // the same session twice, from an earlier migration or a sync conflict,
+    // is kept once
+    let to_store = Store::open(to, &config.history)?;
+    let count = source.len();
+    let (total, added) = to_store.merge(source)?;
+    println!(
+        "{} sessions in the {} history, {} duplicates dropped",
+        total,
+        to.name(),
+        count - added
+    );
+    if config.history.backend != to {
+        println!(
+            "set `backend = \"{}\"` under [history] in the config to use it",
+            to.name()
+        );
+    }
+    Ok(())
+}
+
+/// `pom log`: record a work session that happened away from the timer,
+/// starting `at` or ending now.
+pub fn log(
+    duration: i64,
+    at: Option<DateTime<Local>>,
+    tags: Vec<String>,
+    note: Option<String>,
+) -> Result<()> {
+    let now = Utc::now();
+    let started = at.map_or(now - Duration::seconds(duration), |at| {
+        at.with_timezone(&Utc)
+    });
+    let session = Session {
+        phase: Phase::Work,
+        started,
+        ended: started + Duration::seconds(duration),
+        planned: duration,
+        elapsed: duration,
+        completed: true,
+        pauses: 0,
+        tags,
+        note,
+        source: None,
+        profile: None,
+    };
+    let local = |at: DateTime<Utc>| {
+        at.with_timezone(&Local)
+            .format("%a %m-%d %H:%M")
+            .to_string()
+    };
+    if session.ended > now + Duration::minutes(1) {
+        return Err(PomError::LogFuture {
+            at: local(session.ended),
+        });
+    }
+
+    let config = Config::load()?;
+    let store = Store::configured(&config.history)?;
+    // counted twice otherwise
+    let overlap = store.load()?.into_iter().find(|recorded| {
+        recorded.elapsed > 0 && recorded.started < session.ended && session.started < recorded.ended
+    });
+    if let Some(recorded) = overlap {
+        return Err(PomError::LogOverlap {
+            phase: recorded.phase.name(),
+            at: local(recorded.started),
+        });
+    }
+    store.append(&session)?;
+    println!(
+        "logged {} of work, {} to {}",
+        report::duration(session.elapsed),
+        local(session.started),
+        session.ended.with_timezone(&Local).format("%H:%M")
+    );
+    Ok(())
+}
+
+/// What a session that came out of a real timer always satisfies.
+fn validate(session: &Session) -> std::result::Result<(), &'static str> {
+    if session.ended < session.started {
+        Err("it ended before it started")
+    } else if session.elapsed < 0 || session.planned < 0 {
+        Err("negative duration")
+    } else if session.elapsed > session.planned {
+        Err("ran longer than planned")
+    } else {
+        Ok(())
+    }
+}
+
+/// `session` if it is valid, or an error that says where to find it.
+fn check(session: &Session, path: &Path, at: impl FnOnce() -> String) -> Result<()> {
+    validate(session).map_err(|reason| PomError::HistoryInvalid {
+        path: path.to_path_buf(),
+        at: at(),
+        reason,
+    })
+}
+
+/// Whether to load `session`; one that isn't valid is left out with a
+/// warning that says where to find it, rather than failing the rest.
+fn keep(session: &Session, path: &Path, at: impl FnOnce() -> String) -> bool {
+    match check(session, path, at) {
+        Ok(()) => true,
+        Err(err) => {
+            log::warn!("skipping {}", err.report());
+            false
+        }
+    }
+}
+
+fn started(session: &Session) -> String {
+    format!(
+        "{} from {}",
+        session.phase.name(),
+        session.started.to_rfc3339()
+    )
+}
+
+fn create_parent(path: &Path) -> Result<()> {
+    match path.parent() {
+        Some(dir) => fs::create_dir_all(dir).map_err(|source| PomError::HistoryWrite {
+            path: path.to_path_buf(),
+            source,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Open the database, creating or upgrading the table as needed.
+fn open(path: &Path) -> Result<Connection> {
+    let sqlite = |source| PomError::Sqlite {
+        path: path.to_path_buf(),
+        source,
+    };
+    let mut conn = Connection::open(path).map_err(sqlite)?;
+    let version: i32 = conn
+        .pragma_query_value(None, "user_version", |row| row.get(0))
+        .map_err(sqlite)?;
+    if !(0..=SCHEMA).contains(&version) {
+        return Err(PomError::HistorySchema {
+            path: path.to_path_buf(),
+            version,
+        });
+    }
+    if version < SCHEMA {
+        let tx = conn.transaction().map_err(sqlite)?;
+        for upgrade in &UPGRADES[version as usize..] {
+            tx.execute_batch(upgrade).map_err(sqlite)?;
+        }
+        tx.pragma_update(None, "user_version", SCHEMA)
+            .map_err(sqlite)?;
+        tx.commit().map_err(sqlite)?;
+    }
+    Ok(conn)
+}
+
+fn insert(conn: &Connection, session: &Session) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO sessions
+            (phase, started, ended, planned, elapsed, completed, tags, note, pauses, source,
+             profile)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![
+            phase_id(session.phase),
+            session.started,
+            session.ended,
+            session.planned,
+            session.elapsed,
+            session.completed,
+            serde_json::to_string(&session.tags).unwrap_or_default(),
+            session.note,
+            session.pauses,
+            session.source,
+            session.profile,
+        ],
+    )?;
+    Ok(())
+}
+
+fn update(conn: &Connection, session: &Session) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE sessions
+         SET ended = ?3, planned = ?4, elapsed = ?5, completed = ?6, tags = ?7, note = ?8,
+             pauses = ?9
+         WHERE phase = ?1 AND started = ?2",
+        params![
+            phase_id(session.phase),
+            session.started,
+            session.ended,
+            session.planned,
+            session.elapsed,
+            session.completed,
+            serde_json::to_string(&session.tags).unwrap_or_default(),
+            session.note,
+            session.pauses,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Sessions matching the `filter` condition, oldest first.
+fn load_sqlite(
+    conn: &Connection,
+    filter: &str,
+    values: Vec<Value>,
+) -> rusqlite::Result<Vec<Session>> {
+    let mut statement = conn.prepare(&format!(
+        "SELECT phase, started, ended, planned, elapsed, completed, tags, note, pauses, source,
+             profile
+         FROM sessions WHERE {} ORDER BY started",
+        filter
+    ))?;
+    let rows = statement.query_map(params_from_iter(values), |row| {
+        let phase: String = row.get(0)?;
+        Ok(Session {
+            phase: parse_phase(&phase).ok_or_else(|| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    0,
+                    rusqlite::types::Type::Text,
+                    format!("unknown phase `{}`", phase).into(),
+                )
+            })?,
+            started: row.get(1)?,
+            ended: row.get(2)?,
+            planned: row.get(3)?,
+            elapsed: row.get(4)?,
+            completed: row.get(5)?,
+            tags: serde_json::from_str(&row.get::<_, String>(6)?).map_err(|err| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    6,
+                    rusqlite::types::Type::Text,
+                    err.into(),
+                )
+            })?,
+            note: row.get(7)?,
+            pauses: row.get(8)?,
+            source: row.get(9)?,
+            profile: row.get(10)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// The name phases have in the history, same as in JSON.
+fn phase_id(phase: Phase) -> &'static str {
+    match phase {
+        Phase::Work => "work",
+        Phase::ShortBreak => "short-break",
+        Phase::LongBreak => "long-break",
+    }
+}
+
+fn parse_phase(id: &str) -> Option<Phase> {
+    match id {
+        "work" => Some(Phase::Work),
+        "short-break" => Some(Phase::ShortBreak),
+        "long-break" => Some(Phase::LongBreak),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headless::Scratch;
+
+    fn session(tag: &str) -> Session {
+        let started = Utc::now() - Duration::minutes(30);
+        Session {
+            phase: Phase::Work,
+            started,
+            ended: started + Duration::minutes(25),
+            planned: 1500,
+            elapsed: 1500,
+            completed: true,
+            pauses: 0,
+            tags: vec![tag.to_string()],
+            note: None,
+            source: None,
+            profile: None,
+        }
+    }
+
+    fn encrypted(passphrase: &str) -> config::History {
+        config::History {
+            passphrase_command: Some(format!("echo {}", passphrase)),
+            ..config::History::default()
+        }
+    }
+
+    #[test]
+    fn reads_back_an_encrypted_history() {
+        let scratch = Scratch::new().unwrap();
+        let _here = platform::locate_here(scratch.path().to_path_buf());
+        let store = Store::open(Backend::Jsonl, &encrypted("secret")).unwrap();
+        store.append(&session("acme")).unwrap();
+
+        let path = Backend::Jsonl.path().unwrap();
+        assert!(!fs::read_to_string(&path).unwrap().contains("acme"));
+        let store = Store::open(Backend::Jsonl, &encrypted("secret")).unwrap();
+        let sessions = store.load().unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].tags, ["acme"]);
+    }
+
+    #[test]
+    fn encrypts_a_plain_history_in_place() {
+        let scratch = Scratch::new().unwrap();
+        let _here = platform::locate_here(scratch.path().to_path_buf());
+        let plain = Store::open(Backend::Jsonl, &config::History::default()).unwrap();
+        plain.append(&session("acme")).unwrap();
+
+        let store = Store::open(Backend::Jsonl, &encrypted("secret")).unwrap();
+        assert!(store.is_encrypted());
+        assert!(!fs::read_to_string(Backend::Jsonl.path().unwrap()).unwrap().contains("acme"));
+        assert_eq!(store.load().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn refuses_a_wrong_passphrase() {
+        let scratch = Scratch::new().unwrap();
+        let _here = platform::locate_here(scratch.path().to_path_buf());
+        let store = Store::open(Backend::Jsonl, &encrypted("secret")).unwrap();
+        store.append(&session("acme")).unwrap();
+
+        let wrong = Store::open(Backend::Jsonl, &encrypted("guess"));
+        assert!(matches!(wrong, Err(PomError::WrongPassphrase { .. })));
+        let unset = Store::open(Backend::Jsonl, &config::History::default());
+        assert!(matches!(unset, Err(PomError::HistoryEncrypted { .. })));
+    }
+
+    #[test]
+    fn keeps_plaintext_out_of_git() {
+        let git = |dir: &Path, args: &[&str]| {
+            let output = std::process::Command::new("git").arg("-C").arg(dir).args(args).output();
+            String::from_utf8_lossy(&output.unwrap().stdout).into_owned()
+        };
+        let tracked = config::History {
+            git: Some(config::Git::default()),
+            ..config::History::default()
+        };
+        let sealed = config::History {
+            git: Some(config::Git::default()),
+            ..encrypted("secret")
+        };
+
+        let scratch = Scratch::new().unwrap();
+        let _here = platform::locate_here(scratch.path().to_path_buf());
+        let plain = Store::open(Backend::Jsonl, &config::History::default()).unwrap();
+        plain.append(&session("acme")).unwrap();
+        Store::open(Backend::Jsonl, &sealed).unwrap();
+        let log = git(scratch.path(), &["log", "-p", "--format=%s"]);
+        assert!(log.contains("history up to") && !log.contains("acme"), "{}", log);
+
+        let scratch = Scratch::new().unwrap();
+        let _here = platform::locate_here(scratch.path().to_path_buf());
+        let plain = Store::open(Backend::Jsonl, &tracked).unwrap();
+        plain.append(&session("acme")).unwrap();
+        plain.append(&session("acme")).unwrap();
+        assert!(git(scratch.path(), &["log", "-p"]).contains("acme"));
+        let refused = Store::open(Backend::Jsonl, &sealed);
+        assert!(matches!(refused, Err(PomError::GitPlaintext { .. })));
+    }
+
+    #[test]
+    fn refuses_a_tampered_line() {
+        let scratch = Scratch::new().unwrap();
+        let _here = platform::locate_here(scratch.path().to_path_buf());
+        let store = Store::open(Backend::Jsonl, &encrypted("secret")).unwrap();
+        store.append(&session("acme")).unwrap();
+
+        let path = Backend::Jsonl.path().unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        let (header, line) = text.trim_end().split_once('\n').unwrap();
+        let flipped = if line.starts_with('A') { 'B' } else { 'A' };
+        fs::write(&path, format!("{}\n{}{}\n", header, flipped, &line[1..])).unwrap();
+        assert!(matches!(store.load(), Err(PomError::HistoryDecrypt { line: 2, .. })));
+    }
+}
@@ -0,0 +1,128 @@
+//! System-wide hotkeys for the timer, bound through the desktop portal's
+//! GlobalShortcuts: KDE Plasma, GNOME 48 and later, Hyprland. The desktop
+//! asks the user to confirm them and may let them pick other keys.
+
+use std::{collections::HashMap, thread};
+use zbus::{
+    blocking::{Connection, Proxy},
+    zvariant::{DynamicType, OwnedObjectPath, OwnedValue, Value},
+};
+
+use crate::{app::Command, config::Hotkeys, control::Handle};
+
+const PORTAL: &str = "org.freedesktop.portal.Desktop";
+const DESKTOP: &str = "/org/freedesktop/portal/desktop";
+
+/// Bind the configured hotkeys from a background thread, for as long as
+/// pom runs.
+pub fn serve(hotkeys: &Hotkeys, handle: Handle) {
+    let bindings = hotkeys.bindings();
+    if bindings.is_empty() {
+        return;
+    }
+    thread::spawn(move || {
+        if let Err(err) = bind(&bindings, &handle) {
+            log::warn!("global hotkeys unavailable: {}", err);
+        }
+    });
+}
+
+fn command(id: &str) -> Option<Command> {
+    match id {
+        "start" => Some(Command::Start),
+        "pause" => Some(Command::Pause),
+        "toggle" => Some(Command::Toggle),
+        "skip" => Some(Command::Skip),
+        _ => None,
+    }
+}
+
+fn bind(bindings: &[(&str, &str, String)], handle: &Handle) -> zbus::Result<()> {
+    let conn = Connection::session()?;
+    // the portal names its objects after the caller
+    let sender = conn
+        .unique_name()
+        .map(|name| name.trim_start_matches(':').replace('.', "_"))
+        .unwrap_or_default();
+    let portal = Proxy::new(
+        &conn,
+        PORTAL,
+        DESKTOP,
+        "org.freedesktop.portal.GlobalShortcuts",
+    )?;
+    let activations = portal.receive_signal("Activated")?;
+
+    let options = HashMap::from([
+        ("handle_token", Value::from("pom_create")),
+        ("session_handle_token", Value::from("pom")),
+    ]);
+    request(
+        &conn,
+        &portal,
+        &sender,
+        "pom_create",
+        "CreateSession",
+        &(options,),
+    )?;
+
+    let session = OwnedObjectPath::try_from(format!("{}/session/{}/pom", DESKTOP, sender))?;
+    let shortcuts: Vec<(&str, HashMap<&str, Value>)> = bindings
+        .iter()
+        .map(|(id, description, trigger)| {
+            let properties = HashMap::from([
+                ("description", Value::from(*description)),
+                ("preferred_trigger", Value::from(trigger.as_str())),
+            ]);
+            (*id, properties)
+        })
+        .collect();
+    let options = HashMap::from([("handle_token", Value::from("pom_bind"))]);
+    request(
+        &conn,
+        &portal,
+        &sender,
+        "pom_bind",
+        "BindShortcuts",
+        &(&session, shortcuts, "", options),
+    )?;
+    log::info!("global hotkeys bound");
+
+    for activation in activations {
+        let (_, id, _, _): (OwnedObjectPath, String, u64, HashMap<String, OwnedValue>) =
+            activation.body().deserialize()?;
+        if let Some(command) = command(&id) {
+            handle.send(command);
+        }
+    }
+    Ok(())
+}
+
+/// Call a portal method and wait for the answer, which comes as a signal
+/// on a request object named after the `handle_token` in its options.
+fn request<B>(
+    conn: &Connection,
+    portal: &Proxy,
+    sender: &str,
+    token: &str,
+    method: &str,
+    body: &B,
+) -> zbus::Result<()>
+where
+    B: serde::Serialize + DynamicType,
+{
+    let path = format!("{}/request/{}/{}", DESKTOP, sender, token);
+    let request = Proxy::new(conn, PORTAL, path, "org.freedesktop.portal.Request")?;
+    // listening before the call, the answer can come straight away
+    let mut responses = request.receive_signal("Response")?;
+    portal.call_method(method, body)?;
+
+    let response = responses
+        .next()
+        .ok_or_else(|| zbus::Error::Failure("the portal went away".to_string()))?;
+    let (code, _): (u32, HashMap<String, OwnedValue>) = response.body().deserialize()?;
+    match code {
+        0 => Ok(()),
+        1 => Err(zbus::Error::Failure("the user declined them".to_string())),
+        _ => Err(zbus::Error::Failure(format!("{} failed", method))),
+    }
+}
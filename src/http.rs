@@ -0,0 +1,303 @@
+//! A small HTTP server for browsers, on the address in `http`:
+//!
+//! - `/` is a one-page dashboard, to glance at and drive the timer from a
+//!   phone on the LAN
+//! - `/events` streams the timer as server-sent events, for live widgets
+//!   and OBS overlays: `state` with what `pom status` prints whenever
+//!   anything but the time left changes, `tick` with
+//!   `{"remaining": seconds}` as it counts down
+//! - `POST /command` takes a command in the command pipe's text form
+//!
+//! Anyone who can reach the address can do all of it. Commands need an
+//! `X-Pom` header, which other sites' pages can't send without asking
+//! first, so a page open in the browser can't drive the timer. Nor can
+//! it read `/events`, unless its origin is the one in `http_origin`.
+//! Requests must name the address, `localhost` or an IP in `Host`, so a
+//! site that rebinds its own name to this address gets nowhere.
+
+use serde_json::json;
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{IpAddr, SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{app::Command, control::Handle, template::Context};
+
+const DASHBOARD: &str = include_str!("dashboard.html");
+
+/// Bigger than any command.
+const MAX_BODY: usize = 64;
+
+/// Longer than any request or header line a browser sends pom.
+const MAX_LINE: usize = 8192;
+
+/// More headers than a browser sends.
+const MAX_HEADERS: usize = 100;
+
+/// How long a request may take to arrive.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a write may wait on a peer that stopped reading, after
+/// which its stream is dropped.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Many more dashboards and overlays than anyone keeps open.
+const MAX_CONNECTIONS: usize = 32;
+
+/// How often the stream looks at the timer.
+const INTERVAL: Duration = Duration::from_millis(250);
+
+/// A comment this often, so proxies don't drop a quiet stream.
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+pub fn serve(address: &str, origin: Option<String>, handle: Handle) {
+    let listener = match TcpListener::bind(address) {
+        Ok(listener) => listener,
+        Err(err) => {
+            log::warn!("http on {} unavailable: {}", address, err);
+            return;
+        }
+    };
+    let address = address.to_string();
+    let open = Arc::new(AtomicUsize::new(0));
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(mut stream) => {
+                    if open.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                        open.fetch_sub(1, Ordering::SeqCst);
+                        let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+                        let _ = reply(
+                            &mut stream,
+                            "503 Service Unavailable",
+                            "text/plain",
+                            "too many connections\n",
+                        );
+                        continue;
+                    }
+                    let open = Open(open.clone());
+                    let (address, origin) = (address.clone(), origin.clone());
+                    let handle = handle.clone();
+                    thread::spawn(move || {
+                        let _open = open;
+                        // a browser going away mid-stream is nothing to report
+                        let _ = respond(stream, &address, origin.as_deref(), &handle);
+                    });
+                }
+                Err(err) => log::warn!("http: {}", err),
+            }
+        }
+    });
+}
+
+/// A connection counted among those open, until it's dropped.
+struct Open(Arc<AtomicUsize>);
+
+impl Drop for Open {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn respond(
+    mut stream: TcpStream,
+    address: &str,
+    origin: Option<&str>,
+    handle: &Handle,
+) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    // a stream to a peer that went away fails, rather than waiting forever
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request = String::new();
+    read_line(&mut reader, &mut request)?;
+    let (mut length, mut asked, mut host) = (0, false, None);
+    let mut header = String::new();
+    for _ in 0..MAX_HEADERS {
+        if read_line(&mut reader, &mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            match name.trim().to_ascii_lowercase().as_str() {
+                "content-length" => length = value.trim().parse().unwrap_or(usize::MAX),
+                "x-pom" => asked = true,
+                "host" => host = Some(value.trim().to_string()),
+                _ => {}
+            }
+        }
+        header.clear();
+    }
+    if !host.is_some_and(|host| known(&host, address)) {
+        return reply(
+            &mut stream,
+            "421 Misdirected Request",
+            "text/plain",
+            "unknown Host\n",
+        );
+    }
+
+    let mut words = request.split_whitespace();
+    match (words.next(), words.next()) {
+        (Some("GET"), Some("/")) => {
+            reply(&mut stream, "200 OK", "text/html; charset=utf-8", DASHBOARD)
+        }
+        (Some("GET"), Some("/events")) => events(stream, origin, handle),
+        (Some("POST"), Some("/command")) if !asked => reply(
+            &mut stream,
+            "403 Forbidden",
+            "text/plain",
+            "X-Pom header missing\n",
+        ),
+        (Some("POST"), Some("/command")) if length > MAX_BODY => {
+            reply(&mut stream, "413 Payload Too Large", "text/plain", "")
+        }
+        (Some("POST"), Some("/command")) => {
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body)?;
+            match String::from_utf8_lossy(&body).parse::<Command>() {
+                Ok(command) => {
+                    handle.send(command);
+                    reply(&mut stream, "204 No Content", "text/plain", "")
+                }
+                Err(err) => reply(
+                    &mut stream,
+                    "400 Bad Request",
+                    "text/plain",
+                    &format!("{}\n", err),
+                ),
+            }
+        }
+        _ => reply(&mut stream, "404 Not Found", "text/plain", ""),
+    }
+}
+
+/// One line of the request, an error if it runs past `MAX_LINE`.
+fn read_line(reader: &mut impl BufRead, line: &mut String) -> io::Result<usize> {
+    let read = reader.take(MAX_LINE as u64).read_line(line)?;
+    if read == MAX_LINE && !line.ends_with('\n') {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "line too long"));
+    }
+    Ok(read)
+}
+
+/// `host` names the server: its configured address, `localhost` or an
+/// IP, with or without the port.
+fn known(host: &str, address: &str) -> bool {
+    if host.parse::<SocketAddr>().is_ok()
+        || host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>().is_ok()
+    {
+        return true;
+    }
+    fn name(host: &str) -> &str {
+        host.rsplit_once(':').map_or(host, |(name, _)| name)
+    }
+    let host = name(host);
+    host.eq_ignore_ascii_case("localhost") || host.eq_ignore_ascii_case(name(address))
+}
+
+fn reply(stream: &mut TcpStream, status: &str, kind: &str, body: &str) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        kind,
+        body.len(),
+        body
+    )
+}
+
+/// The event stream, until the browser goes away.
+fn events(mut stream: TcpStream, origin: Option<&str>, handle: &Handle) -> io::Result<()> {
+    let cors = origin.map_or(String::new(), |origin| {
+        format!("Access-Control-Allow-Origin: {}\r\n", origin)
+    });
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\n\
+         Content-Type: text/event-stream\r\n\
+         Cache-Control: no-cache\r\n\
+         {}\
+         Connection: keep-alive\r\n\r\n",
+        cors
+    )?;
+
+    let mut sent: Option<Context> = None;
+    let mut quiet_since = Instant::now();
+    loop {
+        if let Some(context) = handle.context() {
+            let event = match &sent {
+                Some(sent) if *sent == context => None,
+                Some(sent) if ticked(sent, &context) => {
+                    Some(("tick", json!({ "remaining": context.remaining })))
+                }
+                _ => serde_json::to_value(&context)
+                    .ok()
+                    .map(|state| ("state", state)),
+            };
+            if let Some((name, data)) = event {
+                write!(stream, "event: {}\ndata: {}\n\n", name, data)?;
+                stream.flush()?;
+                sent = Some(context);
+                quiet_since = Instant::now();
+            }
+        }
+        if quiet_since.elapsed() >= KEEP_ALIVE {
+            stream.write_all(b":\n\n")?;
+            quiet_since = Instant::now();
+        }
+        thread::sleep(INTERVAL);
+    }
+}
+
+/// Only the time left changed.
+fn ticked(sent: &Context, context: &Context) -> bool {
+    let sent = Context {
+        remaining: context.remaining,
+        ..sent.clone()
+    };
+    sent == *context
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::Remote;
+
+    fn get(address: &str, path: &str) -> TcpStream {
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\nHost: {}\r\n\r\n", path, address).unwrap();
+        stream
+    }
+
+    fn status(stream: TcpStream) -> String {
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).unwrap();
+        line.trim_end().to_string()
+    }
+
+    #[test]
+    fn turns_away_connections_past_the_limit() {
+        // a port that was free a moment ago
+        let address = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+        let address = address.as_str();
+        let remote = Remote::new();
+        serve(address, None, remote.handle());
+        thread::sleep(Duration::from_millis(100));
+
+        let streams: Vec<TcpStream> = (0..MAX_CONNECTIONS).map(|_| get(address, "/events")).collect();
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(status(get(address, "/")), "HTTP/1.1 503 Service Unavailable");
+
+        // closed streams make room again once their writes fail
+        drop(streams);
+        thread::sleep(KEEP_ALIVE + INTERVAL * 4);
+        assert_eq!(status(get(address, "/")), "HTTP/1.1 200 OK");
+    }
+}
@@ -0,0 +1,160 @@
+#[cfg(unix)]
+use std::process::Command;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+#[cfg(unix)]
+use crate::platform::find_executable;
+
+/// How often the system idle time is sampled.
+const POLL: Duration = Duration::from_secs(5);
+
+/// Where the time since the last keyboard/mouse input comes from.
+enum Source {
+    /// X11 screensaver extension, via the `xprintidle` helper
+    #[cfg(all(unix, not(target_os = "macos")))]
+    Xprintidle,
+    /// GNOME's idle monitor, which also covers GNOME on Wayland
+    #[cfg(target_os = "linux")]
+    Mutter(zbus::blocking::Connection),
+    /// `HIDIdleTime` from the IOKit registry
+    #[cfg(target_os = "macos")]
+    Ioreg,
+    #[cfg(windows)]
+    LastInputInfo,
+}
+
+impl Source {
+    #[cfg(windows)]
+    fn detect() -> Option<Self> {
+        Some(Source::LastInputInfo)
+    }
+
+    #[cfg(target_os = "macos")]
+    fn detect() -> Option<Self> {
+        find_executable("ioreg").map(|_| Source::Ioreg)
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn detect() -> Option<Self> {
+        #[cfg(target_os = "linux")]
+        if let Ok(conn) = zbus::blocking::Connection::session() {
+            let source = Source::Mutter(conn);
+            if source.idle().is_some() {
+                return Some(source);
+            }
+        }
+        if std::env::var_os("DISPLAY").is_some() && find_executable("xprintidle").is_some() {
+            return Some(Source::Xprintidle);
+        }
+        None
+    }
+
+    /// Time since the last input, in milliseconds.
+    fn idle(&self) -> Option<u64> {
+        match self {
+            #[cfg(all(unix, not(target_os = "macos")))]
+            Source::Xprintidle => {
+                let output = Command::new("xprintidle").output().ok()?;
+                String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+            }
+            #[cfg(target_os = "linux")]
+            Source::Mutter(conn) => conn
+                .call_method(
+                    Some("org.gnome.Mutter.IdleMonitor"),
+                    "/org/gnome/Mutter/IdleMonitor/Core",
+                    Some("org.gnome.Mutter.IdleMonitor"),
+                    "GetIdletime",
+                    &(),
+                )
+                .ok()?
+                .body()
+                .deserialize::<u64>()
+                .ok(),
+            #[cfg(target_os = "macos")]
+            Source::Ioreg => {
+                let output = Command::new("ioreg")
+                    .args(["-c", "IOHIDSystem", "-d", "4"])
+                    .output()
+                    .ok()?;
+                // `"HIDIdleTime" = 1234567890`, in nanoseconds
+                let text = String::from_utf8_lossy(&output.stdout);
+                let line = text.lines().find(|line| line.contains("\"HIDIdleTime\""))?;
+                let nanos: u64 = line.rsplit('=').next()?.trim().parse().ok()?;
+                Some(nanos / 1_000_000)
+            }
+            #[cfg(windows)]
+            Source::LastInputInfo => windows::idle(),
+        }
+    }
+}
+
+/// System-wide idle time, sampled on a background thread since most
+/// sources mean running a helper program.
+pub struct Monitor {
+    idle_ms: Arc<AtomicU64>,
+}
+
+impl Monitor {
+    pub fn start() -> Self {
+        let idle_ms = Arc::new(AtomicU64::new(0));
+        let shared = Arc::clone(&idle_ms);
+
+        thread::spawn(move || {
+            let Some(source) = Source::detect() else {
+                log::info!("idle detection unavailable on this desktop");
+                return;
+            };
+            loop {
+                shared.store(source.idle().unwrap_or(0), Ordering::Relaxed);
+                thread::sleep(POLL);
+            }
+        });
+
+        Monitor { idle_ms }
+    }
+
+    /// How long the user has been away; zero when it can't be told.
+    pub fn idle(&self) -> Duration {
+        Duration::from_millis(self.idle_ms.load(Ordering::Relaxed))
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    #[repr(C)]
+    struct LastInputInfo {
+        size: u32,
+        time: u32,
+    }
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetLastInputInfo(info: *mut LastInputInfo) -> i32;
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetTickCount() -> u32;
+    }
+
+    pub fn idle() -> Option<u64> {
+        let mut info = LastInputInfo {
+            size: std::mem::size_of::<LastInputInfo>() as u32,
+            time: 0,
+        };
+        // SAFETY: `info` is a properly sized LASTINPUTINFO that outlives the call
+        if unsafe { GetLastInputInfo(&mut info) } == 0 {
+            return None;
+        }
+        // both are milliseconds since boot and wrap together every ~49 days
+        let now = unsafe { GetTickCount() };
+        Some(now.wrapping_sub(info.time) as u64)
+    }
+}
@@ -0,0 +1,408 @@
+//! Real images in terminals that take them: the kitty graphics protocol
+//! and iTerm2's inline images. Everything else keeps the ASCII tomato.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::{
+    env,
+    io::{self, Write},
+};
+use tui::layout::Rect;
+
+/// Pixels of the generated tomato.
+const SIZE: (u32, u32) = (96, 96);
+/// A guess at the size of a cell, for terminals that don't say.
+const CELL: (u32, u32) = (10, 20);
+
+type Rgba = [u8; 4];
+const CLEAR: Rgba = [0, 0, 0, 0];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Protocol {
+    Kitty,
+    Iterm,
+    Sixel,
+}
+
+impl Protocol {
+    /// Going by what the terminal says it is; nothing inside tmux or
+    /// screen, which don't pass the sequences on.
+    pub fn detect() -> Option<Self> {
+        let var = |name| env::var(name).unwrap_or_default();
+        let term = var("TERM");
+        if env::var_os("TMUX").is_some() || term.starts_with("screen") {
+            return None;
+        }
+        if term == "xterm-kitty" || env::var_os("KITTY_WINDOW_ID").is_some() {
+            return Some(Protocol::Kitty);
+        }
+        match (var("TERM_PROGRAM").as_str(), var("LC_TERMINAL").as_str()) {
+            ("iTerm.app" | "WezTerm", _) | (_, "iTerm2") => Some(Protocol::Iterm),
+            ("mintty", _) => Some(Protocol::Sixel),
+            _ if term.starts_with("foot")
+                || term.starts_with("mlterm")
+                || term.contains("sixel") =>
+            {
+                Some(Protocol::Sixel)
+            }
+            _ => None,
+        }
+    }
+
+    /// Draw `image` over `area`, replacing whatever image was there.
+    pub fn show(self, out: &mut impl Write, image: &Image, area: Rect) -> io::Result<()> {
+        match self {
+            Protocol::Sixel => {
+                let (width, height) = cell_size();
+                let image = image.fit(
+                    u32::from(area.width) * width,
+                    u32::from(area.height) * height,
+                );
+                // cursor to the top left cell of the area, 1-based
+                write!(out, "\x1b7\x1b[{};{}H", area.y + 1, area.x + 1)?;
+                out.write_all(image.sixel().as_bytes())?;
+                write!(out, "\x1b8")?;
+                out.flush()
+            }
+            _ => self.show_png(out, &image.png(), area),
+        }
+    }
+
+    /// Draw an encoded PNG over `area`; only kitty and iTerm2 take them.
+    pub fn show_png(self, out: &mut impl Write, png: &[u8], area: Rect) -> io::Result<()> {
+        let data = STANDARD.encode(png);
+        write!(out, "\x1b7\x1b[{};{}H", area.y + 1, area.x + 1)?;
+        match self {
+            Protocol::Kitty => {
+                write!(out, "\x1b_Ga=d,d=A,q=2\x1b\\")?;
+                let chunks: Vec<&[u8]> = data.as_bytes().chunks(4096).collect();
+                for (i, chunk) in chunks.iter().enumerate() {
+                    let more = u8::from(i + 1 < chunks.len());
+                    let chunk = String::from_utf8_lossy(chunk);
+                    if i == 0 {
+                        let (c, r) = (area.width, area.height);
+                        write!(
+                            out,
+                            "\x1b_Ga=T,f=100,q=2,C=1,c={c},r={r},m={more};{chunk}\x1b\\"
+                        )?;
+                    } else {
+                        write!(out, "\x1b_Gm={more};{chunk}\x1b\\")?;
+                    }
+                }
+            }
+            Protocol::Iterm => write!(
+                out,
+                "\x1b]1337;File=inline=1;width={};height={};preserveAspectRatio=1:{}\x07",
+                area.width, area.height, data
+            )?,
+            Protocol::Sixel => {}
+        }
+        write!(out, "\x1b8")?;
+        out.flush()
+    }
+
+    /// Take any image down again (kitty keeps them apart from the text).
+    pub fn hide(self, out: &mut impl Write) -> io::Result<()> {
+        if self == Protocol::Kitty {
+            write!(out, "\x1b_Ga=d,d=A,q=2\x1b\\")?;
+        }
+        out.flush()
+    }
+}
+
+/// Images are on: how to draw them, and what is on screen now.
+pub struct Picture {
+    pub protocol: Protocol,
+    /// PNG shown instead of the generated tomato
+    pub logo: Option<Vec<u8>>,
+    /// where the last frame left room for an image, and for which
+    pub wanted: Option<(Rect, Content)>,
+    /// what was drawn last
+    pub shown: Option<(Rect, Content)>,
+}
+
+/// What goes in the room a frame left.
+#[derive(Clone, PartialEq)]
+pub enum Content {
+    /// the tomato, filled this many percent
+    Tomato(u8),
+    Logo,
+    /// focus seconds per day of the two tags compared, oldest first
+    Chart(Vec<(i64, i64)>),
+}
+
+impl Content {
+    pub fn tomato(ratio: f64) -> Self {
+        Content::Tomato((ratio.clamp(0.0, 1.0) * 100.0).round() as u8)
+    }
+
+    /// The picture for `area`, with the chart at the terminal's pixel
+    /// size; none for the logo, which is a PNG already.
+    pub fn image(&self, area: Rect) -> Option<Image> {
+        Some(match self {
+            Content::Tomato(level) => tomato(f64::from(*level) / 100.0),
+            Content::Logo => return None,
+            Content::Chart(days) => {
+                let (width, height) = cell_size();
+                chart(
+                    days,
+                    u32::from(area.width) * width,
+                    u32::from(area.height) * height,
+                )
+            }
+        })
+    }
+
+    /// Where in `area` it goes: the tomato and logo square in the middle,
+    /// the chart all over.
+    pub fn place(&self, area: Rect) -> Rect {
+        match self {
+            Content::Chart(_) => area,
+            _ => square(area),
+        }
+    }
+}
+
+/// Pixels, row by row.
+pub struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<Rgba>,
+}
+
+impl Image {
+    fn new(width: u32, height: u32) -> Self {
+        Image {
+            width,
+            height,
+            pixels: vec![CLEAR; (width * height) as usize],
+        }
+    }
+
+    fn fill(&mut self, x: u32, y: u32, width: u32, height: u32, color: Rgba) {
+        for row in y..(y + height).min(self.height) {
+            for col in x..(x + width).min(self.width) {
+                self.pixels[(row * self.width + col) as usize] = color;
+            }
+        }
+    }
+
+    /// Scaled to fit in `width` by `height`, keeping the proportions.
+    fn fit(&self, width: u32, height: u32) -> Image {
+        let scale = (f64::from(width) / f64::from(self.width))
+            .min(f64::from(height) / f64::from(self.height));
+        let (w, h) = (
+            ((f64::from(self.width) * scale) as u32).max(1),
+            ((f64::from(self.height) * scale) as u32).max(1),
+        );
+        let mut fitted = Image::new(w, h);
+        for y in 0..h {
+            for x in 0..w {
+                let (sx, sy) = (x * self.width / w, y * self.height / h);
+                fitted.pixels[(y * w + x) as usize] = self.pixels[(sy * self.width + sx) as usize];
+            }
+        }
+        fitted
+    }
+
+    fn png(&self) -> Vec<u8> {
+        png(self.width, self.height, &self.pixels.concat())
+    }
+
+    /// As a DEC sixel sequence; clear pixels leave what's behind them.
+    fn sixel(&self) -> String {
+        let mut palette: Vec<Rgba> = Vec::new();
+        for pixel in &self.pixels {
+            if pixel[3] > 0 && !palette.contains(pixel) && palette.len() < 256 {
+                palette.push(*pixel);
+            }
+        }
+        // 1: don't paint the background, pixel aspect 1:1
+        let mut out = format!("\x1bP0;1;0q\"1;1;{};{}", self.width, self.height);
+        for (i, [r, g, b, _]) in palette.iter().enumerate() {
+            let percent = |c: &u8| u32::from(*c) * 100 / 255;
+            out += &format!("#{};2;{};{};{}", i, percent(r), percent(g), percent(b));
+        }
+        for band in (0..self.height).step_by(6) {
+            for (i, color) in palette.iter().enumerate() {
+                let columns: Vec<u8> = (0..self.width)
+                    .map(|x| {
+                        (0..6)
+                            .filter(|bit| band + bit < self.height)
+                            .filter(|bit| {
+                                self.pixels[((band + bit) * self.width + x) as usize] == *color
+                            })
+                            .fold(0, |mask, bit| mask | 1 << bit)
+                    })
+                    .collect();
+                if columns.iter().all(|&mask| mask == 0) {
+                    continue;
+                }
+                out += &format!("#{}", i);
+                // runs of the same column as !<count><char>
+                let mut x = 0;
+                while x < columns.len() {
+                    let run = columns[x..]
+                        .iter()
+                        .take_while(|&&m| m == columns[x])
+                        .count();
+                    let c = char::from(63 + columns[x]);
+                    if run > 3 {
+                        out += &format!("!{}{}", run, c);
+                    } else {
+                        out.extend(std::iter::repeat_n(c, run));
+                    }
+                    x += run;
+                }
+                // back to the start of the band for the next color
+                out.push('$');
+            }
+            out.push('-');
+        }
+        out + "\x1b\\"
+    }
+}
+
+/// Pixels per cell, from the terminal when it tells, guessed otherwise.
+fn cell_size() -> (u32, u32) {
+    #[cfg(unix)]
+    {
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        let ok = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0;
+        if ok && size.ws_col > 0 && size.ws_row > 0 && size.ws_xpixel > 0 && size.ws_ypixel > 0 {
+            return (
+                u32::from(size.ws_xpixel / size.ws_col),
+                u32::from(size.ws_ypixel / size.ws_row),
+            );
+        }
+    }
+    CELL
+}
+
+/// The largest area inside `area` about as wide as it is high on screen,
+/// cells being roughly twice as tall as they are wide.
+pub fn square(area: Rect) -> Rect {
+    let rows = area.height.min(area.width / 2);
+    Rect::new(
+        area.x + (area.width - rows * 2) / 2,
+        area.y + (area.height - rows) / 2,
+        rows * 2,
+        rows,
+    )
+}
+
+/// A tomato filled `ratio` of the way up with red.
+fn tomato(ratio: f64) -> Image {
+    let (width, height) = SIZE;
+    let (cx, cy, rx, ry) = (48.0, 56.0, 44.0, 38.0);
+    let line = cy + ry - ratio * 2.0 * ry;
+    let mut image = Image::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            let (fx, fy) = (x as f64 + 0.5, y as f64 + 0.5);
+            let body = ((fx - cx) / rx).powi(2) + ((fy - cy) / ry).powi(2);
+            let stem = (fx - cx).abs() < 4.0 && (8.0..22.0).contains(&fy);
+            let leaf = ((fx - cx) / 18.0).powi(2) + ((fy - 20.0) / 5.0).powi(2) <= 1.0;
+            image.pixels[(y * width + x) as usize] = if stem || leaf {
+                [60, 150, 60, 255]
+            } else if body <= 1.0 && fy >= line {
+                [220, 45, 35, 255]
+            } else if body <= 1.0 {
+                [110, 50, 50, 255]
+            } else {
+                CLEAR
+            };
+        }
+    }
+    image
+}
+
+/// A column per day with the two tags' bars side by side, in the stats
+/// screen's cyan and magenta, over a grey baseline.
+fn chart(days: &[(i64, i64)], width: u32, height: u32) -> Image {
+    let mut image = Image::new(width.max(1), height.max(1));
+    if days.is_empty() || height < 4 {
+        return image;
+    }
+    let most = days
+        .iter()
+        .map(|&(l, r)| l.max(r))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let slot = width / days.len() as u32;
+    let bar = (slot * 2 / 5).max(1);
+    let top = height - 2;
+    for (i, &(left, right)) in days.iter().enumerate() {
+        let x = i as u32 * slot + slot / 10;
+        for (n, (secs, color)) in [(left, [0, 190, 190, 255]), (right, [190, 0, 190, 255])]
+            .into_iter()
+            .enumerate()
+        {
+            let tall = (secs.max(0) as u64 * u64::from(top)).div_ceil(most as u64) as u32;
+            image.fill(x + n as u32 * bar, top - tall, bar, tall, color);
+        }
+    }
+    image.fill(0, top, width, 2, [128, 128, 128, 255]);
+    image
+}
+
+/// An RGBA image as an uncompressed PNG; the picture is small enough that
+/// deflate isn't worth a dependency.
+fn png(width: u32, height: u32, rgba: &[u8]) -> Vec<u8> {
+    let mut raw = Vec::with_capacity(rgba.len() + height as usize);
+    for row in rgba.chunks((width * 4) as usize) {
+        // filter type none
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    // zlib with stored blocks
+    let mut zlib = vec![0x78, 0x01];
+    let blocks: Vec<&[u8]> = raw.chunks(0xffff).collect();
+    for (i, block) in blocks.iter().enumerate() {
+        zlib.push(u8::from(i + 1 == blocks.len()));
+        let len = block.len() as u16;
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut ihdr = Vec::new();
+    ihdr.extend_from_slice(&width.to_be_bytes());
+    ihdr.extend_from_slice(&height.to_be_bytes());
+    // 8 bits per channel, RGBA, deflate, no filter, no interlacing
+    ihdr.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+    for (kind, data) in [(b"IHDR", &ihdr), (b"IDAT", &zlib), (b"IEND", &Vec::new())] {
+        out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        let start = out.len();
+        out.extend_from_slice(kind);
+        out.extend_from_slice(data);
+        let crc = crc32(&out[start..]);
+        out.extend_from_slice(&crc.to_be_bytes());
+    }
+    out
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffff_u32;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes {
+        a = (a + u32::from(byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
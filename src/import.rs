@@ -0,0 +1,562 @@
+//! `pom import`: history kept by other pomodoro apps, converted into pom's
+//! own, each session marked with the app it came from. Sessions already
+//! in the history are left out, so an export can be imported again after
+//! it grew.
+//!
+//! - `pomotroid`: JSON, an array or one object per line, with a round
+//!   `type` (`work`, `short-break`, `long-break`), a start and an end or a
+//!   `duration` in seconds
+//! - `flow`: Flow's CSV export, with a start, an end or a duration, the
+//!   session type and a title, which becomes the note
+//! - `toggl-csv`: a Toggl Track detailed report as CSV. Every entry is
+//!   work; its project becomes a tag, beside its own tags
+//!
+//! Column and key names are matched loosely (`Start date`, `start_date`
+//! and `startDate` are the same), since these exports change between
+//! versions.
+
+use chrono::{DateTime, Duration, Local, NaiveDateTime, TimeZone, Utc};
+use clap::ValueEnum;
+use serde_json::Value;
+use std::{collections::HashSet, fs, path::Path};
+
+use crate::{
+    app::{Phase, Session},
+    config::Config,
+    error::{PomError, Result},
+    history::Store,
+    instance,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Source {
+    Pomotroid,
+    Flow,
+    TogglCsv,
+}
+
+impl Source {
+    /// What imported sessions are marked with.
+    fn name(self) -> &'static str {
+        match self {
+            Source::Pomotroid => "pomotroid",
+            Source::Flow => "flow",
+            Source::TogglCsv => "toggl",
+        }
+    }
+
+    fn format(self) -> &'static str {
+        match self {
+            Source::Pomotroid => "Pomotroid JSON",
+            Source::Flow => "Flow CSV",
+            Source::TogglCsv => "Toggl CSV",
+        }
+    }
+}
+
+/// One entry of an export, before it becomes a session.
+#[derive(Default)]
+struct Record {
+    /// the phase as the app calls it; work when there is none
+    kind: Option<String>,
+    started: Option<DateTime<Utc>>,
+    ended: Option<DateTime<Utc>>,
+    /// seconds
+    duration: Option<i64>,
+    completed: Option<bool>,
+    tags: Vec<String>,
+    note: Option<String>,
+}
+
+pub fn run(from: Source, path: &Path) -> Result<()> {
+    let _lock = instance::hold("importing")?;
+    let text = fs::read_to_string(path).map_err(|source| PomError::ImportRead {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let invalid = |reason: String| PomError::ImportFormat {
+        path: path.to_path_buf(),
+        format: from.format(),
+        reason,
+    };
+    let records = match from {
+        Source::Pomotroid => pomotroid(&text).map_err(invalid)?,
+        Source::Flow => flow(&text).map_err(invalid)?,
+        Source::TogglCsv => toggl(&text).map_err(invalid)?,
+    };
+
+    let mut sessions = Vec::new();
+    let mut skipped = 0;
+    for (line, record) in records {
+        match session(record, from) {
+            Ok(session) => sessions.push(session),
+            Err(reason) => {
+                eprintln!("line {} skipped: {}", line, reason);
+                skipped += 1;
+            }
+        }
+    }
+
+    let config = Config::load()?;
+    let count = sessions.len();
+    let (_, added) = Store::configured(&config.history)?.merge(sessions)?;
+    println!(
+        "{} session{} imported from {}, {} already in the history",
+        added,
+        if added == 1 { "" } else { "s" },
+        from.name(),
+        count - added
+    );
+    if skipped > 0 {
+        println!("{} skipped, see above", skipped);
+    }
+    Ok(())
+}
+
+fn session(record: Record, from: Source) -> std::result::Result<Session, String> {
+    let started = record.started.ok_or("no start time")?;
+    let ended = match (record.ended, record.duration) {
+        (Some(ended), _) => ended,
+        (None, Some(duration)) => Duration::try_seconds(duration)
+            .and_then(|duration| started.checked_add_signed(duration))
+            .ok_or("the duration is out of range")?,
+        (None, None) => return Err("no end time or duration".to_string()),
+    };
+    if ended < started {
+        return Err("it ends before it starts".to_string());
+    }
+    // a duration shorter than the span leaves pauses out
+    let elapsed = record
+        .duration
+        .unwrap_or((ended - started).num_seconds())
+        .clamp(0, (ended - started).num_seconds());
+    let phase = match record.kind.as_deref().filter(|kind| !kind.is_empty()) {
+        Some(kind) => parse_phase(kind).ok_or(format!("unknown session type `{}`", kind))?,
+        None => Phase::Work,
+    };
+    Ok(Session {
+        phase,
+        started,
+        ended,
+        planned: elapsed,
+        elapsed,
+        completed: record.completed.unwrap_or(true),
+        pauses: 0,
+        tags: record.tags,
+        note: record.note.filter(|note| !note.is_empty()),
+        source: Some(from.name().to_string()),
+        profile: None,
+    })
+}
+
+fn pomotroid(text: &str) -> std::result::Result<Vec<(usize, Record)>, String> {
+    let entries: Vec<(usize, Value)> = match serde_json::from_str::<Value>(text) {
+        Ok(Value::Array(entries)) => entries
+            .into_iter()
+            .enumerate()
+            .map(|(i, entry)| (i + 1, entry))
+            .collect(),
+        Ok(entry @ Value::Object(_)) => vec![(1, entry)],
+        // one object per line
+        _ => text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str(line)
+                    .map(|entry| (i + 1, entry))
+                    .map_err(|err| format!("line {}: {}", i + 1, err))
+            })
+            .collect::<std::result::Result<_, _>>()?,
+    };
+
+    let mut records = Vec::new();
+    for (line, entry) in entries {
+        let Value::Object(entry) = entry else {
+            return Err(format!("entry {} is not an object", line));
+        };
+        let get = |names: &[&str]| {
+            entry
+                .iter()
+                .find(|(key, _)| names.contains(&key_name(key).as_str()))
+                .map(|(_, value)| value)
+        };
+        let text = |names: &[&str]| get(names).and_then(Value::as_str).map(str::to_string);
+        let time = |names: &[&str]| {
+            get(names).and_then(|value| match value {
+                Value::String(text) => parse_time(text),
+                Value::Number(n) => n.as_i64().and_then(parse_timestamp),
+                _ => None,
+            })
+        };
+        records.push((
+            line,
+            Record {
+                kind: text(&["type", "round", "roundtype", "phase"]),
+                started: time(&["started", "start", "startedat", "starttime"]),
+                ended: time(&["ended", "end", "endedat", "endtime", "finishedat"]),
+                duration: get(&["duration", "elapsed", "seconds"]).and_then(Value::as_i64),
+                completed: get(&["completed", "finished"]).and_then(Value::as_bool),
+                tags: Vec::new(),
+                note: text(&["note", "title", "task"]),
+            },
+        ));
+    }
+    Ok(records)
+}
+
+fn flow(text: &str) -> std::result::Result<Vec<(usize, Record)>, String> {
+    let table = Table::parse(text)?;
+    let start = table.column("Start", &["start", "startdate", "starttime", "started"])?;
+    let end = table.optional(&["end", "enddate", "endtime", "ended"]);
+    let duration = table.optional(&["duration", "length", "minutes"]);
+    let kind = table.optional(&["type", "session", "sessiontype", "mode"]);
+    let title = table.optional(&["title", "task", "name", "label", "note"]);
+    if end.is_none() && duration.is_none() {
+        return Err("no end or duration column".to_string());
+    }
+
+    let mut records = Vec::new();
+    for (line, row) in table.rows() {
+        let field = |column: Option<usize>| column.map(|i| row.get(i).map_or("", String::as_str));
+        records.push((
+            line,
+            Record {
+                kind: field(kind).map(str::to_string),
+                started: field(Some(start)).and_then(parse_time),
+                ended: field(end).and_then(parse_time),
+                duration: field(duration).and_then(parse_duration),
+                note: field(title).map(str::to_string),
+                ..Record::default()
+            },
+        ));
+    }
+    Ok(records)
+}
+
+fn toggl(text: &str) -> std::result::Result<Vec<(usize, Record)>, String> {
+    let table = Table::parse(text)?;
+    let start_date = table.column("Start date", &["startdate"])?;
+    let start_time = table.column("Start time", &["starttime"])?;
+    let end_date = table.optional(&["enddate"]);
+    let end_time = table.optional(&["endtime"]);
+    let duration = table.optional(&["duration"]);
+    let project = table.optional(&["project"]);
+    let tags = table.optional(&["tags"]);
+    let description = table.optional(&["description"]);
+
+    let mut records = Vec::new();
+    for (line, row) in table.rows() {
+        let field = |column: Option<usize>| {
+            column
+                .and_then(|i| row.get(i))
+                .map_or("", |field| field.trim())
+        };
+        let at = |date: Option<usize>, time: Option<usize>| {
+            parse_time(&format!("{} {}", field(date), field(time)))
+        };
+        let mut record = Record {
+            started: at(Some(start_date), Some(start_time)),
+            ended: at(end_date, end_time),
+            duration: parse_duration(field(duration)),
+            note: Some(field(description).to_string()),
+            ..Record::default()
+        };
+        record.tags = std::iter::once(field(project))
+            .chain(field(tags).split(','))
+            .filter_map(tag)
+            .collect();
+        // the project may be among the tags too
+        let mut seen = HashSet::new();
+        record.tags.retain(|tag| seen.insert(tag.clone()));
+        records.push((line, record));
+    }
+    Ok(records)
+}
+
+/// A CSV export: its header and the rows under it, each with the line it
+/// starts on.
+struct Table {
+    header: Vec<String>,
+    rows: Vec<(usize, Vec<String>)>,
+}
+
+impl Table {
+    fn parse(text: &str) -> std::result::Result<Table, String> {
+        let mut rows = csv(text.trim_start_matches('\u{feff}')).into_iter();
+        let (_, header) = rows.next().ok_or("the file is empty")?;
+        Ok(Table {
+            header: header.iter().map(|name| key_name(name)).collect(),
+            rows: rows
+                .filter(|(_, row)| row.iter().any(|field| !field.trim().is_empty()))
+                .collect(),
+        })
+    }
+
+    fn optional(&self, names: &[&str]) -> Option<usize> {
+        self.header
+            .iter()
+            .position(|name| names.contains(&name.as_str()))
+    }
+
+    fn column(&self, label: &str, names: &[&str]) -> std::result::Result<usize, String> {
+        self.optional(names)
+            .ok_or_else(|| format!("no `{}` column", label))
+    }
+
+    fn rows(&self) -> impl Iterator<Item = (usize, &Vec<String>)> {
+        self.rows.iter().map(|(line, row)| (*line, row))
+    }
+}
+
+/// Rows of comma-separated fields, quoted fields included.
+fn csv(text: &str) -> Vec<(usize, Vec<String>)> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => row.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                rows.push((start, std::mem::take(&mut row)));
+                line += 1;
+                start = line;
+            }
+            c => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push((start, row));
+    }
+    rows
+}
+
+/// `Start date`, `start_date` and `startDate` all as `startdate`.
+fn key_name(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn parse_phase(name: &str) -> Option<Phase> {
+    let name = key_name(name);
+    if name.contains("long") {
+        Some(Phase::LongBreak)
+    } else if name.contains("break") || name.contains("short") || name == "rest" {
+        Some(Phase::ShortBreak)
+    } else if ["work", "focus", "pomodoro", "session"].contains(&name.as_str()) {
+        Some(Phase::Work)
+    } else {
+        None
+    }
+}
+
+/// RFC 3339, or a local date and time like `2026-10-13 14:00:05`.
+fn parse_time(text: &str) -> Option<DateTime<Utc>> {
+    let text = text.trim();
+    if let Ok(at) = DateTime::parse_from_rfc3339(text) {
+        return Some(at.with_timezone(&Utc));
+    }
+    if let Ok(seconds) = text.parse() {
+        return parse_timestamp(seconds);
+    }
+    let naive = [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M",
+        "%Y/%m/%d %H:%M:%S",
+        "%Y/%m/%d %H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|at| at.with_timezone(&Utc))
+}
+
+/// Seconds since the epoch, or milliseconds when it's too big for that.
+fn parse_timestamp(n: i64) -> Option<DateTime<Utc>> {
+    if n > 100_000_000_000 {
+        DateTime::from_timestamp_millis(n)
+    } else {
+        DateTime::from_timestamp(n, 0)
+    }
+}
+
+/// `01:30:00`, `25:00` or plain minutes, in seconds.
+fn parse_duration(text: &str) -> Option<i64> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    if let Ok(minutes) = text.parse::<f64>() {
+        return Some((minutes * 60.0).round() as i64);
+    }
+    let parts: Vec<i64> = text
+        .split(':')
+        .map(str::parse)
+        .collect::<std::result::Result<_, _>>()
+        .ok()?;
+    match parts[..] {
+        [h, m, s] => h.checked_mul(3600)?.checked_add(m.checked_mul(60)?)?.checked_add(s),
+        [m, s] => m.checked_mul(60)?.checked_add(s),
+        _ => None,
+    }
+}
+
+/// A project or tag name as a pom tag, spaces as dashes.
+fn tag(name: &str) -> Option<String> {
+    let tag = name.split_whitespace().collect::<Vec<_>>().join("-");
+    (!tag.is_empty()).then_some(tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(text: &str) -> DateTime<Utc> {
+        parse_time(text).unwrap()
+    }
+
+    fn sessions(records: Vec<(usize, Record)>, from: Source) -> Vec<Session> {
+        records
+            .into_iter()
+            .map(|(_, record)| session(record, from).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn reads_pomotroid() {
+        let array = r#"[
+            {"type": "work", "startedAt": "2026-10-13T09:00:00Z", "endedAt": "2026-10-13T09:25:00Z"},
+            {"round": "short-break", "start": 1792141500, "duration": 300, "completed": false}
+        ]"#;
+        let sessions = sessions(pomotroid(array).unwrap(), Source::Pomotroid);
+        assert_eq!(sessions.len(), 2);
+        assert_eq!((sessions[0].phase, sessions[0].elapsed), (Phase::Work, 1500));
+        assert_eq!(sessions[0].source.as_deref(), Some("pomotroid"));
+        assert_eq!((sessions[1].phase, sessions[1].elapsed), (Phase::ShortBreak, 300));
+        assert_eq!(sessions[1].started, DateTime::from_timestamp(1792141500, 0).unwrap());
+        assert!(!sessions[1].completed);
+
+        // one object per line, blank lines between
+        let lines = "{\"type\": \"long-break\", \"start\": 1792141500000, \"duration\": 900}\n\n{\"duration\": 60}\n";
+        let records = pomotroid(lines).unwrap();
+        assert_eq!(records.iter().map(|(line, _)| *line).collect::<Vec<_>>(), [1, 3]);
+        let mut records = records.into_iter();
+        let long = session(records.next().unwrap().1, Source::Pomotroid).unwrap();
+        assert_eq!((long.phase, long.elapsed), (Phase::LongBreak, 900));
+        assert_eq!(
+            session(records.next().unwrap().1, Source::Pomotroid),
+            Err("no start time".to_string())
+        );
+
+        assert!(pomotroid("[1]").is_err());
+        assert!(pomotroid("{not json").is_err());
+    }
+
+    #[test]
+    fn reads_flow() {
+        let csv = "\u{feff}Start Date,End Date,Type,Title\r\n\
+                   2026-10-13 09:00,2026-10-13 09:50,Focus,\"Write, then \"\"edit\"\"\"\r\n\
+                   ,,,\r\n\
+                   2026-10-13 09:50,2026-10-13 10:00,Break,\r\n";
+        let records = flow(csv).unwrap();
+        assert_eq!(records.iter().map(|(line, _)| *line).collect::<Vec<_>>(), [2, 4]);
+        let sessions = sessions(records, Source::Flow);
+        assert_eq!(sessions[0].started, local("2026-10-13 09:00"));
+        assert_eq!((sessions[0].phase, sessions[0].elapsed), (Phase::Work, 3000));
+        assert_eq!(sessions[0].note.as_deref(), Some("Write, then \"edit\""));
+        assert_eq!((sessions[1].phase, sessions[1].note.as_deref()), (Phase::ShortBreak, None));
+
+        assert_eq!(flow("Start,Type\n2026-10-13 09:00,Focus\n").err().as_deref(), Some("no end or duration column"));
+        assert_eq!(flow("End,Duration\n").err().as_deref(), Some("no `Start` column"));
+    }
+
+    #[test]
+    fn reads_toggl() {
+        let csv = "Project,Description,Start date,Start time,End date,End time,Duration,Tags\n\
+                   Pom TUI,Import,2026-10-13,09:00:00,2026-10-13,10:30:00,01:30:00,\"deep work, Pom TUI\"\n\
+                   ,Inbox,2026-10-13,11:00:00,,,00:25:00,\n";
+        let sessions = sessions(toggl(csv).unwrap(), Source::TogglCsv);
+        assert_eq!(sessions[0].tags, ["Pom-TUI", "deep-work"]);
+        assert_eq!((sessions[0].elapsed, sessions[0].note.as_deref()), (5400, Some("Import")));
+        assert_eq!(sessions[1].tags, Vec::<String>::new());
+        assert_eq!(sessions[1].ended - sessions[1].started, Duration::seconds(1500));
+        assert!(sessions.iter().all(|session| session.phase == Phase::Work));
+    }
+
+    #[test]
+    fn refuses_what_cannot_be_a_session() {
+        let record = |kind: &str, started: &str, ended: &str, duration: Option<i64>| Record {
+            kind: Some(kind.to_string()),
+            started: parse_time(started),
+            ended: parse_time(ended),
+            duration,
+            ..Record::default()
+        };
+        let session = |record| session(record, Source::Flow);
+        assert_eq!(session(record("", "2026-10-13 09:00", "", None)), Err("no end time or duration".to_string()));
+        assert_eq!(
+            session(record("", "2026-10-13 09:00", "2026-10-13 08:00", None)),
+            Err("it ends before it starts".to_string())
+        );
+        assert_eq!(
+            session(record("nap", "2026-10-13 09:00", "2026-10-13 09:25", None)),
+            Err("unknown session type `nap`".to_string())
+        );
+        assert_eq!(
+            session(record("", "2026-10-13 09:00", "", Some(i64::MAX))),
+            Err("the duration is out of range".to_string())
+        );
+        // a duration under the span leaves the pauses out
+        let paused = session(record("", "2026-10-13 09:00", "2026-10-13 09:30", Some(1500))).unwrap();
+        assert_eq!(paused.elapsed, 1500);
+    }
+
+    #[test]
+    fn parses_durations_and_times() {
+        assert_eq!(parse_duration("25"), Some(1500));
+        assert_eq!(parse_duration("0.5"), Some(30));
+        assert_eq!(parse_duration("25:00"), Some(1500));
+        assert_eq!(parse_duration(" 01:30:05 "), Some(5405));
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("1:2:3:4"), None);
+        assert_eq!(parse_duration("soon"), None);
+        assert_eq!(parse_duration("9223372036854775807:00"), None);
+
+        let at = DateTime::from_timestamp(1792141500, 0).unwrap();
+        assert_eq!(parse_time("1792141500"), Some(at));
+        assert_eq!(parse_time("1792141500000"), Some(at));
+        assert_eq!(parse_time(&at.to_rfc3339()), Some(at));
+        assert_eq!(parse_time("2026/10/13 09:00"), parse_time("2026-10-13T09:00:00"));
+        assert_eq!(parse_time("13 Oct"), None);
+
+        assert_eq!(key_name("Start date"), "startdate");
+        assert_eq!(key_name("start_Date"), "startdate");
+        assert_eq!(parse_phase("Long Break"), Some(Phase::LongBreak));
+        assert_eq!(parse_phase("rest"), Some(Phase::ShortBreak));
+        assert_eq!(parse_phase("Pomodoro"), Some(Phase::Work));
+        assert_eq!(parse_phase("nap"), None);
+    }
+}
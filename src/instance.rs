@@ -0,0 +1,111 @@
+//! One timer per data directory. The running pom holds a lock on
+//! `pom.lock` beside its state, with its pid inside, so a second one
+//! doesn't record every session twice. The lock goes with the process,
+//! however it ends, so a crash never leaves a stale one behind.
+
+use std::{
+    fs::{File, OpenOptions, TryLockError},
+    io::{Read, Seek, Write},
+    path::PathBuf,
+    process, thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    error::{PomError, Result},
+    platform,
+};
+
+/// How long a pom that was asked to quit gets to let go of the lock.
+const TAKE_OVER_SECS: u64 = 5;
+
+/// Held for as long as this pom is the one running.
+pub struct Lock {
+    /// none without a data directory, when there is nothing to share
+    _file: Option<File>,
+}
+
+pub enum Taken {
+    Held(Lock),
+    /// another pom has it; its pid, when it could be read
+    Busy(Option<u32>),
+}
+
+fn path() -> Option<PathBuf> {
+    platform::project_dirs().map(|dirs| dirs.data_local_dir().join("pom.lock"))
+}
+
+/// Take the lock, unless another pom already has it.
+pub fn lock() -> Result<Taken> {
+    let Some(path) = path() else {
+        return Ok(Taken::Held(Lock { _file: None }));
+    };
+    let failed = |source| PomError::Lock {
+        path: path.clone(),
+        source,
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(failed)?;
+    }
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)
+        .map_err(failed)?;
+    match file.try_lock() {
+        Ok(()) => {
+            file.set_len(0)
+                .and_then(|()| file.rewind())
+                .and_then(|()| write!(file, "{}", process::id()))
+                .map_err(failed)?;
+            Ok(Taken::Held(Lock { _file: Some(file) }))
+        }
+        Err(TryLockError::WouldBlock) => {
+            let mut pid = String::new();
+            let _ = file.read_to_string(&mut pid);
+            Ok(Taken::Busy(pid.trim().parse().ok()))
+        }
+        Err(TryLockError::Error(source)) => Err(failed(source)),
+    }
+}
+
+/// The lock for a subcommand that rewrites the history, which would lose
+/// whatever a running pom appends meanwhile.
+pub fn hold(doing: &'static str) -> Result<Lock> {
+    match lock()? {
+        Taken::Held(lock) => Ok(lock),
+        Taken::Busy(_) => Err(PomError::Running { doing }),
+    }
+}
+
+/// Ask the pom with `pid` to quit, and take the lock once it has. One that
+/// doesn't get asked again, which makes it quit on the spot.
+pub fn take_over(pid: u32) -> Result<Lock> {
+    for _ in 0..2 {
+        terminate(pid)?;
+        let deadline = Instant::now() + Duration::from_secs(TAKE_OVER_SECS);
+        while Instant::now() < deadline {
+            if let Taken::Held(lock) = lock()? {
+                return Ok(lock);
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+    Err(PomError::TakeOver { pid })
+}
+
+#[cfg(unix)]
+fn terminate(pid: u32) -> Result<()> {
+    // SAFETY: sends a signal, nothing more
+    if unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) } != 0 {
+        return Err(PomError::TakeOver { pid });
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn terminate(pid: u32) -> Result<()> {
+    Err(PomError::TakeOver { pid })
+}
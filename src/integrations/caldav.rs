@@ -0,0 +1,156 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Duration, Utc};
+use std::{process, time};
+
+use super::Integration;
+use crate::{
+    app::{Event, Phase},
+    config::{self, OnAbandon},
+    error::{PomError, Result},
+    platform,
+};
+
+/// Blocks out a calendar slot on a CalDAV server for each work session.
+pub struct CalDav {
+    config: config::CalDav,
+    agent: ureq::Agent,
+    /// resolved lazily so a password command only runs when needed
+    auth: Option<String>,
+    current: Option<Booking>,
+}
+
+struct Booking {
+    uid: String,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+}
+
+impl CalDav {
+    pub fn new(config: config::CalDav) -> Self {
+        let agent = ureq::Agent::config_builder()
+            .timeout_global(Some(time::Duration::from_secs(15)))
+            .build()
+            .into();
+
+        CalDav {
+            config,
+            agent,
+            auth: None,
+            current: None,
+        }
+    }
+
+    fn authorization(&mut self) -> Result<String> {
+        if let Some(auth) = &self.auth {
+            return Ok(auth.clone());
+        }
+
+        let password = match &self.config.password_command {
+            Some(command) => platform::run_secret_command(command)?,
+            None => self.config.password.clone().unwrap_or_default(),
+        };
+        let auth = format!(
+            "Basic {}",
+            STANDARD.encode(format!("{}:{}", self.config.username, password))
+        );
+        self.auth = Some(auth.clone());
+        Ok(auth)
+    }
+
+    fn href(&self, uid: &str) -> String {
+        format!("{}/{}.ics", self.config.url.trim_end_matches('/'), uid)
+    }
+
+    fn put(&mut self, booking: &Booking) -> Result<()> {
+        let auth = self.authorization()?;
+        let body = ics(booking, &self.config.summary);
+        self.agent
+            .put(&self.href(&booking.uid))
+            .header("Authorization", &auth)
+            .header("Content-Type", "text/calendar; charset=utf-8")
+            .send(body)
+            .map_err(PomError::Http)?;
+        Ok(())
+    }
+
+    fn delete(&mut self, booking: &Booking) -> Result<()> {
+        let auth = self.authorization()?;
+        self.agent
+            .delete(&self.href(&booking.uid))
+            .header("Authorization", &auth)
+            .call()
+            .map_err(PomError::Http)?;
+        Ok(())
+    }
+}
+
+impl Integration for CalDav {
+    fn name(&self) -> &'static str {
+        "caldav"
+    }
+
+    fn handle(&mut self, event: &Event) -> Result<()> {
+        match *event {
+            Event::PhaseStarted {
+                phase: Phase::Work,
+                planned,
+                at,
+            } => {
+                let booking = Booking {
+                    uid: format!("pom-{}-{}", at.timestamp_millis(), process::id()),
+                    start: at,
+                    end: at + Duration::seconds(planned),
+                };
+                self.put(&booking)?;
+                self.current = Some(booking);
+            }
+            Event::PhaseEnded {
+                phase: Phase::Work,
+                completed,
+                at,
+            } => {
+                let Some(mut booking) = self.current.take() else {
+                    return Ok(());
+                };
+
+                if !completed && self.config.on_abandon == OnAbandon::Delete {
+                    self.delete(&booking)?;
+                } else if (at - booking.end).num_seconds().abs() > 60 {
+                    // abandoned, paused or extended: make the slot match reality
+                    booking.end = at.max(booking.start + Duration::minutes(1));
+                    self.put(&booking)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn ics(booking: &Booking, summary: &str) -> String {
+    let stamp = |at: DateTime<Utc>| at.format("%Y%m%dT%H%M%SZ").to_string();
+    [
+        "BEGIN:VCALENDAR",
+        "VERSION:2.0",
+        "PRODID:-//pomtui//pom//EN",
+        "BEGIN:VEVENT",
+        &format!("UID:{}", booking.uid),
+        &format!("DTSTAMP:{}", stamp(Utc::now())),
+        &format!("DTSTART:{}", stamp(booking.start)),
+        &format!("DTEND:{}", stamp(booking.end)),
+        &format!("SUMMARY:{}", escape(summary)),
+        "TRANSP:OPAQUE",
+        "END:VEVENT",
+        "END:VCALENDAR",
+        "",
+    ]
+    .join("\r\n")
+}
+
+/// RFC 5545 TEXT escaping.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,117 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use std::time::Duration;
+
+use super::Integration;
+use crate::{
+    app::{Event, Phase},
+    config,
+    error::{PomError, Result},
+    platform,
+};
+
+/// Sends WakaTime-compatible heartbeats while a work session runs.
+pub struct Heartbeat {
+    config: config::Heartbeat,
+    agent: ureq::Agent,
+    auth: Option<String>,
+    /// inside an unpaused work session
+    working: bool,
+}
+
+impl Heartbeat {
+    pub fn new(config: config::Heartbeat) -> Self {
+        let agent = ureq::Agent::config_builder()
+            .timeout_global(Some(Duration::from_secs(15)))
+            .build()
+            .into();
+
+        Heartbeat {
+            config,
+            agent,
+            auth: None,
+            working: false,
+        }
+    }
+
+    fn authorization(&mut self) -> Result<String> {
+        if let Some(auth) = &self.auth {
+            return Ok(auth.clone());
+        }
+
+        let key = match &self.config.api_key_command {
+            Some(command) => platform::run_secret_command(command)?,
+            None => self.config.api_key.clone().unwrap_or_default(),
+        };
+        // WakaTime takes the key as the basic auth username
+        let auth = format!("Basic {}", STANDARD.encode(key));
+        self.auth = Some(auth.clone());
+        Ok(auth)
+    }
+
+    fn send(&mut self, at: DateTime<Utc>) -> Result<()> {
+        let auth = self.authorization()?;
+        let url = format!(
+            "{}/users/current/heartbeats",
+            self.config.url.trim_end_matches('/')
+        );
+        let body = json!({
+            "entity": self.config.entity,
+            "type": "app",
+            "category": self.config.category,
+            "project": self.config.project,
+            "time": at.timestamp_millis() as f64 / 1000.0,
+            "is_write": false,
+        });
+
+        self.agent
+            .post(&url)
+            .header("Authorization", &auth)
+            .send_json(body)
+            .map_err(PomError::Http)?;
+        Ok(())
+    }
+}
+
+impl Integration for Heartbeat {
+    fn name(&self) -> &'static str {
+        "heartbeat"
+    }
+
+    fn handle(&mut self, event: &Event) -> Result<()> {
+        let was_working = self.working;
+        let at = match *event {
+            Event::PhaseStarted { phase, at, .. } => {
+                self.working = phase == Phase::Work;
+                at
+            }
+            Event::PhaseEnded { at, .. } | Event::Paused { at } => {
+                self.working = false;
+                at
+            }
+            Event::Resumed { at } => {
+                self.working = true;
+                at
+            }
+            Event::Cue { .. } => return Ok(()),
+        };
+
+        if self.working != was_working {
+            // closing beat too, so the last interval before a stop counts
+            self.send(at)?;
+        }
+        Ok(())
+    }
+
+    fn interval(&self) -> Option<Duration> {
+        Some(Duration::from_secs(self.config.interval.max(30)))
+    }
+
+    fn tick(&mut self) -> Result<()> {
+        if self.working {
+            self.send(Utc::now())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,231 @@
+use chrono::{DateTime, Local};
+use std::{
+    collections::VecDeque,
+    sync::{
+        mpsc::{self, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    app::{Event, Session},
+    config::Config,
+    error::Result,
+};
+
+mod caldav;
+mod heartbeat;
+mod notion;
+#[cfg(feature = "plugins")]
+mod plugin;
+
+/// Something that mirrors timer events to the outside world.
+trait Integration {
+    fn name(&self) -> &str;
+    fn handle(&mut self, event: &Event) -> Result<()>;
+
+    /// A session that just went into the history.
+    fn recorded(&mut self, _session: &Session) -> Result<()> {
+        Ok(())
+    }
+
+    /// Slow setup, run on the integration's own thread before any event;
+    /// failing stops the integration.
+    fn start(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// How often `tick` runs between events, if at all.
+    fn interval(&self) -> Option<Duration> {
+        None
+    }
+
+    fn tick(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// How many failures the debug screen gets to see.
+const FAILURES: usize = 5;
+
+/// An integration failing, for the debug screen.
+#[derive(Clone)]
+pub struct Failure {
+    pub at: DateTime<Local>,
+    pub message: String,
+}
+
+type Failures = Arc<Mutex<VecDeque<Failure>>>;
+
+/// What an integration's thread is handed.
+enum Message {
+    Event(Event),
+    Recorded(Session),
+    /// handle what came before, then stop
+    Stop,
+}
+
+/// An integration's thread, as seen from the UI.
+struct Worker {
+    name: String,
+    messages: Sender<Message>,
+    /// hangs up when the thread ends
+    done: Mutex<mpsc::Receiver<()>>,
+}
+
+/// The configured integrations, each running on its own thread so a slow
+/// server never stalls the UI.
+pub struct Integrations {
+    workers: Vec<Worker>,
+    /// set by plugins, shown in the status bar
+    status: Arc<Mutex<Option<String>>>,
+    failures: Failures,
+}
+
+impl Integrations {
+    pub fn new(config: &Config) -> Self {
+        let mut workers = Vec::new();
+        let failures = Failures::default();
+        if let Some(caldav) = &config.caldav {
+            workers.push(spawn(caldav::CalDav::new(caldav.clone()), &failures));
+        }
+        if let Some(heartbeat) = &config.heartbeat {
+            workers.push(spawn(
+                heartbeat::Heartbeat::new(heartbeat.clone()),
+                &failures,
+            ));
+        }
+        if let Some(notion) = &config.notion {
+            workers.push(spawn(notion::Notion::new(notion.clone()), &failures));
+        }
+
+        let status = Arc::new(Mutex::new(None));
+        #[cfg(feature = "plugins")]
+        for config in &config.plugins {
+            workers.push(spawn(
+                plugin::Plugin::new(config.clone(), Arc::clone(&status)),
+                &failures,
+            ));
+        }
+        #[cfg(not(feature = "plugins"))]
+        if !config.plugins.is_empty() {
+            log::warn!("this build has no plugin support, [[plugins]] are ignored");
+        }
+
+        Integrations {
+            workers,
+            status,
+            failures,
+        }
+    }
+
+    pub fn status(&self) -> Option<String> {
+        self.status.lock().ok()?.clone()
+    }
+
+    /// The latest failures, oldest first.
+    pub fn failures(&self) -> Vec<Failure> {
+        self.failures
+            .lock()
+            .map(|failures| failures.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn dispatch(&self, event: &Event) {
+        for worker in &self.workers {
+            let _ = worker.messages.send(Message::Event(event.clone()));
+        }
+    }
+
+    pub fn record(&self, session: &Session) {
+        for worker in &self.workers {
+            let _ = worker.messages.send(Message::Recorded(session.clone()));
+        }
+    }
+
+    /// On the way out: let every integration handle what it was sent, for
+    /// up to `within` in all, and stop.
+    pub fn finish(&self, within: Duration) {
+        for worker in &self.workers {
+            let _ = worker.messages.send(Message::Stop);
+        }
+        let deadline = Instant::now() + within;
+        for worker in &self.workers {
+            let Ok(done) = worker.done.lock() else {
+                continue;
+            };
+            let left = deadline.saturating_duration_since(Instant::now());
+            if let Err(RecvTimeoutError::Timeout) = done.recv_timeout(left) {
+                log::warn!("{} didn't finish in time, what it had left is dropped", worker.name);
+            }
+        }
+    }
+}
+
+fn spawn<I: Integration + Send + 'static>(mut integration: I, failures: &Failures) -> Worker {
+    let (tx, rx) = mpsc::channel::<Message>();
+    let (finished, done) = mpsc::channel::<()>();
+    let name = integration.name().to_string();
+    let failures = Arc::clone(failures);
+    let failed = move |integration: &I, err: crate::error::PomError| {
+        let message = format!("{}: {}", integration.name(), err.report());
+        log::warn!("{}", message);
+        if let Ok(mut failures) = failures.lock() {
+            if failures.len() == FAILURES {
+                failures.pop_front();
+            }
+            failures.push_back(Failure {
+                at: Local::now(),
+                message,
+            });
+        }
+    };
+    thread::spawn(move || {
+        // dropped on the way out, however that is
+        let _finished = finished;
+        let _span = tracing::info_span!("integration", name = integration.name()).entered();
+        if let Err(err) = integration.start() {
+            failed(&integration, err);
+            return;
+        }
+        loop {
+            let message = match integration.interval() {
+                Some(interval) => match rx.recv_timeout(interval) {
+                    Ok(message) => Some(message),
+                    Err(RecvTimeoutError::Timeout) => None,
+                    Err(RecvTimeoutError::Disconnected) => return,
+                },
+                None => match rx.recv() {
+                    Ok(message) => Some(message),
+                    Err(_) => return,
+                },
+            };
+            let result = match message {
+                Some(Message::Event(event)) => {
+                    tracing::debug!(?event, "handling");
+                    integration.handle(&event)
+                }
+                Some(Message::Recorded(session)) => {
+                    tracing::debug!(started = %session.started, "recording");
+                    integration.recorded(&session)
+                }
+                Some(Message::Stop) => return,
+                None => {
+                    tracing::trace!("tick");
+                    integration.tick()
+                }
+            };
+
+            if let Err(err) = result {
+                failed(&integration, err);
+            }
+        }
+    });
+    Worker {
+        name,
+        messages: tx,
+        done: Mutex::new(done),
+    }
+}
@@ -0,0 +1,234 @@
+use chrono::Local;
+use serde_json::{json, Map, Value};
+use std::{
+    collections::VecDeque,
+    fs,
+    io::{self, BufRead},
+    path::PathBuf,
+    time::Duration,
+};
+
+use super::Integration;
+use crate::{
+    app::{Event, Phase, Session},
+    config,
+    error::{PomError, Result},
+    platform,
+};
+
+const VERSION: &str = "2022-06-28";
+
+/// Adds a row to a Notion database for each completed work session. Rows
+/// that can't be sent wait in a file and go out once Notion is reachable.
+pub struct Notion {
+    config: config::Notion,
+    agent: ureq::Agent,
+    token: Option<String>,
+    /// page bodies not sent yet, oldest first
+    queue: VecDeque<Value>,
+    /// the last try failed; no more warnings until one works
+    offline: bool,
+}
+
+impl Notion {
+    pub fn new(config: config::Notion) -> Self {
+        let agent = ureq::Agent::config_builder()
+            .timeout_global(Some(Duration::from_secs(15)))
+            .http_status_as_error(true)
+            .build()
+            .into();
+
+        Notion {
+            config,
+            agent,
+            token: None,
+            queue: VecDeque::new(),
+            offline: false,
+        }
+    }
+
+    fn token(&mut self) -> Result<String> {
+        if let Some(token) = &self.token {
+            return Ok(token.clone());
+        }
+        let token = match &self.config.token_command {
+            Some(command) => platform::run_secret_command(command)?,
+            None => self.config.token.clone().unwrap_or_default(),
+        };
+        self.token = Some(token.clone());
+        Ok(token)
+    }
+
+    /// The new page, with whichever columns are named.
+    fn row(&self, session: &Session) -> Value {
+        let mut properties = Map::new();
+        let columns = &self.config;
+        if !columns.title.is_empty() {
+            let title = match &session.note {
+                Some(note) => note.lines().next().unwrap_or_default().to_string(),
+                None if session.tags.is_empty() => "Pomodoro".to_string(),
+                None => session.tags.join(" "),
+            };
+            properties.insert(
+                columns.title.clone(),
+                json!({ "title": [{ "text": { "content": title } }] }),
+            );
+        }
+        if !columns.date.is_empty() {
+            properties.insert(
+                columns.date.clone(),
+                json!({ "date": {
+                    "start": session.started.with_timezone(&Local).to_rfc3339(),
+                    "end": session.ended.with_timezone(&Local).to_rfc3339(),
+                } }),
+            );
+        }
+        if !columns.duration.is_empty() {
+            properties.insert(
+                columns.duration.clone(),
+                json!({ "number": session.elapsed / 60 }),
+            );
+        }
+        if !columns.tags.is_empty() {
+            // commas aren't allowed in option names
+            let tags: Vec<Value> = session
+                .tags
+                .iter()
+                .map(|tag| json!({ "name": tag.replace(',', " ") }))
+                .collect();
+            properties.insert(columns.tags.clone(), json!({ "multi_select": tags }));
+        }
+        json!({
+            "parent": { "database_id": self.config.database },
+            "properties": properties,
+        })
+    }
+
+    fn send(&mut self, row: &Value) -> Result<()> {
+        let token = self.token()?;
+        let url = format!("{}/pages", self.config.url.trim_end_matches('/'));
+        self.agent
+            .post(&url)
+            .header("Authorization", &format!("Bearer {}", token))
+            .header("Notion-Version", VERSION)
+            .send_json(row)
+            .map_err(PomError::Http)?;
+        Ok(())
+    }
+
+    /// Send what is queued, in order, until one fails for lack of a
+    /// connection. Rows Notion turns down are dropped, or they would block
+    /// the rest for good.
+    fn flush(&mut self) -> Result<()> {
+        let mut result = Ok(());
+        while let Some(row) = self.queue.front().cloned() {
+            match self.send(&row) {
+                Ok(()) => {
+                    self.queue.pop_front();
+                    if self.offline {
+                        log::info!("notion: reachable again");
+                        self.offline = false;
+                    }
+                }
+                Err(PomError::Http(ureq::Error::StatusCode(code)))
+                    if (400..500).contains(&code) && code != 429 =>
+                {
+                    log::warn!("notion: row turned down with status {}, dropped", code);
+                    self.queue.pop_front();
+                }
+                Err(err) => {
+                    // one warning, not one per retry
+                    if !self.offline {
+                        result = Err(err);
+                    }
+                    self.offline = true;
+                    break;
+                }
+            }
+        }
+        self.save()?;
+        result
+    }
+
+    /// `notion-queue.jsonl` in the data directory.
+    fn path() -> Option<PathBuf> {
+        platform::project_dirs().map(|dirs| dirs.data_dir().join("notion-queue.jsonl"))
+    }
+
+    fn load(&mut self) -> Result<()> {
+        let Some(path) = Notion::path() else {
+            return Ok(());
+        };
+        let file = match fs::File::open(&path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(source) => return Err(PomError::NotionQueue { path, source }),
+        };
+        for line in io::BufReader::new(file).lines() {
+            let line = line.map_err(|source| PomError::NotionQueue {
+                path: path.clone(),
+                source,
+            })?;
+            if let Ok(row) = serde_json::from_str(&line) {
+                self.queue.push_back(row);
+            }
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = Notion::path() else {
+            return Ok(());
+        };
+        let result = if self.queue.is_empty() {
+            match fs::remove_file(&path) {
+                Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+                result => result,
+            }
+        } else {
+            let text: String = self.queue.iter().map(|row| format!("{}\n", row)).collect();
+            path.parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|()| fs::write(&path, text))
+        };
+        result.map_err(|source| PomError::NotionQueue { path, source })
+    }
+}
+
+impl Integration for Notion {
+    fn name(&self) -> &'static str {
+        "notion"
+    }
+
+    /// Rows left from an earlier run go first.
+    fn start(&mut self) -> Result<()> {
+        self.load()?;
+        if let Err(err) = self.flush() {
+            log::warn!("notion: {}", err.report());
+        }
+        Ok(())
+    }
+
+    fn handle(&mut self, _event: &Event) -> Result<()> {
+        Ok(())
+    }
+
+    fn recorded(&mut self, session: &Session) -> Result<()> {
+        if session.phase != Phase::Work || !session.completed {
+            return Ok(());
+        }
+        self.queue.push_back(self.row(session));
+        self.flush()
+    }
+
+    fn interval(&self) -> Option<Duration> {
+        Some(Duration::from_secs(300))
+    }
+
+    fn tick(&mut self) -> Result<()> {
+        if self.queue.is_empty() {
+            return Ok(());
+        }
+        self.flush()
+    }
+}
@@ -0,0 +1,261 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use wasmtime::{
+    component::{Component, HasSelf, Linker, ResourceTable},
+    Engine, Store,
+};
+use wasmtime_wasi::{WasiCtx, WasiCtxView, WasiView};
+
+use super::Integration;
+use crate::{
+    app::{self, Event},
+    config,
+    error::{PomError, Result},
+};
+
+mod bindings {
+    wasmtime::component::bindgen!({ world: "plugin", path: "wit" });
+}
+
+use bindings::pom::plugin::{
+    host::{self, Request, Response},
+    types::{self, EventKind, PhaseEnded, PhaseStarted},
+};
+
+/// Instructions a single call may run before it is cut off, so a plugin
+/// stuck in a loop fails instead of hanging its thread.
+const FUEL: u64 = 1_000_000_000;
+
+/// A third-party integration compiled to a WebAssembly component. It sees
+/// only the events it subscribed to and can only reach the outside world
+/// through the `host` interface: no files, no sockets, and HTTP just to
+/// the hosts it was allowed.
+pub struct Plugin {
+    name: String,
+    config: config::Plugin,
+    status: Arc<Mutex<Option<String>>>,
+    /// set up by `start`, dropped again when the plugin traps
+    instance: Option<Instance>,
+}
+
+struct Instance {
+    store: Store<State>,
+    plugin: bindings::Plugin,
+    subscribed: Vec<EventKind>,
+}
+
+struct State {
+    wasi: WasiCtx,
+    table: ResourceTable,
+    agent: ureq::Agent,
+    allow: Vec<String>,
+    name: String,
+    status: Arc<Mutex<Option<String>>>,
+}
+
+impl Plugin {
+    pub fn new(config: config::Plugin, status: Arc<Mutex<Option<String>>>) -> Self {
+        let name = config.path.file_stem().map_or_else(
+            || "plugin".to_string(),
+            |stem| stem.to_string_lossy().into_owned(),
+        );
+        Plugin {
+            name,
+            config,
+            status,
+            instance: None,
+        }
+    }
+
+    fn error(&self, message: String) -> PomError {
+        PomError::Plugin {
+            path: self.config.path.clone(),
+            message,
+        }
+    }
+
+    fn instantiate(&self) -> wasmtime::Result<Instance> {
+        let mut engine_config = wasmtime::Config::new();
+        engine_config.consume_fuel(true);
+        let engine = Engine::new(&engine_config)?;
+        let component = Component::from_file(&engine, &self.config.path)?;
+
+        let mut linker = Linker::new(&engine);
+        // WASI only so components built for wasip2 link; the context has no
+        // preopened directories, environment or network
+        wasmtime_wasi::p2::add_to_linker_sync(&mut linker)?;
+        bindings::Plugin::add_to_linker::<_, HasSelf<_>>(&mut linker, |state| state)?;
+
+        let agent = ureq::Agent::config_builder()
+            .timeout_global(Some(Duration::from_secs(15)))
+            // the plugin decides what a 4xx means
+            .http_status_as_error(false)
+            // a redirect could lead off the allow list, so the 3xx is
+            // the plugin's response
+            .max_redirects(0)
+            .build()
+            .into();
+        let state = State {
+            wasi: WasiCtx::builder().build(),
+            table: ResourceTable::new(),
+            agent,
+            allow: self.config.allow.clone(),
+            name: self.name.clone(),
+            status: Arc::clone(&self.status),
+        };
+        let mut store = Store::new(&engine, state);
+        store.set_fuel(FUEL)?;
+        let plugin = bindings::Plugin::instantiate(&mut store, &component, &linker)?;
+
+        Ok(Instance {
+            store,
+            plugin,
+            subscribed: Vec::new(),
+        })
+    }
+}
+
+impl Integration for Plugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn start(&mut self) -> Result<()> {
+        // compiling takes a moment, which is why it happens here
+        let mut instance = self
+            .instantiate()
+            .map_err(|err| self.error(format!("{:#}", err)))?;
+
+        let settings: Vec<(String, String)> = self
+            .config
+            .config
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        instance.subscribed = instance
+            .plugin
+            .call_init(&mut instance.store, &settings)
+            .map_err(|err| self.error(format!("{:#}", err)))?
+            .map_err(|message| self.error(message))?;
+
+        log::info!("plugin {} loaded", self.name);
+        self.instance = Some(instance);
+        Ok(())
+    }
+
+    fn handle(&mut self, event: &Event) -> Result<()> {
+        let Some(instance) = &mut self.instance else {
+            return Ok(());
+        };
+        // plugin.wit predates cues
+        let Some(event) = convert(event) else {
+            return Ok(());
+        };
+        let kind = match event {
+            types::Event::PhaseStarted(_) => EventKind::PhaseStarted,
+            types::Event::PhaseEnded(_) => EventKind::PhaseEnded,
+            types::Event::Paused(_) => EventKind::Paused,
+            types::Event::Resumed(_) => EventKind::Resumed,
+        };
+        if !instance.subscribed.contains(&kind) {
+            return Ok(());
+        }
+
+        let result = instance
+            .store
+            .set_fuel(FUEL)
+            .and_then(|()| instance.plugin.call_on_event(&mut instance.store, event));
+        match result {
+            Ok(result) => result.map_err(|message| self.error(message)),
+            Err(err) => {
+                // a trapped instance can't be entered again
+                self.instance = None;
+                Err(self.error(format!("{:#}\ndisabled until pom restarts", err)))
+            }
+        }
+    }
+}
+
+fn convert(event: &Event) -> Option<types::Event> {
+    Some(match *event {
+        Event::PhaseStarted { phase, planned, at } => types::Event::PhaseStarted(PhaseStarted {
+            phase: phase.into(),
+            planned,
+            at: at.timestamp(),
+        }),
+        Event::PhaseEnded {
+            phase,
+            completed,
+            at,
+        } => types::Event::PhaseEnded(PhaseEnded {
+            phase: phase.into(),
+            completed,
+            at: at.timestamp(),
+        }),
+        Event::Paused { at } => types::Event::Paused(at.timestamp()),
+        Event::Resumed { at } => types::Event::Resumed(at.timestamp()),
+        Event::Cue { .. } => return None,
+    })
+}
+
+impl From<app::Phase> for types::Phase {
+    fn from(phase: app::Phase) -> Self {
+        match phase {
+            app::Phase::Work => types::Phase::Work,
+            app::Phase::ShortBreak => types::Phase::ShortBreak,
+            app::Phase::LongBreak => types::Phase::LongBreak,
+        }
+    }
+}
+
+impl WasiView for State {
+    fn ctx(&mut self) -> WasiCtxView<'_> {
+        WasiCtxView {
+            ctx: &mut self.wasi,
+            table: &mut self.table,
+        }
+    }
+}
+
+impl types::Host for State {}
+
+impl host::Host for State {
+    fn http(&mut self, request: Request) -> std::result::Result<Response, String> {
+        let uri: ureq::http::Uri = request.url.parse().map_err(|err| format!("{}", err))?;
+        let host = uri.host().unwrap_or_default();
+        if !self.allow.iter().any(|allowed| allowed == host) {
+            return Err(format!("{} is not in this plugin's allow list", host));
+        }
+
+        let mut builder = ureq::http::Request::builder()
+            .method(request.method.as_str())
+            .uri(uri);
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+        let request = builder
+            .body(request.body.unwrap_or_default())
+            .map_err(|err| err.to_string())?;
+
+        let mut response = self.agent.run(request).map_err(|err| err.to_string())?;
+        Ok(Response {
+            status: response.status().as_u16(),
+            body: response
+                .body_mut()
+                .read_to_string()
+                .map_err(|err| err.to_string())?,
+        })
+    }
+
+    fn set_status(&mut self, text: Option<String>) {
+        if let Ok(mut status) = self.status.lock() {
+            *status = text;
+        }
+    }
+
+    fn log(&mut self, message: String) {
+        log::info!("{}: {}", self.name, message);
+    }
+}
@@ -0,0 +1,142 @@
+//! `pom invoice`: billable focus time per project over a date range, at
+//! the hourly rates under `[billing.rates]`, as a Markdown table or CSV.
+//!
+//! A session is billed once, to the first of its tags that falls within a
+//! project with a rate; the nearest such project above the tag wins, so
+//! `acme/support` can be billed lower than the rest of `acme`.
+
+use chrono::{Datelike, NaiveDate, Utc};
+use clap::ValueEnum;
+use std::collections::BTreeMap;
+
+use crate::{
+    app::{Phase, Session},
+    config::{Billing, Config},
+    error::{PomError, Result},
+    history::Store,
+    report,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Markdown,
+    Csv,
+}
+
+/// One project's line: focus seconds at its rate.
+struct Line<'a> {
+    project: &'a str,
+    focus: i64,
+    rate: f64,
+}
+
+impl Line<'_> {
+    fn hours(&self) -> f64 {
+        self.focus as f64 / 3600.0
+    }
+
+    /// to the cent, so the total adds up to the lines
+    fn amount(&self) -> f64 {
+        (self.hours() * self.rate * 100.0).round() / 100.0
+    }
+}
+
+pub fn run(from: Option<NaiveDate>, to: Option<NaiveDate>, format: Format) -> Result<()> {
+    let config = Config::load()?;
+    if config.billing.rates.is_empty() {
+        return Err(PomError::NoRates);
+    }
+    let today = report::day(Utc::now(), config.day_starts_at);
+    let to = to.unwrap_or(today);
+    let from = from.unwrap_or_else(|| to.with_day(1).unwrap_or(to));
+
+    let sessions = Store::configured(&config.history)?.load()?;
+    let in_range = sessions.iter().filter(|session| {
+        let date = report::local_date(session, config.day_starts_at);
+        session.phase == Phase::Work && (from..=to).contains(&date)
+    });
+    let lines = lines(&config.billing, in_range);
+    let text = match format {
+        Format::Markdown => markdown(&lines, &config.billing.currency, from, to),
+        Format::Csv => csv(&lines),
+    };
+    print!("{}", text);
+    Ok(())
+}
+
+/// Focus per billed project, by name.
+fn lines<'a>(billing: &'a Billing, sessions: impl Iterator<Item = &'a Session>) -> Vec<Line<'a>> {
+    let mut focus: BTreeMap<&str, i64> = BTreeMap::new();
+    for session in sessions {
+        let project = session.tags.iter().find_map(|tag| {
+            report::levels(tag)
+                .filter_map(|level| billing.rates.get_key_value(level))
+                .last()
+        });
+        if let Some((project, _)) = project {
+            *focus.entry(project).or_default() += session.elapsed;
+        }
+    }
+    focus
+        .into_iter()
+        .map(|(project, focus)| Line {
+            project,
+            focus,
+            rate: billing.rates[project],
+        })
+        .collect()
+}
+
+fn markdown(lines: &[Line], currency: &str, from: NaiveDate, to: NaiveDate) -> String {
+    let unit = if currency.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", currency)
+    };
+    let mut text = format!("Billable time, {} to {}\n\n", from, to);
+    text += &format!("| Project | Hours | Rate{} | Amount{} |\n", unit, unit);
+    text += "|---|--:|--:|--:|\n";
+    for line in lines {
+        text += &format!(
+            "| {} | {:.2} | {:.2} | {:.2} |\n",
+            line.project.replace('|', "\\|"),
+            line.hours(),
+            line.rate,
+            line.amount()
+        );
+    }
+    let (hours, amount) = totals(lines);
+    text += &format!("| **Total** | {:.2} | | **{:.2}** |\n", hours, amount);
+    text
+}
+
+fn csv(lines: &[Line]) -> String {
+    let mut text = String::from("project,hours,rate,amount\n");
+    for line in lines {
+        text += &format!(
+            "{},{:.2},{:.2},{:.2}\n",
+            quote(line.project),
+            line.hours(),
+            line.rate,
+            line.amount()
+        );
+    }
+    let (hours, amount) = totals(lines);
+    text += &format!("total,{:.2},,{:.2}\n", hours, amount);
+    text
+}
+
+fn totals(lines: &[Line]) -> (f64, f64) {
+    let hours = lines.iter().map(Line::hours).sum();
+    let amount = lines.iter().map(Line::amount).sum();
+    (hours, amount)
+}
+
+/// A CSV field, quoted when it has to be.
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,85 @@
+//! The daily journal: what the user said came of each work session, one
+//! Markdown file per day.
+
+use chrono::{Local, NaiveDate, NaiveTime};
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{
+    app::Session,
+    config,
+    error::{PomError, Result},
+    platform, report,
+};
+
+/// Append an entry for `session` to the file of the day it counts towards:
+///
+/// ```markdown
+/// - 14:00–14:25 #rust
+///   finished the parser refactor
+/// ```
+pub fn append(
+    config: &config::Journal,
+    day_start: NaiveTime,
+    session: &Session,
+    text: &str,
+) -> Result<()> {
+    let started = session.started.with_timezone(&Local);
+    let ended = session.ended.with_timezone(&Local);
+    let mut entry = format!(
+        "- {}\u{2013}{}",
+        started.format("%H:%M"),
+        ended.format("%H:%M")
+    );
+    for tag in &session.tags {
+        entry.push_str(&format!(" #{}", tag));
+    }
+    entry.push('\n');
+    for line in text.lines() {
+        entry.push_str(&format!("  {}\n", line));
+    }
+    write(config, report::local_date(session, day_start), &entry)
+}
+
+/// Start `date`'s file with what is left to do, as a checklist.
+pub fn carry_over(config: &config::Journal, date: NaiveDate, items: &[String]) -> Result<()> {
+    let mut entry = "Carried over:\n\n".to_string();
+    for item in items {
+        entry.push_str(&format!("- [ ] {}\n", item));
+    }
+    entry.push('\n');
+    write(config, date, &entry)
+}
+
+/// Add `entry` to the end of `date`'s file, creating it with a heading.
+fn write(config: &config::Journal, date: NaiveDate, entry: &str) -> Result<()> {
+    let Some(dir) = config
+        .dir
+        .clone()
+        .or_else(|| platform::project_dirs().map(|dirs| dirs.data_dir().join("journal")))
+    else {
+        return Ok(());
+    };
+    let path = dir.join(format!("{}.md", date.format("%Y-%m-%d")));
+
+    let mut text = String::new();
+    if !path.exists() {
+        text.push_str(&format!("# {}\n\n", date.format("%A, %B %-d %Y")));
+    }
+    text.push_str(entry);
+    append_file(&path, &text).map_err(|source| PomError::JournalWrite { path, source })
+}
+
+fn append_file(path: &Path, text: &str) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(text.as_bytes())
+}
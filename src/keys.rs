@@ -0,0 +1,418 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fmt,
+    time::{Duration, Instant},
+};
+
+use crate::error::{PomError, Result};
+
+/// How long a half-typed chord waits for its next key.
+const CHORD_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// Everything a key can be bound to.
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum Action {
+    Quit,
+    /// one more second for the next work session (input mode)
+    Increase,
+    Decrease,
+    Start,
+    Reset,
+    Toggle,
+    Skip,
+    /// five more minutes for the current phase
+    Extend,
+    /// five minutes less for the current phase
+    Trim,
+    VolumeUp,
+    VolumeDown,
+    Mute,
+    /// open or close the stats screen
+    Stats,
+    /// shorter period on the stats screen
+    PreviousPeriod,
+    /// longer period on the stats screen
+    NextPeriod,
+    /// next tag in the left column of the stats screen
+    FirstTag,
+    /// next tag in the right column
+    SecondTag,
+    /// copy the stats screen's summary to the clipboard
+    Copy,
+    /// open or close the history browser
+    History,
+    /// type a search in the history browser
+    Search,
+    /// pick a tag to focus on, searching every tag used so far
+    Focus,
+    /// pick one of the tags worked on last by its number
+    Recent,
+    /// hand out today's sessions to tasks
+    Plan,
+    /// switch the stats screen between two tags, the project tree and bars
+    View,
+    /// add up the stats bars by day, week or month
+    Group,
+    /// correct the selected session's tags and time in the history browser
+    Edit,
+    /// delete the selected session in the history browser, pressed twice
+    Delete,
+    /// write a note on the last work session
+    Note,
+    /// take back a skip or reset from the last few seconds
+    Undo,
+    /// show or hide the debug screen: draws, latency, integration errors
+    Debug,
+    /// type any action by name, plus a few that take a number
+    Palette,
+    /// switch to the next of the config's `[profiles]`
+    Profile,
+    /// set the next work session to one of the `presets` (input mode)
+    Preset1,
+    Preset2,
+    Preset3,
+    Preset4,
+    Preset5,
+}
+
+impl Action {
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Increase => "increase",
+            Action::Decrease => "decrease",
+            Action::Start => "start",
+            Action::Reset => "reset",
+            Action::Toggle => "toggle",
+            Action::Skip => "skip",
+            Action::Extend => "extend",
+            Action::Trim => "trim",
+            Action::VolumeUp => "volume-up",
+            Action::VolumeDown => "volume-down",
+            Action::Mute => "mute",
+            Action::Stats => "stats",
+            Action::PreviousPeriod => "previous-period",
+            Action::NextPeriod => "next-period",
+            Action::FirstTag => "first-tag",
+            Action::SecondTag => "second-tag",
+            Action::Copy => "copy",
+            Action::History => "history",
+            Action::Search => "search",
+            Action::Focus => "focus",
+            Action::Recent => "recent",
+            Action::Plan => "plan",
+            Action::View => "view",
+            Action::Group => "group",
+            Action::Edit => "edit",
+            Action::Delete => "delete",
+            Action::Note => "note",
+            Action::Undo => "undo",
+            Action::Debug => "debug",
+            Action::Palette => "palette",
+            Action::Profile => "profile",
+            Action::Preset1 => "preset-1",
+            Action::Preset2 => "preset-2",
+            Action::Preset3 => "preset-3",
+            Action::Preset4 => "preset-4",
+            Action::Preset5 => "preset-5",
+        }
+    }
+    /// Which of the `presets` this selects, counting from 0.
+    pub fn preset(self) -> Option<usize> {
+        match self {
+            Action::Preset1 => Some(0),
+            Action::Preset2 => Some(1),
+            Action::Preset3 => Some(2),
+            Action::Preset4 => Some(3),
+            Action::Preset5 => Some(4),
+            _ => None,
+        }
+    }
+}
+
+const DEFAULTS: &[(Action, &[&str])] = &[
+    (Action::Quit, &["q"]),
+    (Action::Increase, &["up"]),
+    (Action::Decrease, &["down"]),
+    (Action::Start, &["enter"]),
+    (Action::Reset, &["esc"]),
+    (Action::Toggle, &["p", "space"]),
+    (Action::Skip, &["s"]),
+    (Action::Extend, &["g +"]),
+    (Action::Trim, &["g -"]),
+    (Action::VolumeUp, &["+", "="]),
+    (Action::VolumeDown, &["-"]),
+    (Action::Mute, &["m"]),
+    (Action::Stats, &["tab"]),
+    (Action::PreviousPeriod, &["left"]),
+    (Action::NextPeriod, &["right"]),
+    (Action::FirstTag, &["["]),
+    (Action::SecondTag, &["]"]),
+    (Action::Copy, &["c"]),
+    (Action::History, &["h"]),
+    (Action::Search, &["/"]),
+    (Action::Focus, &["t"]),
+    (Action::Recent, &["r"]),
+    (Action::Plan, &["a"]),
+    (Action::View, &["v"]),
+    (Action::Group, &["b"]),
+    (Action::Edit, &["e"]),
+    (Action::Delete, &["d"]),
+    (Action::Note, &["n"]),
+    (Action::Undo, &["u"]),
+    (Action::Debug, &["f12"]),
+    (Action::Palette, &[":"]),
+    (Action::Profile, &["P"]),
+    (Action::Preset1, &["1"]),
+    (Action::Preset2, &["2"]),
+    (Action::Preset3, &["3"]),
+    (Action::Preset4, &["4"]),
+    (Action::Preset5, &["5"]),
+];
+
+/// Every action there is.
+pub fn actions() -> impl Iterator<Item = Action> {
+    DEFAULTS.iter().map(|&(action, _)| action)
+}
+
+/// One key press, as written in the config: `q`, `space`, `ctrl-x`, `f5`...
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Self {
+        // shift is already in the character (`+`, `G`); terminals disagree
+        // on whether to report it as well
+        let mut modifiers = event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT);
+        if !matches!(event.code, KeyCode::Char(_)) {
+            modifiers |= event.modifiers & KeyModifiers::SHIFT;
+        }
+        Key {
+            code: event.code,
+            modifiers,
+        }
+    }
+}
+
+impl From<Key> for KeyEvent {
+    fn from(key: Key) -> Self {
+        KeyEvent::new(key.code, key.modifiers)
+    }
+}
+
+impl Key {
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        loop {
+            if let Some(r) = rest.strip_prefix("ctrl-") {
+                modifiers |= KeyModifiers::CONTROL;
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix("alt-") {
+                modifiers |= KeyModifiers::ALT;
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix("shift-") {
+                modifiers |= KeyModifiers::SHIFT;
+                rest = r;
+            } else {
+                break;
+            }
+        }
+
+        let code = match rest {
+            "space" => KeyCode::Char(' '),
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backspace" => KeyCode::Backspace,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            _ if rest.chars().count() == 1 => KeyCode::Char(rest.chars().next()?),
+            _ => KeyCode::F(rest.strip_prefix('f')?.parse().ok()?),
+        };
+        Some(Key { code, modifiers })
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "alt-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "shift-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "f{}", n),
+            code => write!(f, "{}", format!("{:?}", code).to_lowercase()),
+        }
+    }
+}
+
+/// A key as the instructions show it, arrows as `^ v < >`.
+fn glyph(key: &Key) -> String {
+    match (key.code, key.modifiers.is_empty()) {
+        (KeyCode::Up, true) => "^".to_string(),
+        (KeyCode::Down, true) => "v".to_string(),
+        (KeyCode::Left, true) => "<".to_string(),
+        (KeyCode::Right, true) => ">".to_string(),
+        _ => key.to_string(),
+    }
+}
+
+/// `toggle = "p"` or `toggle = ["p", "space p"]` under `[keys]`.
+#[derive(Deserialize, Clone)]
+#[serde(untagged)]
+pub enum Binding {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Key sequences and the actions they trigger, plus whatever chord is
+/// half-typed right now.
+pub struct Keymap {
+    bindings: Vec<(Vec<Key>, Action)>,
+    pending: Vec<Key>,
+    /// when the last key of the pending chord came in
+    since: Option<Instant>,
+}
+
+impl Keymap {
+    /// The defaults, with any action in `overrides` bound only to its
+    /// configured sequences instead.
+    pub fn new(overrides: &HashMap<Action, Binding>) -> Result<Self> {
+        let mut bindings = Vec::new();
+        for &(action, defaults) in DEFAULTS {
+            let sequences: Vec<&str> = match overrides.get(&action) {
+                Some(Binding::One(sequence)) => vec![sequence.as_str()],
+                Some(Binding::Many(sequences)) => sequences.iter().map(String::as_str).collect(),
+                None => defaults.to_vec(),
+            };
+
+            for sequence in sequences {
+                let keys: Option<Vec<Key>> = sequence.split_whitespace().map(Key::parse).collect();
+                match keys {
+                    Some(keys) if !keys.is_empty() => bindings.push((keys, action)),
+                    _ => {
+                        return Err(PomError::KeyBinding {
+                            action: action.name(),
+                            sequence: sequence.to_string(),
+                        })
+                    }
+                }
+            }
+        }
+
+        Ok(Keymap {
+            bindings,
+            pending: Vec::new(),
+            since: None,
+        })
+    }
+
+    /// Feed a key press; the action it completes, if any.
+    ///
+    /// A sequence that is also the start of a longer one waits for the
+    /// chord timeout before it fires, like vim's `timeoutlen`.
+    pub fn feed(&mut self, key: Key, now: Instant) -> Option<Action> {
+        self.pending.push(key);
+        let longer = self
+            .bindings
+            .iter()
+            .any(|(keys, _)| keys.len() > self.pending.len() && keys.starts_with(&self.pending));
+        if longer {
+            self.since = Some(now);
+            return None;
+        }
+
+        // complete, or a chord that leads nowhere: either way start over
+        self.since = None;
+        let pending = std::mem::take(&mut self.pending);
+        self.lookup(&pending)
+    }
+
+    /// Give up on a chord nobody finished, firing it if it is complete.
+    pub fn expire(&mut self, now: Instant) -> Option<Action> {
+        let since = self.since?;
+        if now.duration_since(since) < CHORD_TIMEOUT {
+            return None;
+        }
+        self.since = None;
+        let pending = std::mem::take(&mut self.pending);
+        self.lookup(&pending)
+    }
+
+    /// The first sequence bound to `action`, as it would be written in
+    /// the config.
+    pub fn sequence(&self, action: Action) -> Option<String> {
+        let keys: Vec<String> = self.first(action)?.iter().map(Key::to_string).collect();
+        Some(keys.join(" "))
+    }
+
+    /// How `actions` show in the instructions: `[ g +/- ]` for extend and
+    /// trim, the start they share said once. `None` if one isn't bound.
+    pub fn label(&self, actions: &[Action]) -> Option<String> {
+        let sequences: Vec<&[Key]> = actions
+            .iter()
+            .map(|&action| self.first(action))
+            .collect::<Option<_>>()?;
+        let (first, rest) = sequences.split_first()?;
+        let shared = &first[..first.len() - 1];
+        let alike = rest
+            .iter()
+            .all(|keys| keys.len() == first.len() && keys.starts_with(shared));
+        let spell = |keys: &[Key]| keys.iter().map(glyph).collect::<Vec<_>>().join(" ");
+        let text = if alike && !shared.is_empty() {
+            let last: Vec<String> = sequences
+                .iter()
+                .map(|keys| glyph(&keys[shared.len()]))
+                .collect();
+            format!("{} {}", spell(shared), last.join("/"))
+        } else {
+            let each: Vec<String> = sequences.iter().map(|keys| spell(keys)).collect();
+            each.join("/")
+        };
+        Some(format!("[ {} ]", text))
+    }
+
+    /// `[ p ] pause/resume, [ s ] skip`, leaving out what isn't bound.
+    pub fn hints(&self, hints: &[(&[Action], &str)]) -> String {
+        let hints: Vec<String> = hints
+            .iter()
+            .filter_map(|(actions, what)| Some(format!("{} {}", self.label(actions)?, what)))
+            .collect();
+        hints.join(", ")
+    }
+
+    fn first(&self, action: Action) -> Option<&[Key]> {
+        self.bindings
+            .iter()
+            .find(|(_, bound)| *bound == action)
+            .map(|(keys, _)| keys.as_slice())
+    }
+
+    /// Keys of the chord typed so far, for the indicator.
+    pub fn pending(&self) -> &[Key] {
+        &self.pending
+    }
+
+    fn lookup(&self, keys: &[Key]) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(sequence, _)| sequence == keys)
+            .map(|(_, action)| *action)
+    }
+}
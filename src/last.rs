@@ -0,0 +1,75 @@
+//! How the last work session was set up, so the next run starts the same
+//! way and is one Enter away from repeating it.
+
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::PathBuf};
+
+use crate::{app::App, platform};
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Last {
+    /// seconds picked for the work session
+    pub time: i64,
+    pub tags: Vec<String>,
+    pub volume: Option<f32>,
+    pub muted: bool,
+}
+
+impl Last {
+    pub fn of(app: &App) -> Self {
+        Last {
+            time: app.timer.time,
+            tags: app.timer.tags.clone(),
+            volume: Some(app.volume),
+            muted: app.muted,
+        }
+    }
+
+    /// Set `app` up the same way; tags given on the command line win.
+    pub fn apply(self, app: &mut App) {
+        app.timer.time = self.time.max(0);
+        if app.timer.tags.is_empty() {
+            app.timer.tags = self.tags;
+        }
+        if let Some(volume) = self.volume {
+            app.volume = volume.clamp(0.0, 1.0);
+        }
+        app.muted = self.muted;
+    }
+}
+
+fn path() -> Option<PathBuf> {
+    platform::project_dirs().map(|dirs| dirs.data_local_dir().join("last.json"))
+}
+
+/// The last setup, or the defaults the first time and whenever the file
+/// can't be read.
+pub fn load() -> Last {
+    let Some(path) = path() else {
+        return Last::default();
+    };
+    match fs::read_to_string(&path) {
+        Ok(text) => serde_json::from_str(&text).unwrap_or_else(|err| {
+            log::warn!("ignoring {}: {}", path.display(), err);
+            Last::default()
+        }),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Last::default(),
+        Err(err) => {
+            log::warn!("could not read {}: {}", path.display(), err);
+            Last::default()
+        }
+    }
+}
+
+pub fn save(last: &Last) -> io::Result<()> {
+    let Some(path) = path() else {
+        return Ok(());
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_vec(last)?)?;
+    fs::rename(tmp, path)
+}
@@ -0,0 +1,2240 @@
+use crossterm::{
+    event::{poll, read, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use std::{
+    fs,
+    io::{self, IsTerminal, Write},
+    process::ExitCode,
+    sync::mpsc::{self, Receiver, Sender},
+    time::{Duration, Instant},
+};
+use tui::{
+    backend::{Backend, CrosstermBackend},
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans, Text},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame, Terminal,
+};
+use chrono::{self, DateTime, Local, Utc};
+use log::Level;
+// use unicode_width::UnicodeWidthStr;
+
+mod app;
+#[cfg(unix)]
+mod attach;
+mod audio;
+mod awake;
+mod backup;
+mod browser;
+mod cipher;
+mod cli;
+mod complete;
+mod config;
+mod control;
+mod crash;
+#[cfg(target_os = "linux")]
+mod dbus;
+mod degrade;
+mod diagnostics;
+mod email;
+mod error;
+mod events;
+mod export;
+mod git;
+mod goal;
+#[cfg(feature = "grpc")]
+mod grpc;
+pub mod headless;
+mod history;
+#[cfg(target_os = "linux")]
+mod hotkeys;
+mod http;
+mod idle;
+mod image;
+mod import;
+mod instance;
+mod integrations;
+mod invoice;
+mod journal;
+mod keys;
+mod last;
+mod link;
+#[cfg(target_os = "linux")]
+mod lock;
+mod logger;
+mod notify;
+mod obsidian;
+mod palette;
+mod picker;
+#[cfg(unix)]
+mod pipe;
+mod platform;
+mod power;
+mod report;
+mod review;
+#[cfg(feature = "lua")]
+mod script;
+mod signals;
+mod state;
+mod speech;
+mod stats;
+mod tasks;
+mod template;
+mod viewer;
+
+use app::{App, Command, Countdown, Grace, Mode, Note, Phase, Session};
+use pom_core::PomodoroWidget;
+use audio::Sound;
+use clap::Parser;
+use cli::{Cli, Cmd};
+use config::{Ambient, Config, Icons, LowPower, Ticking, TimerStyle};
+use control::Remote;
+use degrade::Degrade;
+use link::Link;
+use integrations::Integrations;
+use keys::{Action, Keymap};
+use error::{PomError, Result};
+use history::Store;
+use image::Content;
+use platform::Capabilities;
+use signals::Signals;
+
+/// The `pom` binary: the TUI, or one of the subcommands.
+pub fn main() -> ExitCode {
+    crash::install();
+    let cli = Cli::parse();
+    platform::locate(cli.portable, cli.data_dir.clone());
+    let result = match cli.command {
+        None => run(cli.tags, cli.profile.as_deref()),
+        Some(Cmd::Status { format }) => state::print_status(format.as_deref()),
+        Some(Cmd::Attach) => attach(cli.profile.as_deref()),
+        Some(Cmd::Report {
+            period,
+            previous,
+            template,
+            email,
+        }) => report::run(period, previous, template.as_deref(), email),
+        Some(Cmd::Log {
+            duration,
+            at,
+            tags,
+            note,
+        }) => history::log(duration, at, tags, note),
+        Some(Cmd::Invoice { from, to, format }) => invoice::run(from, to, format),
+        Some(Cmd::Obsidian { date }) => obsidian::export(date),
+        Some(Cmd::Import { from, path }) => import::run(from, &path),
+        Some(Cmd::Review) => review::run(),
+        Some(Cmd::Backup { path }) => backup::backup(&path),
+        Some(Cmd::Restore { path }) => backup::restore(&path),
+        Some(Cmd::Migrate { from, to }) => history::migrate(from, to),
+        Some(Cmd::RebuildStats) => events::rebuild(),
+        Some(Cmd::Export { anonymize }) => export::run(anonymize),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("pom: {}", err.report());
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(tags: Vec<String>, profile: Option<&str>) -> Result<()> {
+    // catch termination before touching the terminal so we can always restore it
+    let signals = Signals::register().map_err(PomError::Signals)?;
+    let config = Config::load_today(profile)?;
+    logger::init(
+        config.log_level,
+        config.log_file.as_deref(),
+        config.log_filter.as_deref(),
+        config.log_rotate,
+    )?;
+    let caps = Capabilities::detect();
+    let wants_sound = config.sound.alarm
+        || config.sound.tick != Ticking::Off
+        || config.sound.ambient != Ambient::Off;
+    if caps.player.is_none() && wants_sound {
+        log::warn!("no audio player found (paplay, pw-play, aplay, afplay), sounds are off");
+    }
+    if caps.speaker.is_none() && config.speech.enabled {
+        log::warn!("no speech synthesizer found (espeak-ng, espeak, say), announcements are off");
+    }
+    // one timer per data directory, or every session is recorded twice
+    let _lock = match instance::lock()? {
+        instance::Taken::Held(lock) => lock,
+        instance::Taken::Busy(pid) => match already_running(pid)? {
+            Busy::TakeOver(pid) => instance::take_over(pid)?,
+            Busy::Attach => return view(&signals, pid, &config),
+            Busy::Quit => return Ok(()),
+        },
+    };
+    // before the terminal is taken over, the passphrase command may prompt
+    let history = Store::configured(&config.history)?;
+
+    // setup terminal
+    setup_terminal()?;
+
+    // setup backend
+    let backend = CrosstermBackend::new(io::stdout());
+    let mut terminal = Terminal::new(backend)?;
+
+    // create app state
+    log::info!(
+        "terminal: {:?} colors, unicode {}, blink {}",
+        caps.colors,
+        caps.unicode,
+        caps.blink
+    );
+    let app = prepare(&config, &caps, tags)?;
+    let remote = Remote::new();
+    crash::watch(remote.handle());
+    #[cfg(target_os = "linux")]
+    if config.dbus {
+        dbus::serve(remote.handle());
+    }
+    #[cfg(unix)]
+    if let Some(path) = &config.command_pipe {
+        pipe::serve(path.clone(), remote.handle());
+    }
+    #[cfg(unix)]
+    attach::serve(remote.handle());
+    #[cfg(feature = "grpc")]
+    if let Some(address) = &config.grpc {
+        grpc::serve(address, remote.handle());
+    }
+    #[cfg(not(feature = "grpc"))]
+    if config.grpc.is_some() {
+        log::warn!("this build has no gRPC support, `grpc` is ignored");
+    }
+    if let Some(address) = &config.http {
+        http::serve(address, config.http_origin.clone(), remote.handle());
+    }
+    #[cfg(target_os = "linux")]
+    hotkeys::serve(&config.hotkeys, remote.handle());
+    #[cfg(not(target_os = "linux"))]
+    if !config.hotkeys.bindings().is_empty() {
+        log::warn!("global hotkeys need the desktop portal, which this platform lacks");
+    }
+
+    let services = Services::new(&config, &caps, remote, history, true);
+
+    let result = run_app(&mut terminal, &mut Tty, app, &signals, &config, &caps, &services);
+
+    // nothing is running any more, whether or not the terminal survives
+    state::remove();
+    #[cfg(unix)]
+    attach::remove();
+
+    // restore terminal, even if the app failed, before reporting anything
+    restore_terminal()?;
+    terminal.show_cursor()?;
+
+    result
+}
+
+/// What to do when another pom has the lock.
+enum Busy {
+    Attach,
+    TakeOver(u32),
+    Quit,
+}
+
+/// Ask, before the terminal is taken over, what to do about the pom that
+/// is already running.
+fn already_running(pid: Option<u32>) -> Result<Busy> {
+    if !io::stdin().is_terminal() {
+        return Err(PomError::AlreadyRunning);
+    }
+    let prompt = match pid {
+        Some(pid) => format!("pom is already running here (pid {}): [a]ttach to it, [t]ake over or [q]uit? ", pid),
+        None => "pom is already running here: [a]ttach to it or [q]uit? ".to_string(),
+    };
+    loop {
+        eprint!("{}", prompt);
+        io::stderr().flush()?;
+        let mut line = String::new();
+        if io::stdin().read_line(&mut line)? == 0 {
+            return Ok(Busy::Quit);
+        }
+        match (line.trim().to_lowercase().as_str(), pid) {
+            ("a" | "attach", _) => return Ok(Busy::Attach),
+            ("t" | "take over", Some(pid)) => return Ok(Busy::TakeOver(pid)),
+            ("q" | "quit" | "", _) => return Ok(Busy::Quit),
+            _ => {}
+        }
+    }
+}
+
+/// `pom attach`: the running pom's timer, here too.
+fn attach(profile: Option<&str>) -> Result<()> {
+    let signals = Signals::register().map_err(PomError::Signals)?;
+    let config = Config::load_today(profile)?;
+    match instance::lock()? {
+        instance::Taken::Held(_) => Err(PomError::NotRunning),
+        instance::Taken::Busy(pid) => view(&signals, pid, &config),
+    }
+}
+
+/// The pom that has the lock, attached to it or, without its socket,
+/// read-only.
+fn view(signals: &Signals, pid: Option<u32>, config: &Config) -> Result<()> {
+    let keys = Keymap::new(&config.keys)?;
+    #[cfg(unix)]
+    let link = viewer::Link::connect();
+    #[cfg(not(unix))]
+    let link = None;
+    setup_terminal()?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+    let result = viewer::run(&mut terminal, signals, pid, link, keys);
+    restore_terminal()?;
+    terminal.show_cursor()?;
+    result
+}
+
+fn setup_terminal() -> io::Result<()> {
+    enable_raw_mode()?;
+    crash::terminal(true);
+    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)
+}
+
+fn restore_terminal() -> io::Result<()> {
+    crash::terminal(false);
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture)
+}
+
+/// Give the terminal back to the shell and stop until we are foregrounded.
+fn suspend() -> io::Result<()> {
+    restore_terminal()?;
+    signals::stop()
+}
+
+/// What runs alongside the UI, fed and polled by the main loop.
+struct Services {
+    remote: Remote,
+    history: Store,
+    /// buttons pressed on time's-up notifications
+    answer: Sender<Answer>,
+    answers: Receiver<Answer>,
+    integrations: Integrations,
+    idle: Option<idle::Monitor>,
+    awake: Option<awake::Awake>,
+    power: Option<power::Monitor>,
+    #[cfg(target_os = "linux")]
+    lock: Option<lock::Monitor>,
+    #[cfg(feature = "lua")]
+    script: Option<script::Script>,
+}
+
+impl Services {
+    /// What `config` asks for. Without `system`, nothing watches or holds
+    /// the machine (idle, sleep, power, the screen lock) and the user's
+    /// script doesn't run, as in a headless run.
+    fn new(config: &Config, caps: &Capabilities, remote: Remote, history: Store, system: bool) -> Services {
+        #[cfg(feature = "lua")]
+        let script = config
+            .script
+            .clone()
+            .filter(|_| system)
+            .map(|path| script::Script::start(path, remote.handle(), caps.notifier.clone()));
+        #[cfg(not(feature = "lua"))]
+        if config.script.is_some() {
+            log::warn!("this build has no Lua support, the script is ignored");
+        }
+        #[cfg(not(feature = "lua"))]
+        let _ = caps;
+
+        let (answer, answers) = mpsc::channel();
+        Services {
+            remote,
+            history,
+            answer,
+            answers,
+            integrations: Integrations::new(config),
+            idle: (system && config.auto_pause.idle > 0).then(idle::Monitor::start),
+            awake: (system && config.keep_awake).then(awake::Awake::start),
+            power: (system && config.low_power == LowPower::Auto).then(power::Monitor::start),
+            #[cfg(target_os = "linux")]
+            lock: (system && config.auto_pause.on_lock).then(lock::Monitor::start),
+            #[cfg(feature = "lua")]
+            script,
+        }
+    }
+}
+
+/// The app as `config` and the terminal have it, before the loop starts.
+fn prepare(config: &Config, caps: &Capabilities, tags: Vec<String>) -> Result<App> {
+    let mut app = App::new(config)?;
+    app.icons = match config.display.icons {
+        Some(icons) if caps.unicode || icons == Icons::Ascii => icons,
+        Some(_) => {
+            log::warn!("the terminal doesn't look like UTF-8, icons are ASCII");
+            Icons::Ascii
+        }
+        None if caps.unicode => Icons::Emoji,
+        None => Icons::Ascii,
+    };
+    app.degrade = Degrade::new(caps);
+    app.links = (config.links.enabled && caps.hyperlinks).then(Vec::new);
+    app.timer.tags = tags;
+    app.timer.cues = config.cues.at.clone();
+    app.timer.profile = config.profile.clone();
+    app.timer.hold = !config.auto_advance;
+    if config.remember {
+        last::load().apply(&mut app);
+    }
+    Ok(app)
+}
+
+/// Where the event loop's input comes from: the terminal, or a script for
+/// a [`headless`] run.
+trait Input {
+    /// The next event, waiting up to `timeout` for one.
+    fn next(&mut self, timeout: Duration) -> io::Result<Option<Event>>;
+
+    /// Nothing more is coming: the loop ends once it has drawn the rest.
+    fn finished(&self) -> bool {
+        false
+    }
+}
+
+/// The terminal's own keys, mouse and resizes.
+struct Tty;
+
+impl Input for Tty {
+    fn next(&mut self, timeout: Duration) -> io::Result<Option<Event>> {
+        if poll(timeout)? {
+            read().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// A button on a time's-up notification, and the phase it came up in:
+/// one pressed after the timer moved on is too late.
+struct Answer {
+    button: Button,
+    phase: Phase,
+    round: u32,
+}
+
+#[derive(Clone, Copy)]
+enum Button {
+    Start,
+    Skip,
+    Extend,
+}
+
+#[tracing::instrument(name = "event_loop", skip_all)]
+fn run_app<B: Backend>(
+    terminal: &mut Terminal<B>,
+    input: &mut impl Input,
+    mut app: App,
+    signals: &Signals,
+    config: &Config,
+    caps: &Capabilities,
+    services: &Services,
+) -> Result<()> {
+    let result = event_loop(terminal, input, &mut app, signals, config, caps, services);
+
+    // quitting mid-phase abandons it, as far as the outside world knows;
+    // it was never recorded, so it isn't now either
+    app.timer.quit(Utc::now());
+    for event in app.timer.take_events() {
+        if let Some(entry) = events::Entry::of(&event, &app.timer.tags) {
+            if let Err(err) = services.history.log(&entry) {
+                log::warn!("{}", err.report());
+            }
+        }
+        services.integrations.dispatch(&event);
+        #[cfg(feature = "lua")]
+        if let Some(script) = &services.script {
+            script.event(&event, app.timer.round);
+        }
+    }
+    services.integrations.finish(Duration::from_secs(3));
+    result
+}
+
+fn event_loop<B: Backend>(
+    terminal: &mut Terminal<B>,
+    input: &mut impl Input,
+    app: &mut App,
+    signals: &Signals,
+    config: &Config,
+    caps: &Capabilities,
+    services: &Services,
+) -> Result<()> {
+    // redraw when something happened, and otherwise at the display's pace
+    let mut dirty = true;
+    let mut next_frame = Instant::now();
+    // a profile switched to, or an autostart's, takes over the settings
+    // from then on
+    let mut switched: Option<Config> = None;
+    let mut checked = Local::now();
+
+    // what is planned for today, and how far along it is
+    app.tasks = tasks::load();
+    let today = report::day(Utc::now(), config.day_starts_at);
+    if app.tasks.recur(today) {
+        if let Err(err) = tasks::save(&app.tasks) {
+            log::warn!("could not save the task list: {}", err);
+        }
+    }
+    match services.history.tallies(config.day_starts_at) {
+        Ok(tallies) => app.done = tasks::Done::new(&tallies, today),
+        Err(err) => log::warn!("{}", err.report()),
+    }
+    match services.history.load() {
+        Ok(sessions) => app.streak = goal::Streak::new(&sessions, today, config.goal.daily, config),
+        Err(err) => log::warn!("{}", err.report()),
+    }
+    if config.plan && app.tasks.day != Some(today) {
+        open_planner(app, config, &services.history);
+    }
+
+    loop {
+        if let Some(profile) = autostart(app, switched.as_ref().unwrap_or(config), caps, &mut checked) {
+            app.switch(switched.as_ref().unwrap_or(config), &profile);
+            switched = Some(profile);
+        }
+        if let Some(name) = app.switching.take() {
+            match Config::load_profile(Some(&name)) {
+                Ok(profile) => {
+                    app.switch(switched.as_ref().unwrap_or(config), &profile);
+                    log::info!("switched to the {} profile", name);
+                    switched = Some(profile);
+                    dirty = true;
+                }
+                Err(err) => log::warn!("{}", err.report()),
+            }
+        }
+        let config = switched.as_ref().unwrap_or(config);
+
+        // SIGTERM/SIGHUP/SIGINT: leave the loop so main restores the terminal
+        if signals.should_terminate() {
+            return Ok(());
+        }
+
+        // ^Z from another process; raw mode means our own ^Z arrives as a key
+        if signals.take_suspend() {
+            app.ambient = None;
+            suspend()?;
+        }
+
+        // back from the background (or an external SIGSTOP): take over again
+        if signals.take_resumed() {
+            setup_terminal()?;
+            terminal.clear()?;
+            if let Some(picture) = &mut app.picture {
+                picture.shown = None;
+            }
+            dirty = true;
+        }
+
+        if dirty || Instant::now() >= next_frame {
+            let due = (!dirty).then_some(next_frame);
+            let start = Instant::now();
+            if app.debug {
+                app.diagnostics.failures = services.integrations.failures();
+            }
+            terminal.draw(|f| {
+                ui(f, app, config);
+                if let Some(degrade) = app.degrade {
+                    f.render_widget(degrade, f.size());
+                }
+            })?;
+            if let Some(links) = &app.links {
+                link::write(&mut io::stdout(), links, app.degrade)?;
+            }
+            // taking the image down clears the screen, to be drawn again
+            dirty = show_picture(app, terminal)?;
+            app.diagnostics.drawn(start, due);
+            next_frame = Instant::now() + frame_interval(app, config);
+            if input.finished() {
+                return Ok(());
+            }
+        }
+
+        // non-blocking event read, short enough to keep up with commands
+        // and signals between frames
+        let timeout = next_frame
+            .saturating_duration_since(Instant::now())
+            .min(Duration::from_millis(100));
+        if let Some(event) = input.next(timeout)? {
+            // keys, releases, resizes: all worth a fresh frame
+            dirty = true;
+            app.diagnostics.input();
+            if let (Event::Resize(..), Some(picture)) = (&event, &mut app.picture) {
+                picture.shown = None;
+            }
+            if let Event::Mouse(mouse) = event {
+                scroll(app, mouse);
+                drag(app, mouse);
+            }
+            if let Event::Key(key) = event {
+                // terminals with key enhancement (and Windows consoles) also
+                // report releases, which would otherwise act as a second press
+                if key.kind == KeyEventKind::Release {
+                    app.hold = None;
+                    continue;
+                }
+
+                // the break screen swallows everything but holding space
+                if app.enforcing(config) {
+                    app.ringing = None;
+                    hold_to_unlock(app, key, config);
+                    continue;
+                }
+
+                if caps.job_control
+                    && key.code == KeyCode::Char('z')
+                    && key.modifiers.contains(KeyModifiers::CONTROL)
+                {
+                    app.ambient = None;
+                    suspend()?;
+                    continue;
+                }
+
+                // the key that acknowledges the alarm does nothing else
+                if app.ringing.take().is_some() {
+                    continue;
+                }
+
+                if app.confirm.is_some() {
+                    confirm_abandon(app, key, config);
+                    continue;
+                }
+
+                // keys are text while a note is being typed
+                if app.note.is_some() {
+                    edit_note(app, key, config, &services.history);
+                    continue;
+                }
+
+                if app.picker.is_some() {
+                    pick(app, key, &services.history);
+                    continue;
+                }
+
+                if app.planner.is_some() {
+                    plan(app, key);
+                    continue;
+                }
+
+                if app.palette.is_some() {
+                    let chosen = choose(app, key);
+                    if chosen.is_some_and(|choice| run_choice(app, choice, config, &services.history)) {
+                        return Ok(());
+                    }
+                    continue;
+                }
+
+                if app.browser.as_ref().is_some_and(|browser| browser.editing.is_some()) {
+                    edit_entry(app, key, &services.history);
+                    continue;
+                }
+
+                if app.browser.as_ref().is_some_and(|browser| browser.searching) {
+                    edit_search(app, key, &services.history);
+                    continue;
+                }
+
+                if let Some(action) = app.keys.feed(key.into(), Instant::now()) {
+                    if perform(app, action, config, &services.history) {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+
+        if let Some(action) = app.keys.expire(Instant::now()) {
+            dirty = true;
+            if perform(app, action, config, &services.history) {
+                return Ok(());
+            }
+        }
+
+        for command in services.remote.commands() {
+            dirty = true;
+            remote(app, config, command);
+        }
+        for answer in services.answers.try_iter() {
+            if (answer.phase, answer.round) == (app.timer.phase, app.timer.round) {
+                dirty = true;
+                answered(app, config, answer.button);
+            }
+        }
+
+        if let Some(monitor) = &services.idle {
+            auto_pause(app, config, monitor.idle());
+        }
+        let low_power = match config.low_power {
+            LowPower::Always => true,
+            LowPower::Never => false,
+            LowPower::Auto => services.power.as_ref().is_some_and(power::Monitor::on_battery),
+        };
+        if low_power != app.low_power {
+            dirty = true;
+            app.low_power = low_power;
+            if config.low_power == LowPower::Auto {
+                log::info!("on {}", if low_power { "battery, saving power" } else { "mains power" });
+            }
+        }
+        #[cfg(target_os = "linux")]
+        if let Some(locked) = services.lock.as_ref().and_then(lock::Monitor::take_change) {
+            lock_pause(app, config, locked);
+        }
+
+        let previous = app.timer.elapsed;
+        let now = Utc::now();
+        let ran_out = app.timer.update(now);
+        app.diagnostics.ticked(previous, app.timer.elapsed);
+        dirty |= app.timer.elapsed != previous;
+        if ran_out && app.grace.is_none() {
+            // on to the next phase, after the grace period if there is one;
+            // the alarm tells the user
+            if config.grace > 0 {
+                let round = app.timer.round + u32::from(app.timer.phase == Phase::Work);
+                app.grace = Some(Grace {
+                    until: now + chrono::Duration::seconds(config.grace as i64),
+                    next: config.schedule.after(app.timer.phase, true, round),
+                });
+            } else {
+                app.timer.advance(&config.schedule, now);
+            }
+
+            alert(app, config, caps, &services.answer);
+            if config.alarm.insistent {
+                app.ringing = Some(Instant::now());
+            }
+        } else if app.grace.as_ref().is_some_and(|grace| now >= grace.until) {
+            app.grace = None;
+            app.timer.advance(&config.schedule, now);
+        } else if app.timer.elapsed != previous && app.timer.phase == Phase::Work {
+            tick(app, config, caps);
+        }
+        let warn_at = config.speech.warn_at * 60;
+        if app.timer.elapsed != previous
+            && warn_at > 0
+            && app.timer.remaining() == warn_at
+            && app.timer.planned > warn_at
+        {
+            say(config, caps, &format!("{} left", speech::minutes(config.speech.warn_at)));
+        }
+
+        app.status_text = services.integrations.status();
+        #[cfg(feature = "lua")]
+        if let Some(script) = &services.script {
+            if !ran_out && app.timer.elapsed != previous {
+                script.tick(app.timer.phase, app.timer.remaining());
+            }
+            // the user's own script wins over plugins
+            if let Some(text) = script.status() {
+                app.status_text = Some(text);
+            }
+        }
+
+        for event in app.timer.take_events() {
+            dirty = true;
+            if let app::Event::PhaseStarted { phase, planned, .. } = event {
+                app.unlocked = false;
+                say(config, caps, &format!("{}, {}", phase.name(), speech::minutes(planned / 60)));
+            }
+            if let app::Event::Cue { cue, .. } = event {
+                cued(app, config, caps, cue);
+            }
+            if let app::Event::PhaseStarted { phase: Phase::Work, .. } = event {
+                if config.remember {
+                    if let Err(err) = last::save(&last::Last::of(app)) {
+                        log::warn!("could not remember the setup: {}", err);
+                    }
+                }
+            }
+            if let app::Event::PhaseEnded { phase: Phase::Work, completed: true, .. } = event {
+                if config.journal.is_some() && app.note.is_none() {
+                    app.note = Some(Note {
+                        index: app.timer.sessions.len() - 1,
+                        text: String::new(),
+                        journal: true,
+                    });
+                }
+            }
+            if let Some(entry) = events::Entry::of(&event, &app.timer.tags) {
+                if let Err(err) = services.history.log(&entry) {
+                    log::warn!("{}", err.report());
+                }
+            }
+            tracing::debug!(?event, "timer");
+            services.integrations.dispatch(&event);
+            #[cfg(feature = "lua")]
+            if let Some(script) = &services.script {
+                script.event(&event, app.timer.round);
+            }
+        }
+
+        for (i, session) in app.timer.sessions.iter().enumerate().skip(app.recorded) {
+            if let Err(err) = services.history.append(session) {
+                log::warn!("{}", err.report());
+            }
+            services.integrations.record(session);
+            // with the journal's answer, once it is given
+            if app.note.as_ref().is_none_or(|note| !note.journal || note.index != i) {
+                to_obsidian(config, session);
+            }
+            if let Some(stats) = &mut app.stats {
+                stats.add(session.clone());
+            }
+            if let Some(picker) = &mut app.picker {
+                picker.add(session);
+            }
+            app.done.add(session, report::day(session.started, config.day_starts_at), &app.tasks);
+            app.streak.add(session, report::day(session.started, config.day_starts_at), config);
+        }
+        if app.recorded < app.timer.sessions.len() {
+            if let Some(browser) = &mut app.browser {
+                if let Err(err) = browser.refresh(&services.history) {
+                    log::warn!("{}", err.report());
+                }
+            }
+        }
+        app.recorded = app.timer.sessions.len();
+
+        services.remote.publish(app.timer.snapshot());
+        services.remote.share(app.context());
+        if let Some(awake) = &services.awake {
+            awake.hold(app.timer.mode == Mode::Running && !app.timer.is_paused());
+        }
+        ambient(app, config, caps);
+
+        let repeat_every = Duration::from_secs(config.alarm.repeat_every.max(1));
+        if app.ringing.is_some_and(|last| last.elapsed() >= repeat_every) {
+            alert(app, config, caps, &services.answer);
+            app.ringing = Some(Instant::now());
+        }
+
+        publish_status(app, config);
+    }
+}
+
+/// How long the screen may go without a redraw when nothing happens: the
+/// gauge animates between seconds, digits only change once a second.
+fn frame_interval(app: &App, config: &Config) -> Duration {
+    let still = config.display.reduced_motion || app.low_power;
+    let animating = config.display.style == TimerStyle::Gauge
+        && !still
+        && matches!(app.timer.mode, Mode::Running)
+        && !app.timer.is_paused();
+    let counting_tenths = config.display.tenths
+        && !still
+        && app.timer.mode == Mode::Running
+        && !app.timer.is_paused()
+        && app.timer.remaining_ms(Utc::now()) < 10_000;
+    if animating {
+        Duration::from_secs(1) / config.display.fps.clamp(1, 120)
+    } else if counting_tenths {
+        Duration::from_millis(100)
+    } else {
+        Duration::from_secs(1)
+    }
+}
+
+/// The wheel over the timer sets the time while it's being picked: a
+/// second a notch, like the arrow keys, or a minute with a modifier held.
+fn scroll(app: &mut App, mouse: MouseEvent) {
+    let notch = match mouse.kind {
+        MouseEventKind::ScrollUp => 1,
+        MouseEventKind::ScrollDown => -1,
+        _ => return,
+    };
+    let over = app.timer_area.intersects(Rect::new(mouse.column, mouse.row, 1, 1));
+    if !over
+        || app.timer.mode != Mode::Input
+        || app.ringing.is_some()
+        || app.note.is_some()
+        || app.autostart.is_some()
+    {
+        return;
+    }
+    let step = if mouse.modifiers.is_empty() { 1 } else { 60 };
+    app.timer.time = (app.timer.time + notch * step).max(0);
+}
+
+/// Rows on the planning screen picked up with the mouse and dropped
+/// elsewhere in the list, which keeps the order.
+fn drag(app: &mut App, mouse: MouseEvent) {
+    let Some(planner) = &mut app.planner else {
+        return;
+    };
+    let over = planner.row_at(app.planner_area, mouse.column, mouse.row);
+    match (mouse.kind, over) {
+        (MouseEventKind::Down(MouseButton::Left), Some(i)) => {
+            planner.select_at(i);
+            planner.dragging = true;
+        }
+        (MouseEventKind::Drag(MouseButton::Left), Some(i)) if planner.dragging => {
+            let mut moved = false;
+            while planner.selected() != i && planner.shift(if i < planner.selected() { -1 } else { 1 }) {
+                moved = true;
+            }
+            if moved {
+                keep_tasks(app);
+            }
+        }
+        (MouseEventKind::Up(_), _) => planner.dragging = false,
+        _ => {}
+    }
+}
+
+/// Keep the task list in the order the planning screen shows it, with
+/// what it has archived.
+fn keep_tasks(app: &mut App) {
+    let Some(planner) = &app.planner else {
+        return;
+    };
+    app.tasks = planner.order(&app.tasks);
+    if let Err(err) = tasks::save(&app.tasks) {
+        log::warn!("could not save the task list: {}", err);
+    }
+}
+
+/// Carry out a bound action; true when it's time to quit.
+fn perform(app: &mut App, action: Action, config: &Config, history: &Store) -> bool {
+    // esc opts out of a scheduled start, enter takes it right away
+    if let Some(countdown) = &mut app.autostart {
+        match action {
+            Action::Reset => {
+                app.autostart = None;
+                log::info!("scheduled start skipped");
+                return false;
+            }
+            Action::Start => {
+                countdown.until = Utc::now();
+                return false;
+            }
+            _ => {}
+        }
+    }
+
+    // over any screen, which then gets what is picked
+    if action == Action::Palette {
+        app.palette = Some(palette::Palette::new(&app.keys));
+        return false;
+    }
+
+    // the stats screen has keys of its own; the timer carries on behind it
+    // and can still be paused
+    let reaches_timer = matches!(
+        action,
+        Action::Toggle | Action::VolumeUp | Action::VolumeDown | Action::Mute
+    );
+    if let Some(stats) = app.stats.as_mut().filter(|_| !reaches_timer) {
+        match action {
+            Action::PreviousPeriod => stats.period = stats.period.step(-1),
+            Action::NextPeriod => stats.period = stats.period.step(1),
+            Action::FirstTag => stats.cycle(false),
+            Action::SecondTag => stats.cycle(true),
+            Action::View => stats.next_view(),
+            Action::Group => stats.group = stats.group.next(),
+            Action::Copy => {
+                let summary = stats.summary();
+                copy(app, summary);
+            }
+            Action::Stats | Action::Reset | Action::Quit => app.stats = None,
+            _ => {}
+        }
+        return false;
+    }
+
+    // so has the history browser
+    if let Some(browser) = app.browser.as_mut().filter(|_| !reaches_timer) {
+        let deleting = std::mem::take(&mut browser.deleting);
+        let refresh = match action {
+            Action::Increase => {
+                browser.select(-1);
+                false
+            }
+            Action::Decrease => {
+                browser.select(1);
+                false
+            }
+            Action::PreviousPeriod | Action::NextPeriod => {
+                let by = if action == Action::NextPeriod { 1 } else { -1 };
+                browser.period = browser.period.step(by);
+                true
+            }
+            Action::FirstTag => {
+                browser.cycle_tag();
+                true
+            }
+            Action::SecondTag => {
+                browser.cycle_outcome();
+                true
+            }
+            Action::Search => {
+                browser.searching = true;
+                false
+            }
+            Action::Edit => {
+                browser.editing = browser.selected().map(browser::editable);
+                false
+            }
+            Action::Delete => {
+                if let Some(session) = browser.selected().cloned() {
+                    if deleting {
+                        amend(app, history, session, None);
+                    } else {
+                        browser.deleting = true;
+                    }
+                }
+                false
+            }
+            Action::History | Action::Reset | Action::Quit => {
+                app.browser = None;
+                false
+            }
+            _ => false,
+        };
+        if let Some(browser) = app.browser.as_mut().filter(|_| refresh) {
+            if let Err(err) = browser.refresh(history) {
+                log::warn!("{}", err.report());
+            }
+        }
+        return false;
+    }
+
+    // volume works in every mode
+    let command = match (&app.timer.mode, action) {
+        (_, Action::VolumeUp) => {
+            app.change_volume(0.1);
+            None
+        }
+        (_, Action::VolumeDown) => {
+            app.change_volume(-0.1);
+            None
+        }
+        (_, Action::Mute) => {
+            app.toggle_mute();
+            None
+        }
+        (_, Action::Note) => {
+            let last = app.timer.sessions.iter().rposition(|s| s.phase == Phase::Work);
+            match last {
+                Some(index) => {
+                    let text = app.timer.sessions[index].note.clone().unwrap_or_default();
+                    app.note = Some(Note {
+                        index,
+                        text,
+                        journal: false,
+                    });
+                }
+                None => log::info!("no work session has ended yet"),
+            }
+            None
+        }
+        (_, Action::Debug) => {
+            app.debug = !app.debug;
+            None
+        }
+        (_, Action::Profile) => {
+            app.switching = next_profile(config);
+            None
+        }
+        (_, Action::Undo) => {
+            match app.timer.undo(Utc::now()) {
+                Some(session) => {
+                    // already in the history: take it out again
+                    if app.recorded > app.timer.sessions.len() {
+                        app.recorded = app.timer.sessions.len();
+                        if let Err(err) = history.remove(&session) {
+                            log::warn!("{}", err.report());
+                        }
+                    }
+                    if app.note.as_ref().is_some_and(|note| note.index >= app.timer.sessions.len()) {
+                        app.note = None;
+                    }
+                    log::info!("undone, back to the {}", session.phase.name());
+                }
+                None => log::info!("nothing to undo"),
+            }
+            None
+        }
+        (_, Action::History) => {
+            match browser::Browser::new(history, config.day_starts_at) {
+                Ok(browser) => app.browser = Some(browser),
+                Err(err) => log::warn!("{}", err.report()),
+            }
+            None
+        }
+        (_, Action::Focus) => {
+            match picker::Picker::new(history, Utc::now(), config.day_starts_at) {
+                Ok(picker) => app.picker = Some(picker),
+                Err(err) => log::warn!("{}", err.report()),
+            }
+            None
+        }
+        (_, Action::Plan) => {
+            open_planner(app, config, history);
+            None
+        }
+        (_, Action::Recent) => {
+            match picker::Picker::recent(history, Utc::now(), config.day_starts_at) {
+                Ok(picker) if picker.numbered(1).is_none() => log::info!("no tag has been worked on yet"),
+                Ok(picker) => app.picker = Some(picker),
+                Err(err) => log::warn!("{}", err.report()),
+            }
+            None
+        }
+        (_, Action::Stats) => {
+            match history.load() {
+                Ok(mut sessions) => {
+                    sessions.extend_from_slice(&app.timer.sessions[app.recorded..]);
+                    app.stats = Some(stats::Stats::new(sessions, config.day_starts_at));
+                }
+                Err(err) => log::warn!("{}", err.report()),
+            }
+            None
+        }
+        (Mode::Input, Action::Quit) => return true,
+        (Mode::Input, Action::Increase) => {
+            app.timer.time += 1;
+            None
+        }
+        (Mode::Input, Action::Decrease) => {
+            app.timer.time = (app.timer.time - 1).max(0);
+            None
+        }
+        (Mode::Input, _) if action.preset().is_some() => {
+            if let Some(minutes) = action.preset().and_then(|i| config.presets.get(i)) {
+                app.timer.time = (*minutes).max(0) * 60;
+            }
+            None
+        }
+        (Mode::Input, Action::Start) => Some(Command::Start),
+        (Mode::Running, Action::Reset) => Some(Command::Reset),
+        (Mode::Running, Action::Toggle) => Some(Command::Toggle),
+        (Mode::Running, Action::Skip) => Some(Command::Skip),
+        (Mode::Running, Action::Extend) => Some(Command::Add(5 * 60)),
+        (Mode::Running, Action::Trim) => Some(Command::Add(-5 * 60)),
+        _ => None,
+    };
+
+    if let Some(command) = command {
+        if end_grace(app, config, command) {
+            return false;
+        }
+    }
+
+    // strict mode: the work session runs its course, or is abandoned with
+    // the confirmation word
+    let command = match command {
+        Some(Command::Reset) if app.strict(config) => {
+            app.confirm = Some(String::new());
+            None
+        }
+        // resuming is fine, after an automatic pause
+        Some(Command::Toggle) if app.timer.is_paused() => Some(Command::Toggle),
+        Some(Command::Toggle | Command::Skip | Command::Add(_)) if app.strict(config) => {
+            log::info!("strict mode: no pausing, skipping or changing work sessions");
+            None
+        }
+        command => command,
+    };
+
+    if let Some(command) = command.filter(|&command| within_hours(app, config, command)) {
+        app.timer.apply(command, &config.schedule, Utc::now());
+        if matches!(command, Command::Skip | Command::Reset) {
+            log::info!("press u within {}s to undo", app::UNDO_SECS);
+        }
+    }
+    false
+}
+
+/// Count down to `[[autostart]]` sessions as they come due and start them;
+/// returns the settings of the profile one comes with.
+fn autostart(
+    app: &mut App,
+    config: &Config,
+    caps: &Capabilities,
+    checked: &mut DateTime<Local>,
+) -> Option<Config> {
+    let local = Local::now();
+    let since = std::mem::replace(checked, local);
+    let now = Utc::now();
+    // started by hand in the meantime
+    if app.timer.mode != Mode::Input {
+        app.autostart = None;
+    }
+
+    if let Some(entry) = config.autostart.iter().find(|entry| entry.due(since, local)) {
+        if app.timer.mode == Mode::Input && app.autostart.is_none() {
+            let what = entry.profile.as_deref().unwrap_or("work");
+            let body = format!("{} starts in {}s, esc in pom skips it", what, entry.notice);
+            log::info!("{}", body);
+            if config.notifications {
+                if let Err(err) = caps.notifier.send("Focus block", &body) {
+                    log::warn!("{}", err.report());
+                }
+            }
+            app.autostart = Some(Countdown {
+                until: now + chrono::Duration::seconds(entry.notice as i64),
+                entry: entry.clone(),
+            });
+        } else {
+            log::info!("the {} autostart was skipped, a session is under way", entry.at.format("%H:%M"));
+        }
+    }
+
+    let entry = app.autostart.take_if(|countdown| now >= countdown.until)?.entry;
+    if !within_hours(app, config, Command::Start) {
+        return None;
+    }
+    let profile = entry.profile.as_deref().and_then(|name| match Config::load_profile(Some(name)) {
+        Ok(profile) => Some(profile),
+        Err(err) => {
+            log::warn!("{}", err.report());
+            None
+        }
+    });
+    if let Some(minutes) = entry.minutes {
+        app.timer.time = minutes * 60;
+    }
+    let schedule = &profile.as_ref().unwrap_or(config).schedule;
+    app.timer.apply(Command::Start, schedule, now);
+    profile
+}
+
+/// Hold a work session about to start up against the working hours: outside
+/// them it starts with a warning, or with `outside = "refuse"` not at all.
+fn within_hours(app: &App, config: &Config, command: Command) -> bool {
+    let starts = app.timer.mode == Mode::Input && matches!(command, Command::Start | Command::Toggle);
+    if !starts || config.hours.contain(Local::now()) {
+        return true;
+    }
+    match config.hours.outside {
+        config::Outside::Warn => {
+            log::warn!("outside working hours");
+            true
+        }
+        config::Outside::Refuse => {
+            log::warn!("outside working hours, not starting");
+            false
+        }
+    }
+}
+
+/// Put `text` on the system clipboard.
+fn copy(app: &mut App, text: String) {
+    let mut set = || -> std::result::Result<(), arboard::Error> {
+        let clipboard = match app.clipboard.take() {
+            Some(clipboard) => clipboard,
+            None => arboard::Clipboard::new()?,
+        };
+        app.clipboard.insert(clipboard).set_text(text.as_str())
+    };
+    match set() {
+        Ok(()) => log::info!("copied \"{}\"", text),
+        Err(err) => log::warn!("couldn't copy to the clipboard: {}", err),
+    }
+}
+
+/// Space held long enough on the break screen puts it away, for this
+/// break; the terminal's key repeat keeps the hold going.
+fn hold_to_unlock(app: &mut App, key: KeyEvent, config: &Config) {
+    let now = Instant::now();
+    if key.code != KeyCode::Char(' ') {
+        app.hold = None;
+        return;
+    }
+    // repeats come faster than this once they start
+    let (since, _) = match app.hold {
+        Some((since, last)) if now.duration_since(last) < Duration::from_millis(700) => (since, now),
+        _ => (now, now),
+    };
+    app.hold = Some((since, now));
+    if now.duration_since(since) >= Duration::from_secs(config.breaks.unlock_hold) {
+        app.hold = None;
+        app.unlocked = true;
+        log::info!("break screen unlocked");
+    }
+}
+
+/// A command from outside the UI, held to the same rules as keys.
+fn remote(app: &mut App, config: &Config, command: Command) {
+    tracing::debug!(?command, "remote command");
+    if end_grace(app, config, command) {
+        return;
+    }
+    let resume = matches!(command, Command::Start)
+        || matches!(command, Command::Toggle) && app.timer.is_paused();
+    if app.strict(config) && !resume {
+        log::info!("strict mode: {:?} refused during work", command);
+        return;
+    }
+    if !within_hours(app, config, command) {
+        return;
+    }
+    app.timer.apply(command, &config.schedule, Utc::now());
+}
+
+/// A button pressed on the notification, which also silences the alarm.
+/// During the grace period skip and +5 min are about the next phase and
+/// the one that ran out; after it, both are about the phase running.
+fn answered(app: &mut App, config: &Config, button: Button) {
+    app.ringing = None;
+    match button {
+        Button::Start => remote(app, config, Command::Start),
+        Button::Skip => {
+            end_grace(app, config, Command::Skip);
+            remote(app, config, Command::Skip);
+        }
+        Button::Extend => {
+            app.grace = None;
+            remote(app, config, Command::Add(5 * 60));
+        }
+    }
+}
+
+/// Start the next phase when anything is done to the timer during the
+/// grace period; true when skipping or starting was all `command` meant.
+fn end_grace(app: &mut App, config: &Config, command: Command) -> bool {
+    if app.grace.take().is_none() {
+        return false;
+    }
+    let now = Utc::now();
+    app.timer.advance(&config.schedule, now);
+    // the user is there to start it
+    if app.timer.is_waiting() {
+        app.timer.apply(Command::Start, &config.schedule, now);
+    }
+    matches!(command, Command::Skip | Command::Start)
+}
+
+/// A key typed into strict mode's confirmation: the right word abandons
+/// the work session, esc keeps it going.
+fn confirm_abandon(app: &mut App, key: KeyEvent, config: &Config) {
+    let Some(typed) = &mut app.confirm else {
+        return;
+    };
+    match key.code {
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) && typed.len() < 64 => {
+            typed.push(c)
+        }
+        KeyCode::Backspace => {
+            typed.pop();
+        }
+        KeyCode::Esc => app.confirm = None,
+        KeyCode::Enter => {
+            if app.confirm.take().as_deref().map(str::trim) == Some(config.strict.confirm.as_str()) {
+                app.timer.apply(Command::Reset, &config.schedule, Utc::now());
+                log::info!("abandoned, press u within {}s to undo", app::UNDO_SECS);
+            } else {
+                log::info!("not abandoned, keep going");
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A key typed into the command palette; what was picked, on enter.
+fn choose(app: &mut App, key: KeyEvent) -> Option<palette::Choice> {
+    let palette = app.palette.as_mut()?;
+    let control = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Up => palette.select(-1),
+        KeyCode::Down => palette.select(1),
+        KeyCode::Char('p') if control => palette.select(-1),
+        KeyCode::Char('n') if control => palette.select(1),
+        KeyCode::Char(c) if !control => {
+            palette.query.push(c);
+            palette.filter();
+        }
+        KeyCode::Backspace => {
+            palette.query.pop();
+            palette.filter();
+        }
+        KeyCode::Esc => app.palette = None,
+        // what is missing goes to the messages, to be typed in
+        KeyCode::Enter => match palette.picked() {
+            Ok(choice) => {
+                app.palette = None;
+                return Some(choice);
+            }
+            Err(missing) => log::info!("{}", missing),
+        },
+        _ => {}
+    }
+    None
+}
+
+/// Do what was picked from the palette; true to quit.
+fn run_choice(app: &mut App, choice: palette::Choice, config: &Config, history: &Store) -> bool {
+    match choice {
+        palette::Choice::Action(action) => return perform(app, action, config, history),
+        palette::Choice::Duration(minutes) if app.timer.mode == Mode::Input => {
+            app.timer.time = minutes.clamp(0, app::MAX_ADD / 60) * 60;
+        }
+        palette::Choice::Duration(minutes) => {
+            let by = minutes.clamp(0, app::MAX_ADD / 60) * 60 - app.timer.planned;
+            remote(app, config, Command::Add(by));
+        }
+        palette::Choice::Add(minutes) => {
+            let secs = minutes.clamp(-app::MAX_ADD / 60, app::MAX_ADD / 60) * 60;
+            remote(app, config, Command::Add(secs));
+        }
+        palette::Choice::Volume(percent) => app.set_volume(percent as f32 / 100.0),
+        palette::Choice::Profile(name) => app.switching = Some(name),
+    }
+    false
+}
+
+/// The profile after the one in use, going round `[profiles]`.
+fn next_profile(config: &Config) -> Option<String> {
+    let profiles = Config::profiles();
+    if profiles.is_empty() {
+        log::info!("there are no [profiles] in the config to switch between");
+        return None;
+    }
+    let current = config.profile.as_ref().and_then(|name| profiles.iter().position(|p| p == name));
+    let next = current.map_or(0, |i| (i + 1) % profiles.len());
+    Some(profiles[next].clone())
+}
+
+/// A key typed into the tag picker: enter focuses on the pick, esc backs out.
+fn pick(app: &mut App, key: KeyEvent, history: &Store) {
+    let Some(picker) = &mut app.picker else {
+        return;
+    };
+    let control = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Up => picker.select(-1),
+        KeyCode::Down => picker.select(1),
+        KeyCode::Char('p') if control => picker.select(-1),
+        KeyCode::Char('n') if control => picker.select(1),
+        KeyCode::Tab => picker.complete(1),
+        KeyCode::BackTab => picker.complete(-1),
+        // the quick pick's numbers, before anything is typed
+        KeyCode::Char(c @ '1'..='9') if picker.query.is_empty() && picker.recent => {
+            if let Some(tag) = c.to_digit(10).and_then(|n| picker.numbered(n as usize)) {
+                focus(app, tag, history);
+            }
+        }
+        // tags are one word
+        KeyCode::Char(c) if !control && !c.is_whitespace() => {
+            picker.query.push(c);
+            picker.filter();
+        }
+        KeyCode::Backspace => {
+            picker.query.pop();
+            picker.filter();
+        }
+        KeyCode::Esc => app.picker = None,
+        KeyCode::Enter => match picker.picked() {
+            Some(tag) => focus(app, tag, history),
+            None => app.picker = None,
+        },
+        _ => {}
+    }
+}
+
+/// The planning screen, with the tags worked on last to plan for.
+fn open_planner(app: &mut App, config: &Config, history: &Store) {
+    let recent = history.tallies(config.day_starts_at).unwrap_or_else(|err| {
+        log::warn!("{}", err.report());
+        Vec::new()
+    });
+    let today = report::day(Utc::now(), config.day_starts_at);
+    // a day may have started since
+    if app.tasks.recur(today) {
+        if let Err(err) = tasks::save(&app.tasks) {
+            log::warn!("could not save the task list: {}", err);
+        }
+    }
+    app.planner = Some(tasks::Planner::new(&app.tasks, &recent[..recent.len().min(5)], today));
+}
+
+/// A key on the planning screen: enter adds the tag typed, or keeps the
+/// plan with nothing typed; esc backs out.
+fn plan(app: &mut App, key: KeyEvent) {
+    let Some(planner) = &mut app.planner else {
+        return;
+    };
+    let control = key.modifiers.contains(KeyModifiers::CONTROL);
+    let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+    let typing = !planner.adding.is_empty();
+    // the task moves, and the list keeps its new order
+    let moving = match key.code {
+        KeyCode::Up if shift => Some(-1),
+        KeyCode::Down if shift => Some(1),
+        KeyCode::Char('K') if !typing => Some(-1),
+        KeyCode::Char('J') if !typing => Some(1),
+        _ => None,
+    };
+    if let Some(by) = moving {
+        if planner.shift(by) {
+            keep_tasks(app);
+        }
+        return;
+    }
+    if key.code == KeyCode::Char('X') && !typing {
+        if planner.archive() {
+            keep_tasks(app);
+        }
+        return;
+    }
+    match key.code {
+        KeyCode::Tab => planner.toggle(),
+        KeyCode::Char(_) if planner.archived => {}
+        KeyCode::Up => planner.select(-1),
+        KeyCode::Down => planner.select(1),
+        KeyCode::Left => planner.change(-1),
+        KeyCode::Right => planner.change(1),
+        // a space only after the tag, for a rule to repeat by
+        KeyCode::Char(c) if !control && (!c.is_whitespace() || typing) => planner.adding.push(c),
+        KeyCode::Backspace => {
+            planner.adding.pop();
+        }
+        KeyCode::Esc if !planner.adding.is_empty() => planner.adding.clear(),
+        KeyCode::Esc if planner.archived => planner.toggle(),
+        KeyCode::Esc => app.planner = None,
+        KeyCode::Enter if typing && planner.add() => keep_tasks(app),
+        KeyCode::Enter if typing => {}
+        KeyCode::Enter => {
+            app.tasks = planner.plan();
+            let sessions: u32 = app.tasks.tasks.iter().map(|task| task.planned).sum();
+            log::info!("{} sessions planned for today", sessions);
+            if let Err(err) = tasks::save(&app.tasks) {
+                log::warn!("could not save the task list: {}", err);
+            }
+            app.planner = None;
+        }
+        _ => {}
+    }
+}
+
+/// Tag the sessions from now on with what was picked.
+fn focus(app: &mut App, tag: String, history: &Store) {
+    log::info!("focusing on #{}, sessions are tagged with it from now on", tag);
+    app.timer.tags = vec![tag];
+    let entry = events::Entry::Tagged {
+        at: Utc::now(),
+        tags: app.timer.tags.clone(),
+    };
+    if let Err(err) = history.log(&entry) {
+        log::warn!("{}", err.report());
+    }
+    app.picker = None;
+}
+
+/// Typing a correction to the selected session in the history browser.
+fn edit_entry(app: &mut App, key: KeyEvent, history: &Store) {
+    let Some(browser) = &mut app.browser else {
+        return;
+    };
+    let Some(text) = &mut browser.editing else {
+        return;
+    };
+    if !matches!(key.code, KeyCode::Tab | KeyCode::BackTab) {
+        browser.completion.typed();
+    }
+    match key.code {
+        KeyCode::Tab => browser.completion.tab(text, 1),
+        KeyCode::BackTab => browser.completion.tab(text, -1),
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => text.push(c),
+        KeyCode::Backspace => {
+            text.pop();
+        }
+        KeyCode::Esc => browser.editing = None,
+        KeyCode::Enter => {
+            let text = text.clone();
+            let Some(session) = browser.selected().cloned() else {
+                return;
+            };
+            match browser::edited(&session, &text) {
+                Ok(edited) => {
+                    browser.editing = None;
+                    if edited != session {
+                        amend(app, history, session, Some(edited));
+                    }
+                }
+                Err(err) => log::warn!("{}", err),
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Put a correction (or with `None`, a deletion) of a recorded session in
+/// the history, and in this run's sessions if it is one of them.
+fn amend(app: &mut App, history: &Store, before: Session, after: Option<Session>) {
+    if let Err(err) = history.amend(&before, after.as_ref()) {
+        log::warn!("{}", err.report());
+        return;
+    }
+    let started = before.started.with_timezone(&Local).format("%a %m-%d %H:%M");
+    let same = |s: &Session| s.phase == before.phase && s.started == before.started;
+    if let Some(i) = app.timer.sessions.iter().position(same) {
+        match &after {
+            Some(after) => app.timer.sessions[i] = after.clone(),
+            None => {
+                app.timer.sessions.remove(i);
+                if i < app.recorded {
+                    app.recorded -= 1;
+                }
+            }
+        }
+    }
+    match after {
+        Some(_) => log::info!("{} {} corrected", before.phase.name(), started),
+        None => log::info!("{} {} deleted", before.phase.name(), started),
+    }
+    if let Some(browser) = &mut app.browser {
+        if let Err(err) = browser.refresh(history) {
+            log::warn!("{}", err.report());
+        }
+    }
+}
+
+/// Typing in the history browser's search, which narrows the list as it
+/// goes; enter keeps it, esc drops it.
+fn edit_search(app: &mut App, key: KeyEvent, history: &Store) {
+    let Some(browser) = &mut app.browser else {
+        return;
+    };
+    match key.code {
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => browser.search.push(c),
+        KeyCode::Backspace => {
+            browser.search.pop();
+        }
+        KeyCode::Enter => {
+            browser.searching = false;
+            return;
+        }
+        KeyCode::Esc => {
+            browser.searching = false;
+            browser.search.clear();
+        }
+        _ => return,
+    }
+    if let Err(err) = browser.refresh(history) {
+        log::warn!("{}", err.report());
+    }
+}
+
+/// A key typed into the note being written: enter keeps it, esc drops it.
+fn edit_note(app: &mut App, key: KeyEvent, config: &Config, history: &Store) {
+    let Some(note) = &mut app.note else {
+        return;
+    };
+    let room = note.text.chars().count() < note.max();
+    // alt-enter, or ctrl-j as raw terminals send it
+    let newline = key.code == KeyCode::Enter && key.modifiers.contains(KeyModifiers::ALT)
+        || key.code == KeyCode::Char('j') && key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        _ if newline && note.journal && room => note.text.push('\n'),
+        _ if newline => {}
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) && room => {
+            note.text.push(c);
+        }
+        KeyCode::Backspace => {
+            note.text.pop();
+        }
+        KeyCode::Esc => {
+            let skipped = app.note.take().filter(|note| note.journal);
+            if let Some(session) = skipped.and_then(|note| app.timer.sessions.get(note.index)) {
+                to_obsidian(config, session);
+            }
+        }
+        KeyCode::Enter => {
+            let Some(note) = app.note.take() else {
+                return;
+            };
+            let Some(session) = app.timer.sessions.get_mut(note.index) else {
+                return;
+            };
+            let text = note.text.trim();
+            session.note = (!text.is_empty()).then(|| text.to_string());
+            if let Some(journal) = config.journal.as_ref().filter(|_| note.journal && !text.is_empty()) {
+                if let Err(err) = journal::append(journal, config.day_starts_at, session, text) {
+                    log::warn!("{}", err.report());
+                }
+            }
+            if note.journal {
+                to_obsidian(config, session);
+            }
+            // one not yet recorded goes out with its note anyway
+            if note.index < app.recorded {
+                if let Err(err) = history.replace(session) {
+                    log::warn!("{}", err.report());
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A line in the Obsidian daily note for a session that just ended.
+fn to_obsidian(config: &Config, session: &Session) {
+    let Some(obsidian) = config.obsidian.as_ref().filter(|obsidian| obsidian.automatic) else {
+        return;
+    };
+    if let Err(err) = obsidian::append(obsidian, config.day_starts_at, session) {
+        log::warn!("{}", err.report());
+    }
+}
+
+/// Pause a running work session once the user has been away long enough,
+/// backdated so the idle stretch doesn't count as work.
+fn auto_pause(app: &mut App, config: &Config, idle: Duration) {
+    let limit = Duration::from_secs(config.auto_pause.idle * 60);
+    if idle < limit {
+        app.away = false;
+        return;
+    }
+
+    if app.away || !app.timer.is_working() {
+        return;
+    }
+
+    let since = Utc::now() - chrono::Duration::from_std(idle).unwrap_or_else(|_| chrono::Duration::zero());
+    app.timer.apply(Command::Pause, &config.schedule, since);
+    app.away = true;
+    log::info!("auto-paused after {} idle minutes", idle.as_secs() / 60);
+}
+
+/// Pause a running work session when the screen locks, and on unlock
+/// resume it or leave it to the user.
+#[cfg(target_os = "linux")]
+fn lock_pause(app: &mut App, config: &Config, locked: bool) {
+    let now = Utc::now();
+    if locked {
+        if app.timer.is_working() {
+            app.timer.apply(Command::Pause, &config.schedule, now);
+            app.lock_paused = true;
+            log::info!("paused, screen locked");
+        }
+        return;
+    }
+
+    if !std::mem::take(&mut app.lock_paused) || !app.timer.is_paused() {
+        return;
+    }
+    match config.auto_pause.on_unlock {
+        config::OnUnlock::Resume => {
+            app.timer.apply(Command::Start, &config.schedule, now);
+            log::info!("welcome back, resumed");
+        }
+        config::OnUnlock::Prompt => log::info!("welcome back, press p to resume"),
+    }
+}
+
+/// Refresh the user's status file whenever its text changes, and the state
+/// file for `pom status` once a second so readers can tell we're alive.
+fn publish_status(app: &mut App, config: &Config) {
+    let context = app.context();
+
+    if let Some(path) = &config.status_file {
+        let status = template::render(&config.templates.status, &context);
+        if status != app.status {
+            if let Err(err) = fs::write(path, &status) {
+                log::warn!("could not write status file {}: {}", path.display(), err);
+            }
+            app.status = status;
+        }
+    }
+
+    if app.state_written.is_some_and(|at| at.elapsed() < Duration::from_secs(1)) {
+        return;
+    }
+    if let Err(err) = state::write(context) {
+        log::warn!("could not write state file: {}", err);
+    }
+    app.state_written = Some(Instant::now());
+}
+
+/// Tell the user the time is up: desktop notification, plus the terminal
+/// bell when the alarm is insistent.
+fn alert(app: &App, config: &Config, caps: &Capabilities, answer: &Sender<Answer>) {
+    if config.notifications {
+        let context = app.context();
+        let title = template::render(&config.templates.notification_title, &context);
+        let body = template::render(&config.templates.notification_body, &context);
+
+        // without a grace period the next phase is already running
+        let (start, skip) = match &app.grace {
+            Some(grace) => (Some(format!("Start {}", grace.next.name())), grace.next),
+            None => (None, app.timer.phase),
+        };
+        let skip = format!("Skip {}", skip.name());
+        let mut buttons = vec![("skip", skip.as_str()), ("extend", "+5 min")];
+        if let Some(start) = &start {
+            buttons.insert(0, ("start", start.as_str()));
+        }
+        let (answer, phase, round) = (answer.clone(), app.timer.phase, app.timer.round);
+        let answered = move |key: &str| {
+            let button = match key {
+                "start" => Button::Start,
+                "skip" => Button::Skip,
+                _ => Button::Extend,
+            };
+            let _ = answer.send(Answer {
+                button,
+                phase,
+                round,
+            });
+        };
+        if let Err(err) = caps.notifier.ask(&title, &body, &buttons, answered) {
+            log::warn!("{}", err.report());
+        }
+    }
+
+    if config.sound.alarm {
+        play(caps, Sound::Alarm, app.level(config.sound.alarm_volume));
+    }
+
+    if config.alarm.insistent && !caps.notifier.rings() {
+        if let Err(err) = notify::bell() {
+            log::warn!("{}", err.report());
+        }
+    }
+}
+
+/// The nudge for a cue: a chime, a notification, or both.
+fn cued(app: &App, config: &Config, caps: &Capabilities, cue: config::Cue) {
+    log::info!("{}", cue);
+    if config.cues.sound {
+        play(caps, Sound::Cue, app.level(config.cues.volume));
+    }
+    if config.cues.notify {
+        if let Err(err) = caps.notifier.send("pom", &cue.to_string()) {
+            log::warn!("{}", err.report());
+        }
+    }
+}
+
+/// Speak `text`, when announcements are on.
+fn say(config: &Config, caps: &Capabilities, text: &str) {
+    let Some(speaker) = caps.speaker.filter(|_| config.speech.enabled) else {
+        return;
+    };
+    if let Err(err) = speaker.say(text) {
+        log::warn!("{}", err.report());
+    }
+}
+
+/// Once-a-second tick while a session runs, alternating with a tock when
+/// configured.
+fn tick(app: &App, config: &Config, caps: &Capabilities) {
+    let sound = match config.sound.tick {
+        Ticking::Off => return,
+        Ticking::TickTock if app.timer.elapsed % 2 == 1 => Sound::Tock,
+        _ => Sound::Tick,
+    };
+    play(caps, sound, app.level(config.sound.tick_volume));
+}
+
+/// Keep the ambient loop playing exactly while a work session runs.
+fn ambient(app: &mut App, config: &Config, caps: &Capabilities) {
+    let volume = app.level(config.sound.ambient_volume);
+    let working = app.timer.is_working() && volume > 0.0 && !app.low_power;
+    let Some(player) = caps.player.filter(|_| working && config.sound.ambient != Ambient::Off)
+    else {
+        app.ambient = None;
+        return;
+    };
+
+    if app.ambient.is_none() {
+        app.ambient = Some(match &config.sound.ambient_file {
+            Some(path) => audio::Loop::file(player, path.clone(), volume),
+            None => audio::Loop::noise(player, config.sound.ambient, volume),
+        });
+    }
+}
+
+fn play(caps: &Capabilities, sound: Sound, volume: f32) {
+    if volume <= 0.0 {
+        return;
+    }
+
+    if let Some(player) = caps.player {
+        if let Err(err) = player.play(sound, volume) {
+            log::warn!("{}", err.report());
+        }
+    }
+}
+
+/// One-line summary of secondary state under the timer.
+fn status_bar<B: Backend>(f: &mut Frame<B>, app: &mut App, config: &Config, area: Rect) {
+    let volume = if app.muted {
+        Span::styled("vol muted", Style::default().fg(Color::DarkGray))
+    } else {
+        Span::raw(format!("vol {:>3}%", (app.volume * 100.0).round()))
+    };
+
+    let mut status = vec![volume];
+    // the daily goal, and the days in a row that met theirs
+    let today = report::day(Utc::now(), config.day_starts_at);
+    app.streak.roll(today, config);
+    let streak = &app.streak;
+    if streak.goal > 0 || streak.days() > 0 {
+        let color = if streak.goal > 0 && streak.done >= streak.goal { Color::Green } else { Color::DarkGray };
+        let mut text = match streak.goal {
+            0 => String::new(),
+            goal => format!("goal {}/{}", streak.done, goal),
+        };
+        if streak.days() > 0 {
+            text = format!("{}{}{}-day streak", text, if text.is_empty() { "" } else { ", " }, streak.days());
+        }
+        status.insert(0, Span::styled(format!("{}  ", text), Style::default().fg(color)));
+    }
+    // today's plan, as far as it got
+    for task in app.tasks.planned(today).collect::<Vec<_>>().into_iter().rev() {
+        let done = app.done.of(&task.tag);
+        let color = match done.cmp(&(task.planned as usize)) {
+            std::cmp::Ordering::Less => Color::DarkGray,
+            std::cmp::Ordering::Equal => Color::Green,
+            std::cmp::Ordering::Greater => Color::Yellow,
+        };
+        status.insert(0, Span::styled(format!("#{} {}/{}  ", task.tag, done, task.planned), Style::default().fg(color)));
+    }
+    if app.low_power {
+        status.insert(0, Span::styled("low power  ", Style::default().fg(Color::DarkGray)));
+    }
+    let status_widget = Paragraph::new(Spans::from(status))
+        .alignment(tui::layout::Alignment::Right);
+    f.render_widget(status_widget, area);
+
+    // what the number keys pick, while there is something to pick
+    if app.timer.mode == Mode::Input && app.stats.is_none() && app.browser.is_none() && app.picker.is_none() {
+        let keys = [Action::Preset1, Action::Preset2, Action::Preset3, Action::Preset4, Action::Preset5];
+        let presets: Vec<String> = config
+            .presets
+            .iter()
+            .zip(keys)
+            .filter_map(|(minutes, preset)| Some(format!("{} {}m", app.keys.label(&[preset])?, minutes)))
+            .collect();
+        let legend = Span::styled(presets.join("  "), Style::default().fg(Color::DarkGray));
+        f.render_widget(Paragraph::new(Spans::from(legend)).alignment(tui::layout::Alignment::Center), area);
+    }
+
+    // a half-typed chord, so it's clear the next key finishes something
+    if !app.keys.pending().is_empty() {
+        let keys: Vec<String> = app.keys.pending().iter().map(|key| key.to_string()).collect();
+        let chord = Span::styled(format!("{} \u{2026}", keys.join(" ")), Style::default().fg(Color::Yellow));
+        f.render_widget(Paragraph::new(Spans::from(chord)), area);
+    } else if let Some(text) = &app.status_text {
+        f.render_widget(Paragraph::new(Spans::from(Span::raw(text.as_str()))), area);
+    } else if let Some(grace) = &app.grace {
+        let left = (grace.until - Utc::now()).num_seconds().max(0) + 1;
+        let countdown = format!("{} starts in {}s\u{2026}", grace.next.name(), left);
+        let countdown = Span::styled(countdown, Style::default().fg(Color::Yellow));
+        f.render_widget(Paragraph::new(Spans::from(countdown)), area);
+    } else if let Some(countdown) = &app.autostart {
+        let left = (countdown.until - Utc::now()).num_seconds().max(0) + 1;
+        let what = countdown.entry.profile.as_deref().unwrap_or("work");
+        let countdown = Span::styled(format!("{} starts in {}s\u{2026}", what, left), Style::default().fg(Color::Yellow));
+        f.render_widget(Paragraph::new(Spans::from(countdown)), area);
+    } else if !app.timer.tags.is_empty() {
+        let tags: Vec<String> = app.timer.tags.iter().map(|tag| format!("#{}", tag)).collect();
+        if let Some(links) = &mut app.links {
+            let mut x = area.x;
+            for tag in &tags {
+                if let Some(url) = config.links.url(&tag[1..]) {
+                    links.push(Link { x, y: area.y, text: tag.clone(), color: Color::Cyan, url });
+                }
+                x += Span::raw(tag.as_str()).width() as u16 + 1;
+            }
+        }
+        let tags = Span::styled(tags.join(" "), Style::default().fg(Color::Cyan));
+        f.render_widget(Paragraph::new(Spans::from(tags)), area);
+    }
+}
+
+/// Bottom panel with the newest log messages, colored by level.
+fn messages<B: Backend>(f: &mut Frame<B>, area: Rect) {
+    let lines: Vec<Spans> = logger::recent(area.height.saturating_sub(1) as usize)
+        .into_iter()
+        .map(|entry| {
+            let color = match entry.level {
+                Level::Error => Color::Red,
+                Level::Warn => Color::Yellow,
+                _ => Color::Gray,
+            };
+
+            Spans::from(vec![
+                Span::styled(
+                    entry.at.format("%H:%M:%S ").to_string(),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(entry.message, Style::default().fg(color)),
+            ])
+        })
+        .collect();
+
+    let message_widget = Paragraph::new(lines)
+        .block(Block::default().title("Messages"));
+
+    f.render_widget(message_widget, area);
+}
+
+/// The whole screen, during a break with `breaks.enforce` on.
+fn break_screen<B: Backend>(f: &mut Frame<B>, app: &App, config: &Config) {
+    let area = f.size();
+    let remaining = app.timer.remaining().max(0);
+    let held = app.hold.map_or(0, |(since, _)| since.elapsed().as_secs());
+    let hint = match held {
+        0 => format!("hold space for {}s to get back to work", config.breaks.unlock_hold),
+        held => format!("keep holding\u{2026} {}", config.breaks.unlock_hold.saturating_sub(held)),
+    };
+    let lines = vec![
+        Spans::from(Span::styled(
+            "S T E P   A W A Y",
+            Style::default().fg(Color::Green).add_modifier(Modifier::BOLD),
+        )),
+        Spans::default(),
+        Spans::from(format!(
+            "{} left of the {}",
+            format_args!("{}:{:02}", remaining / 60, remaining % 60),
+            app.timer.phase.name()
+        )),
+        Spans::default(),
+        Spans::from(Span::styled(hint, Style::default().fg(Color::DarkGray))),
+    ];
+    let top = area.height.saturating_sub(lines.len() as u16) / 2;
+    let rect = Rect::new(area.x, area.y + top, area.width, area.height - top);
+    f.render_widget(Clear, area);
+    f.render_widget(Paragraph::new(lines).alignment(tui::layout::Alignment::Center), rect);
+}
+
+/// Put the image where the last frame left room for it, sending it again
+/// only when it moved or changed. True when the screen was cleared.
+fn show_picture<B: Backend>(app: &mut App, terminal: &mut Terminal<B>) -> Result<bool> {
+    let Some(picture) = &mut app.picture else {
+        return Ok(false);
+    };
+    let mut out = io::stdout();
+    match &picture.wanted {
+        Some(wanted) if picture.shown.as_ref() != Some(wanted) => {
+            let (area, content) = wanted;
+            let place = content.place(*area);
+            match (content.image(place), &picture.logo) {
+                (Some(image), _) => picture.protocol.show(&mut out, &image, place)?,
+                (None, Some(png)) => picture.protocol.show_png(&mut out, png, place)?,
+                (None, None) => {}
+            }
+            picture.shown = picture.wanted.clone();
+        }
+        Some(_) => {}
+        None => {
+            if picture.shown.take().is_some() {
+                picture.protocol.hide(&mut out)?;
+                // iTerm2's and sixel pixels stay in the cells until they're
+                // written over
+                terminal.clear()?;
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App, config: &Config) {
+    if let Some(picture) = &mut app.picture {
+        picture.wanted = None;
+    }
+    app.timer_area = Rect::default();
+    app.planner_area = Rect::default();
+    if let Some(links) = &mut app.links {
+        links.clear();
+    }
+    if app.enforcing(config) {
+        break_screen(f, app, config);
+        return;
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints(
+            [
+                Constraint::Length(1),
+                Constraint::Min(1),
+                Constraint::Length(1),
+                Constraint::Length(4)
+            ].as_ref()
+        )
+        .split(f.size());
+
+
+    // whatever keys the keymap has for what can be done right now
+    let keys = &app.keys;
+    let instructions = match app.timer.mode {
+        _ if app.ringing.is_some() => "[ any key ] to dismiss the alarm".to_string(),
+        _ if app.grace.is_some() => keys.hints(&[(&[Action::Skip], "to start the next phase now"), (&[Action::Reset], "to stop after this one")]),
+        _ if app.autostart.is_some() => keys.hints(&[(&[Action::Start], "to start the scheduled session now"), (&[Action::Reset], "to skip it")]),
+        _ if app.confirm.is_some() => "[ enter ] to abandon the session, [ esc ] to keep going".to_string(),
+        _ if app.note.as_ref().is_some_and(|note| note.journal) => "[ enter ] to save, [ alt-enter ] new line, [ esc ] to skip".to_string(),
+        _ if app.note.is_some() => "[ enter ] to save the note on the last work session, [ esc ] to cancel".to_string(),
+        _ if app.picker.as_ref().is_some_and(|picker| picker.recent) => {
+            let last = app.picker.as_ref().map_or(0, picker::Picker::count);
+            format!("[ 1-{} ] resume one, [ ^ v ] choose, [ enter ] to focus on it, [ esc ] to go back", last)
+        }
+        _ if app.picker.is_some() => "type to narrow the tags, [ ^ v ] choose, [ tab ] complete, [ enter ] to focus on it, [ esc ] to go back".to_string(),
+        _ if app.planner.as_ref().is_some_and(|planner| planner.archived) => "[ ^ v ] choose, [ X ] back on the list, [ tab ] or [ esc ] to the tasks to do".to_string(),
+        _ if app.planner.is_some() => "[ ^ v ] choose, [ < > ] fewer/more sessions, [ J K ] or drag to move, [ X ] archive, [ tab ] archived, type a #tag to add it, a rule after it to repeat it, [ enter ] to keep the plan, [ esc ] to go back".to_string(),
+        _ if app.palette.is_some() => "type an action, an argument after those that take one, [ ^ v ] choose, [ enter ] to do it, [ esc ] to go back".to_string(),
+        _ if app.browser.as_ref().is_some_and(|browser| browser.searching) => "type to search tags and notes, [ enter ] to keep it, [ esc ] to clear it".to_string(),
+        _ if app.browser.as_ref().is_some_and(|browser| browser.editing.is_some()) => "minutes and #tags, e.g. 25m #writing, [ tab ] complete a #tag, [ enter ] to save, [ esc ] to cancel".to_string(),
+        _ if app.browser.as_ref().is_some_and(|browser| browser.deleting) => {
+            let again = keys.hints(&[(&[Action::Delete], "again to delete the selected session")]);
+            format!("{}, any other key to keep it", again)
+        }
+        _ if app.browser.is_some() => keys.hints(&[
+            (&[Action::History], "back"),
+            (&[Action::Increase, Action::Decrease], "scroll"),
+            (&[Action::PreviousPeriod, Action::NextPeriod], "period"),
+            (&[Action::FirstTag], "tag"),
+            (&[Action::SecondTag], "outcome"),
+            (&[Action::Search], "search"),
+            (&[Action::Edit], "edit"),
+            (&[Action::Delete], "delete"),
+        ]),
+        _ if app.stats.is_some() => keys.hints(&[
+            (&[Action::Stats], "back to the timer"),
+            (&[Action::PreviousPeriod, Action::NextPeriod], "period"),
+            (&[Action::FirstTag, Action::SecondTag], "next tag"),
+            (&[Action::View], "view"),
+            (&[Action::Group], "by day/week/month"),
+            (&[Action::Copy], "copy summary"),
+            (&[Action::Toggle], "pause/resume"),
+        ]),
+        Mode::Input => keys.hints(&[
+            (&[Action::Quit], "to quit"),
+            (&[Action::Increase], "inc time"),
+            (&[Action::Decrease], "dec time"),
+            (&[Action::Start], "to start time"),
+            (&[Action::VolumeUp, Action::VolumeDown], "volume"),
+            (&[Action::Mute], "mute"),
+            (&[Action::Stats], "stats"),
+            (&[Action::Palette], "commands"),
+        ]),
+        Mode::Running => keys.hints(&[
+            (&[Action::Reset], "to change time"),
+            (&[Action::Toggle], "pause/resume"),
+            (&[Action::Skip], "skip"),
+            (&[Action::Extend, Action::Trim], "±5 min"),
+            (&[Action::VolumeUp, Action::VolumeDown], "volume"),
+            (&[Action::Mute], "mute"),
+            (&[Action::Stats], "stats"),
+            (&[Action::Palette], "commands"),
+        ]),
+    };
+    let instruction_widget = Paragraph::new(Text::from(Span::raw(instructions)));
+    f.render_widget(instruction_widget, chunks[0]);
+
+    if let Some(palette) = &app.palette {
+        palette::draw(f, palette, chunks[1]);
+    } else if let Some(planner) = &app.planner {
+        app.planner_area = chunks[1];
+        tasks::draw(f, planner, &app.done, chunks[1]);
+    } else if let Some(picker) = &app.picker {
+        picker::draw(f, picker, chunks[1]);
+    } else if let Some(browser) = &app.browser {
+        browser::draw(f, browser, chunks[1]);
+    } else if let Some(stats) = &app.stats {
+        let images = app.picture.is_some() && app.confirm.is_none() && app.note.is_none();
+        let chart = stats::draw(f, stats, config, chunks[1], images, app.links.as_mut());
+        if let Some(picture) = &mut app.picture {
+            picture.wanted = chart;
+        }
+    } else {
+        let still = config.display.reduced_motion || app.low_power;
+        let timer = PomodoroWidget::new(Utc::now())
+            .gauge(config.display.style == TimerStyle::Gauge)
+            .tomato(config.display.style == TimerStyle::Tomato)
+            .font(app.font.as_ref())
+            .icons(app.icons)
+            .cycle(config.schedule.cycle())
+            .tenths(config.display.tenths && !still)
+            .still(still)
+            .picture(app.picture.is_some() && app.confirm.is_none() && app.note.is_none())
+            .ringing(app.ringing.is_some());
+        let ratio = app.timer.progress(Utc::now());
+        if let Some(picture) = &mut app.picture {
+            let content = match picture.logo {
+                Some(_) => Content::Logo,
+                None => Content::tomato(ratio),
+            };
+            picture.wanted = timer.picture_area(chunks[1]).map(|area| (area, content));
+        }
+        app.timer_area = chunks[1];
+        f.render_stateful_widget(timer, chunks[1], &mut app.timer);
+    }
+
+    status_bar(f, app, config, chunks[2]);
+    messages(f, chunks[3]);
+
+    if let Some(typed) = &app.confirm {
+        let title = format!("Strict mode: type \"{}\" to abandon", config.strict.confirm);
+        input_box(f, &title, typed, chunks[1]);
+    } else if let Some(note) = &app.note {
+        let title = match &config.journal {
+            Some(journal) if note.journal => journal.question.as_str(),
+            _ => "Note on the last work session",
+        };
+        input_box(f, title, &note.text, chunks[1]);
+    }
+
+    if app.debug {
+        debug_screen(f, app, chunks[1]);
+    }
+}
+
+/// The diagnostics over the middle of `area`, until f12 again.
+fn debug_screen<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let lines = app.diagnostics.lines();
+    let width = area.width.min(72);
+    let inner = width.saturating_sub(2).max(1) as usize;
+    // wrapped lines, so long errors fit
+    let rows: usize = lines.iter().map(|line| line.chars().count().max(1).div_ceil(inner)).sum();
+    let height = (rows as u16 + 2).min(area.height);
+    let lines: Vec<Spans> = lines.into_iter().map(Spans::from).collect();
+    let rect = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+    let widget = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title(Span::styled("Debug", Style::default().fg(Color::Yellow))));
+    f.render_widget(Clear, rect);
+    f.render_widget(widget, rect);
+}
+
+/// Text being typed, in a box over the middle of `area`.
+fn input_box<B: Backend>(f: &mut Frame<B>, title: &str, text: &str, area: Rect) {
+    let width = area.width.min(64);
+    let text = format!("{}\u{258f}", text);
+    let inner = width.saturating_sub(2).max(1) as usize;
+    // wrapped lines, so the box grows with the text
+    let lines: usize = text.lines().map(|line| line.chars().count() / inner + 1).sum();
+    let height = (lines as u16 + 2).min(area.height);
+    let rect = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+
+    let widget = Paragraph::new(text)
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title(Span::styled(title, Style::default().fg(Color::Yellow))));
+    f.render_widget(Clear, rect);
+    f.render_widget(widget, rect);
+}
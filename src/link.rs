@@ -0,0 +1,50 @@
+//! OSC 8 hyperlinks over text already drawn, for tags that point somewhere.
+//!
+//! The sequences can't go in the frame itself, which counts every byte
+//! of a cell towards its width, so the linked text is written again on
+//! top after each draw.
+
+use crossterm::{
+    cursor::{MoveTo, RestorePosition, SavePosition},
+    queue,
+    style::{Print, ResetColor, SetForegroundColor},
+};
+use std::io::{self, Write};
+use tui::style::Color;
+
+use crate::degrade::Degrade;
+
+/// `text` at `x`, `y` leads to `url`.
+pub struct Link {
+    pub x: u16,
+    pub y: u16,
+    pub text: String,
+    pub color: Color,
+    pub url: String,
+}
+
+pub fn write(out: &mut impl Write, links: &[Link], degrade: Option<Degrade>) -> io::Result<()> {
+    if links.is_empty() {
+        return Ok(());
+    }
+    queue!(out, SavePosition)?;
+    for link in links {
+        // redrawn in ASCII, the text would no longer match
+        if degrade.is_some_and(|d| !d.unicode()) && !link.text.is_ascii() {
+            continue;
+        }
+        let color = degrade.map_or(link.color, |d| d.color(link.color));
+        queue!(
+            out,
+            MoveTo(link.x, link.y),
+            SetForegroundColor(color.into()),
+            Print(format!(
+                "\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\",
+                link.url, link.text
+            )),
+            ResetColor
+        )?;
+    }
+    queue!(out, RestorePosition)?;
+    out.flush()
+}
@@ -0,0 +1,78 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+};
+use zbus::{
+    blocking::{Connection, Proxy},
+    zvariant::OwnedObjectPath,
+};
+
+const LOGIND: &str = "org.freedesktop.login1";
+
+/// Whether the session's screen is locked, as logind's `LockedHint` tells
+/// it; GNOME, KDE and most lockers built for logind keep that up to date.
+pub struct Monitor {
+    state: Arc<State>,
+}
+
+#[derive(Default)]
+struct State {
+    locked: AtomicBool,
+    changed: AtomicBool,
+}
+
+impl State {
+    fn set(&self, locked: bool) {
+        if self.locked.swap(locked, Ordering::Relaxed) != locked {
+            self.changed.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Monitor {
+    pub fn start() -> Self {
+        let state = Arc::new(State::default());
+        let shared = Arc::clone(&state);
+
+        thread::spawn(move || {
+            if let Err(err) = watch(&shared) {
+                log::info!("screen lock detection unavailable: {}", err);
+            }
+        });
+
+        Monitor { state }
+    }
+
+    /// The new lock state, if it changed since the last call.
+    pub fn take_change(&self) -> Option<bool> {
+        self.state
+            .changed
+            .swap(false, Ordering::Relaxed)
+            .then(|| self.state.locked.load(Ordering::Relaxed))
+    }
+}
+
+fn watch(state: &State) -> zbus::Result<()> {
+    let conn = Connection::system()?;
+    let manager = Proxy::new(
+        &conn,
+        LOGIND,
+        "/org/freedesktop/login1",
+        "org.freedesktop.login1.Manager",
+    )?;
+    // signals come from the session's real path, not the `auto` alias;
+    // outside a session of our own (tmux started elsewhere) fall back to it
+    let path: OwnedObjectPath = manager
+        .call("GetSessionByPID", &(std::process::id()))
+        .or_else(|_| manager.call("GetSession", &("auto")))?;
+    let session = Proxy::new(&conn, LOGIND, path, "org.freedesktop.login1.Session")?;
+
+    state.set(session.get_property("LockedHint")?);
+    for change in session.receive_property_changed::<bool>("LockedHint") {
+        state.set(change.get()?);
+    }
+    Ok(())
+}
@@ -0,0 +1,164 @@
+//! Where log messages and traces go: the newest to the message panel at
+//! `log_level`, and to `log_file` as well when there is one, filtered by
+//! `POM_LOG` or `log_filter` so a bug report can carry a trace of just the
+//! part that failed. Both `log` and `tracing` macros end up here.
+
+use chrono::{DateTime, Local};
+use log::{Level, LevelFilter};
+use std::{
+    collections::VecDeque,
+    env, fmt, io,
+    path::Path,
+    sync::{Mutex, OnceLock},
+};
+use tracing::{field::Field, Event, Subscriber};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_log::AsTrace;
+use tracing_subscriber::{
+    field::Visit,
+    layer::{Context, SubscriberExt},
+    util::SubscriberInitExt,
+    EnvFilter, Layer,
+};
+
+use crate::{
+    config::LogRotate,
+    error::{PomError, Result},
+};
+
+/// How many messages the on-screen panel keeps around.
+const CAPACITY: usize = 32;
+
+/// Rotated log files kept, the current one included.
+const KEEP: usize = 7;
+
+static RECENT: OnceLock<Mutex<VecDeque<Entry>>> = OnceLock::new();
+
+#[derive(Clone)]
+pub struct Entry {
+    pub at: DateTime<Local>,
+    pub level: Level,
+    pub message: String,
+}
+
+/// Feeds the message panel.
+struct Panel;
+
+impl<S: Subscriber> Layer<S> for Panel {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut message = Message::default();
+        event.record(&mut message);
+        let level = match *event.metadata().level() {
+            tracing::Level::ERROR => Level::Error,
+            tracing::Level::WARN => Level::Warn,
+            tracing::Level::INFO => Level::Info,
+            tracing::Level::DEBUG => Level::Debug,
+            tracing::Level::TRACE => Level::Trace,
+        };
+        let entry = Entry {
+            at: Local::now(),
+            level,
+            message: message.0,
+        };
+
+        if let Some(Ok(mut recent)) = RECENT.get().map(Mutex::lock) {
+            if recent.len() == CAPACITY {
+                recent.pop_front();
+            }
+            recent.push_back(entry);
+        }
+    }
+}
+
+/// An event's message, then its other fields as `name=value`.
+#[derive(Default)]
+struct Message(String);
+
+impl Visit for Message {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "message" => self.0.insert_str(0, &format!("{:?}", value)),
+            // where a `log` record came from, not worth the space
+            name if name.starts_with("log.") => {}
+            name => self.0.push_str(&format!(" {}={:?}", name, value)),
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "message" => self.0.insert_str(0, value),
+            name if name.starts_with("log.") => {}
+            name => self.0.push_str(&format!(" {}={}", name, value)),
+        }
+    }
+}
+
+/// Install the logger. Messages at `level` or above reach the panel and,
+/// when `file` is given, are appended there too, unless `filter` or the
+/// `POM_LOG` variable pick others: `pom::integrations=trace,info`.
+pub fn init(
+    level: LevelFilter,
+    file: Option<&Path>,
+    filter: Option<&str>,
+    rotate: LogRotate,
+) -> Result<()> {
+    let file = match file {
+        Some(path) => {
+            let directives = env::var("POM_LOG")
+                .ok()
+                .or(filter.map(str::to_string))
+                .unwrap_or_else(|| level.to_string());
+            let filter = EnvFilter::try_new(&directives).map_err(|err| PomError::LogFilter {
+                filter: directives,
+                reason: err.to_string(),
+            })?;
+            let layer = tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(appender(path, rotate)?)
+                .with_filter(filter);
+            Some(layer)
+        }
+        None => None,
+    };
+
+    RECENT.get_or_init(|| Mutex::new(VecDeque::with_capacity(CAPACITY)));
+    // only fails if a logger is already installed, which is fine
+    let _ = tracing_subscriber::registry()
+        .with(Panel.with_filter(level.as_trace()))
+        .with(file)
+        .try_init();
+    Ok(())
+}
+
+/// `path` itself, or with `rotate` a file a day or an hour named after it.
+fn appender(path: &Path, rotate: LogRotate) -> Result<RollingFileAppender> {
+    let failed = |source| PomError::LogFile {
+        path: path.to_path_buf(),
+        source,
+    };
+    let Some(name) = path.file_name() else {
+        return Err(failed(io::ErrorKind::InvalidInput.into()));
+    };
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let builder = RollingFileAppender::builder().filename_prefix(name.to_string_lossy());
+    let builder = match rotate {
+        LogRotate::Never => builder.rotation(Rotation::NEVER),
+        LogRotate::Hourly => builder.rotation(Rotation::HOURLY).max_log_files(KEEP),
+        LogRotate::Daily => builder.rotation(Rotation::DAILY).max_log_files(KEEP),
+    };
+    builder
+        .build(dir)
+        .map_err(|err| failed(io::Error::other(err)))
+}
+
+/// The newest `count` messages, oldest first.
+pub fn recent(count: usize) -> Vec<Entry> {
+    let Some(Ok(recent)) = RECENT.get().map(Mutex::lock) else {
+        return Vec::new();
+    };
+    let skip = recent.len().saturating_sub(count);
+    recent.iter().skip(skip).cloned().collect()
+}
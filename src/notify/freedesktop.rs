@@ -0,0 +1,61 @@
+//! libnotify's `notify-send`, for Linux and BSD desktops.
+
+use std::{
+    process::{Command, Stdio},
+    thread,
+};
+
+use super::{launch, Answered, Backend};
+use crate::error::Result;
+
+pub struct NotifySend;
+
+impl Backend for NotifySend {
+    fn send(&self, title: &str, body: &str) -> Result<()> {
+        let mut command = Command::new("notify-send");
+        command.args(["--app-name=pom", title, body]);
+        launch(command)
+    }
+
+    fn ask(
+        &self,
+        title: &str,
+        body: &str,
+        buttons: &[(&str, &str)],
+        answered: Answered,
+    ) -> Result<()> {
+        let mut command = Command::new("notify-send");
+        command
+            .args(["--app-name=pom", "--wait"])
+            .args(
+                buttons
+                    .iter()
+                    .map(|(key, label)| format!("--action={}={}", key, label)),
+            )
+            .args([title, body])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null());
+        let Ok(child) = command.spawn() else {
+            return self.send(title, body);
+        };
+
+        let (title, body) = (title.to_string(), body.to_string());
+        thread::spawn(move || match child.wait_with_output() {
+            // nothing printed when it was dismissed
+            Ok(output) if output.status.success() => {
+                let key = String::from_utf8_lossy(&output.stdout);
+                if !key.trim().is_empty() {
+                    answered(key.trim());
+                }
+            }
+            // notify-send before 0.7.10 has no buttons
+            _ => {
+                if let Err(err) = NotifySend.send(&title, &body) {
+                    log::warn!("{}", err.report());
+                }
+            }
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+//! Notification Center. `osascript` is always there but its notifications
+//! can't have buttons; `alerter`, when installed, shows ones that do.
+
+use std::{
+    process::{Command, Stdio},
+    thread,
+};
+
+use super::{launch, Answered, Backend};
+use crate::error::Result;
+
+pub struct Osascript;
+
+impl Backend for Osascript {
+    fn send(&self, title: &str, body: &str) -> Result<()> {
+        let mut command = Command::new("osascript");
+        command.args([
+            "-e",
+            &format!("display notification {:?} with title {:?}", body, title),
+        ]);
+        launch(command)
+    }
+}
+
+/// <https://github.com/vjeantet/alerter>, which prints the label of the
+/// button pressed.
+pub struct Alerter;
+
+impl Backend for Alerter {
+    fn send(&self, title: &str, body: &str) -> Result<()> {
+        // alerter's notifications stay until dismissed, too much for these
+        Osascript.send(title, body)
+    }
+
+    fn ask(
+        &self,
+        title: &str,
+        body: &str,
+        buttons: &[(&str, &str)],
+        answered: Answered,
+    ) -> Result<()> {
+        // commas separate the labels
+        let labels: Vec<(String, String)> = buttons
+            .iter()
+            .map(|(key, label)| (key.to_string(), label.replace(',', "")))
+            .collect();
+        let actions: Vec<&str> = labels.iter().map(|(_, label)| label.as_str()).collect();
+        let mut command = Command::new("alerter");
+        command
+            .args(["-title", title, "-message", body, "-group", "pom"])
+            .args(["-actions", &actions.join(",")])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null());
+        let Ok(child) = command.spawn() else {
+            return self.send(title, body);
+        };
+
+        thread::spawn(move || {
+            let Ok(output) = child.wait_with_output() else {
+                return;
+            };
+            // anything else is `@CLOSED`, `@TIMEOUT` and the like
+            let pressed = String::from_utf8_lossy(&output.stdout);
+            if let Some((key, _)) = labels.iter().find(|(_, label)| *label == pressed.trim()) {
+                answered(key);
+            }
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+use std::{
+    io::{self, Write},
+    process::Command,
+    sync::Arc,
+    thread,
+};
+
+use crate::{
+    error::{PomError, Result},
+    platform::find_executable,
+};
+
+mod freedesktop;
+mod macos;
+mod windows;
+
+/// What a notification button hands back: the key of the one pressed.
+type Answered = Box<dyn FnOnce(&str) + Send>;
+
+/// One way of putting a notification on the screen, native to an OS.
+trait Backend: Send + Sync {
+    fn send(&self, title: &str, body: &str) -> Result<()>;
+
+    /// A notification with buttons, `(key, label)`. Backends that can't
+    /// hear a click show it without them and never answer.
+    fn ask(
+        &self,
+        title: &str,
+        body: &str,
+        _buttons: &[(&str, &str)],
+        _answered: Answered,
+    ) -> Result<()> {
+        self.send(title, body)
+    }
+
+    /// Rings the terminal bell, so there's no need to ring it again.
+    fn rings(&self) -> bool {
+        false
+    }
+}
+
+/// How a desktop notification gets delivered on this machine.
+#[derive(Clone)]
+pub struct Notifier(Arc<dyn Backend>);
+
+impl Notifier {
+    pub fn detect() -> Self {
+        let backend: Option<Arc<dyn Backend>> = if cfg!(windows) {
+            find_executable("powershell").map(|_| Arc::new(windows::Toast) as _)
+        } else if cfg!(target_os = "macos") {
+            if find_executable("alerter").is_some() {
+                Some(Arc::new(macos::Alerter))
+            } else {
+                find_executable("osascript").map(|_| Arc::new(macos::Osascript) as _)
+            }
+        } else {
+            find_executable("notify-send").map(|_| Arc::new(freedesktop::NotifySend) as _)
+        };
+        Notifier(backend.unwrap_or_else(|| Arc::new(Bell)))
+    }
+
+    /// One that tells nobody, for headless runs.
+    pub fn silent() -> Self {
+        Notifier(Arc::new(Silent))
+    }
+
+    /// Fire and forget: the notifier process is reaped on a helper thread
+    /// so a slow notification daemon never stalls the UI.
+    pub fn send(&self, title: &str, body: &str) -> Result<()> {
+        self.0.send(title, body)
+    }
+
+    /// A notification with buttons, `(key, label)`; `answered` gets the key
+    /// of the one pressed, on another thread. Where the notifier can't have
+    /// buttons this is a plain notification that never answers.
+    pub fn ask(
+        &self,
+        title: &str,
+        body: &str,
+        buttons: &[(&str, &str)],
+        answered: impl FnOnce(&str) + Send + 'static,
+    ) -> Result<()> {
+        if buttons.is_empty() {
+            return self.send(title, body);
+        }
+        self.0.ask(title, body, buttons, Box::new(answered))
+    }
+
+    pub fn rings(&self) -> bool {
+        self.0.rings()
+    }
+}
+
+/// No notifier found: ring the terminal bell instead.
+struct Bell;
+
+impl Backend for Bell {
+    fn send(&self, _title: &str, _body: &str) -> Result<()> {
+        bell()
+    }
+
+    fn rings(&self) -> bool {
+        true
+    }
+}
+
+/// Nothing is shown, nothing rings.
+struct Silent;
+
+impl Backend for Silent {
+    fn send(&self, _title: &str, _body: &str) -> Result<()> {
+        Ok(())
+    }
+
+    // nor does the bell, there's no terminal to ring
+    fn rings(&self) -> bool {
+        true
+    }
+}
+
+/// Start a notifier and reap it on a helper thread.
+fn launch(mut command: Command) -> Result<()> {
+    match command.spawn() {
+        Ok(mut child) => {
+            thread::spawn(move || child.wait());
+            Ok(())
+        }
+        // the notifier vanished since detection, still tell the user
+        Err(err) => {
+            log::warn!(
+                "notifier failed to start ({}), ringing the bell instead",
+                err
+            );
+            bell()
+        }
+    }
+}
+
+/// Ring the terminal bell.
+pub fn bell() -> Result<()> {
+    let mut stdout = io::stdout();
+    stdout
+        .write_all(b"\x07")
+        .and_then(|_| stdout.flush())
+        .map_err(PomError::Notify)
+}
@@ -0,0 +1,37 @@
+//! WinRT toasts, raised through PowerShell. A click on a toast button only
+//! reaches an app registered with the Start menu, so these have none.
+
+use std::process::Command;
+
+use super::{launch, Backend};
+use crate::error::Result;
+
+pub struct Toast;
+
+impl Backend for Toast {
+    fn send(&self, title: &str, body: &str) -> Result<()> {
+        let mut command = Command::new("powershell");
+        command.args([
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            &toast_script(title, body),
+        ]);
+        launch(command)
+    }
+}
+
+fn toast_script(title: &str, body: &str) -> String {
+    // single quotes are the only thing that needs escaping in a PS literal
+    let quote = |s: &str| s.replace('\'', "''");
+    format!(
+        "$m = [Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime]; \
+         $t = $m::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02); \
+         $x = $t.GetElementsByTagName('text'); \
+         $x.Item(0).AppendChild($t.CreateTextNode('{}')) > $null; \
+         $x.Item(1).AppendChild($t.CreateTextNode('{}')) > $null; \
+         $m::CreateToastNotifier('pom').Show([Windows.UI.Notifications.ToastNotification]::new($t))",
+        quote(title),
+        quote(body)
+    )
+}
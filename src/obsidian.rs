@@ -0,0 +1,127 @@
+//! Obsidian daily notes: a line for each work session at the end of the
+//! note of the day it counts towards, e.g.
+//!
+//! ```markdown
+//! - 🍅 14:00–14:25 #rust parser refactor
+//! ```
+//!
+//! A line that is already in the note isn't added again, so `pom obsidian`
+//! can be run over a day as often as needed.
+
+use chrono::{Local, NaiveDate, NaiveTime, Utc};
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    app::{Phase, Session},
+    config::{self, Config},
+    error::{PomError, Result},
+    history::Store,
+    report, template,
+};
+
+/// Add `session`'s line to its day's note; false when it was there
+/// already, or isn't a work session of a minute or more.
+pub fn append(config: &config::Obsidian, day_start: NaiveTime, session: &Session) -> Result<bool> {
+    if session.phase != Phase::Work || session.elapsed < 60 {
+        return Ok(false);
+    }
+    let path = note(config, report::local_date(session, day_start));
+    let line = line(config, session);
+    let existing = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(source) => return Err(PomError::ObsidianWrite { path, source }),
+    };
+    if existing.lines().any(|l| l == line) {
+        return Ok(false);
+    }
+    let mut text = String::new();
+    if !existing.is_empty() && !existing.ends_with('\n') {
+        text.push('\n');
+    }
+    text.push_str(&line);
+    text.push('\n');
+    append_file(&path, &text).map_err(|source| PomError::ObsidianWrite { path, source })?;
+    Ok(true)
+}
+
+/// `pom obsidian`: every work session of `date`, today by default.
+pub fn export(date: Option<NaiveDate>) -> Result<()> {
+    let config = Config::load()?;
+    let obsidian = config.obsidian.as_ref().ok_or(PomError::NoObsidian)?;
+    let date = date.unwrap_or_else(|| report::day(Utc::now(), config.day_starts_at));
+    let sessions = Store::configured(&config.history)?.load()?;
+    let mut added = 0;
+    for session in sessions
+        .iter()
+        .filter(|session| report::local_date(session, config.day_starts_at) == date)
+    {
+        added += append(obsidian, config.day_starts_at, session)? as usize;
+    }
+    println!(
+        "{} session{} added to {}",
+        added,
+        if added == 1 { "" } else { "s" },
+        note(obsidian, date).display()
+    );
+    Ok(())
+}
+
+/// The daily note for `date`, as Obsidian names it.
+fn note(config: &config::Obsidian, date: NaiveDate) -> PathBuf {
+    let name = format!("{}.md", date.format(&config.format));
+    config.vault.join(&config.folder).join(name)
+}
+
+/// `session` through the configured line, without the gaps left by empty
+/// placeholders.
+fn line(config: &config::Obsidian, session: &Session) -> String {
+    let started = session.started.with_timezone(&Local);
+    let ended = session.ended.with_timezone(&Local);
+    let line = template::render_with(&config.line, |name| {
+        Some(match name {
+            "start" => started.format("%H:%M").to_string(),
+            "end" => ended.format("%H:%M").to_string(),
+            "date" => started.format("%Y-%m-%d").to_string(),
+            "duration" => report::duration(session.elapsed),
+            "tags" => session
+                .tags
+                .iter()
+                .map(|tag| format!("#{}", tag))
+                .collect::<Vec<_>>()
+                .join(" "),
+            "note" => session
+                .note
+                .as_deref()
+                .unwrap_or_default()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" "),
+            "phase" => session.phase.name().to_string(),
+            _ => return None,
+        })
+    });
+    // the indent stays, so lines can nest under a bullet
+    let body = line.trim_start();
+    let indent = &line[..line.len() - body.len()];
+    format!(
+        "{}{}",
+        indent,
+        body.split_whitespace().collect::<Vec<_>>().join(" ")
+    )
+}
+
+fn append_file(path: &Path, text: &str) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(text.as_bytes())
+}
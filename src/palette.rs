@@ -0,0 +1,187 @@
+//! The command palette, on `:`: every action by name, narrowed down by the
+//! tag picker's fuzzy search, and a few that take an argument, so what is
+//! rarely used doesn't need a key of its own.
+
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Paragraph},
+    Frame,
+};
+
+use crate::{
+    keys::{self, Action, Keymap},
+    picker::score,
+};
+
+pub struct Palette {
+    pub query: String,
+    entries: Vec<Entry>,
+    /// indices into `entries` that match the query, best first
+    matches: Vec<usize>,
+    selected: usize,
+}
+
+struct Entry {
+    name: &'static str,
+    does: Does,
+    /// what it is bound to, for learning the keys
+    keys: Option<String>,
+}
+
+enum Does {
+    Action(Action),
+    /// what the argument is, and what it makes if it reads
+    Argument(&'static str, fn(&str) -> Option<Choice>),
+}
+
+/// What was picked from the palette.
+pub enum Choice {
+    Action(Action),
+    /// minutes for the next work session, or for the current phase
+    Duration(i64),
+    /// minutes more for the current phase, or fewer
+    Add(i64),
+    /// percent
+    Volume(i64),
+    /// one of `[profiles]`, by name
+    Profile(String),
+}
+
+impl Palette {
+    pub fn new(keymap: &Keymap) -> Self {
+        let argument = |name, what, choice: fn(&str) -> Option<Choice>| Entry {
+            name,
+            does: Does::Argument(what, choice),
+            keys: None,
+        };
+        let mut entries = vec![
+            argument("duration", "<minutes>", |text| {
+                text.parse().ok().map(Choice::Duration)
+            }),
+            argument("add", "<minutes>", |text| {
+                text.parse().ok().map(Choice::Add)
+            }),
+            argument("volume", "<percent>", |text| {
+                text.parse().ok().map(Choice::Volume)
+            }),
+            argument("profile", "<name>", |text| {
+                (!text.is_empty()).then(|| Choice::Profile(text.to_string()))
+            }),
+        ];
+        entries.extend(
+            keys::actions()
+                .filter(|&action| action != Action::Palette)
+                .map(|action| Entry {
+                    name: action.name(),
+                    does: Does::Action(action),
+                    keys: keymap.sequence(action),
+                }),
+        );
+        let mut palette = Palette {
+            query: String::new(),
+            entries,
+            matches: Vec::new(),
+            selected: 0,
+        };
+        palette.filter();
+        palette
+    }
+
+    /// Match the entries against the query again, after it changed: the
+    /// first word against their names, and with more after it, only those
+    /// that take an argument.
+    pub fn filter(&mut self) {
+        let (name, rest) = split(&self.query);
+        let mut scored: Vec<(i64, usize)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| rest.is_empty() || matches!(entry.does, Does::Argument(..)))
+            .filter_map(|(i, entry)| score(name, entry.name).map(|score| (score, i)))
+            .collect();
+        // ties keep the list's order
+        scored.sort_by_key(|&(score, _)| -score);
+        self.matches = scored.into_iter().map(|(_, i)| i).collect();
+        self.selected = 0;
+    }
+
+    pub fn select(&mut self, by: isize) {
+        let last = self.matches.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(by).min(last);
+    }
+
+    /// The selected entry, with the argument typed after it; what is missing
+    /// otherwise.
+    pub fn picked(&self) -> Result<Choice, String> {
+        let Some(&i) = self.matches.get(self.selected) else {
+            return Err(format!("no action matches \"{}\"", self.query.trim()));
+        };
+        let entry = &self.entries[i];
+        match entry.does {
+            Does::Action(action) => Ok(Choice::Action(action)),
+            Does::Argument(what, choice) => {
+                choice(split(&self.query).1).ok_or_else(|| format!("{} needs {}", entry.name, what))
+            }
+        }
+    }
+}
+
+/// The first word, and whatever follows it.
+fn split(query: &str) -> (&str, &str) {
+    let query = query.trim_start();
+    match query.split_once(' ') {
+        Some((name, rest)) => (name, rest.trim()),
+        None => (query, ""),
+    }
+}
+
+pub fn draw<B: Backend>(f: &mut Frame<B>, palette: &Palette, area: Rect) {
+    let block = Block::default().title(format!(
+        "Command: {} of {} actions",
+        palette.matches.len(),
+        palette.entries.len()
+    ));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let mut lines = vec![Spans::from(vec![
+        Span::styled(": ", Style::default().fg(Color::Yellow)),
+        Span::raw(format!("{}_", palette.query)),
+    ])];
+    let rows = (inner.height as usize).saturating_sub(1);
+    // keep the selection in view
+    let offset = (palette.selected + 1).saturating_sub(rows);
+    let width = inner.width as usize;
+    lines.extend(
+        palette
+            .matches
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(rows)
+            .map(|(i, &entry)| {
+                let entry = &palette.entries[entry];
+                let name = match entry.does {
+                    Does::Action(_) => entry.name.to_string(),
+                    Does::Argument(what, _) => format!("{} {}", entry.name, what),
+                };
+                let keys = entry.keys.as_deref().unwrap_or_default();
+                let gap = width.saturating_sub(name.chars().count() + keys.chars().count());
+                if i == palette.selected {
+                    Spans::from(Span::styled(
+                        format!("{}{:gap$}{}", name, "", keys, gap = gap),
+                        Style::default().add_modifier(Modifier::REVERSED),
+                    ))
+                } else {
+                    Spans::from(vec![
+                        Span::raw(format!("{}{:gap$}", name, "", gap = gap)),
+                        Span::styled(keys.to_string(), Style::default().fg(Color::DarkGray)),
+                    ])
+                }
+            }),
+    );
+    f.render_widget(Paragraph::new(lines), inner);
+}
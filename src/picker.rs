@@ -0,0 +1,286 @@
+//! Picking what to focus on: every tag in the history, narrowed down by a
+//! fuzzy search, the ones used most and most lately first. Or quicker, one
+//! of the few worked on last, by its number.
+
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, Utc};
+use std::collections::HashMap;
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Paragraph},
+    Frame,
+};
+
+use crate::{
+    app::{Phase, Session},
+    error::Result,
+    history::{tally, Store, Tally},
+    report,
+};
+
+/// Days for a use to count half as much.
+const HALF_LIFE: f64 = 7.0;
+
+/// Tags in the quick pick.
+const RECENT: usize = 5;
+
+pub struct Picker {
+    pub query: String,
+    /// each tag with how much it has been used, recent uses counting more
+    tags: Vec<(String, f64)>,
+    /// indices into `tags` that match the query, best first
+    matches: Vec<usize>,
+    selected: usize,
+    /// the quick pick: the tags worked on last, in that order
+    pub recent: bool,
+    /// each tag's work sessions so far, kept up as more end
+    tallies: HashMap<String, Tally>,
+    today: NaiveDate,
+    day_start: NaiveTime,
+}
+
+impl Picker {
+    pub fn new(history: &Store, now: DateTime<Utc>, day_start: NaiveTime) -> Result<Self> {
+        let sessions = history.load()?;
+        let today = report::day(now, day_start);
+        let mut picker = Picker {
+            query: String::new(),
+            tags: used(&sessions, now),
+            matches: Vec::new(),
+            selected: 0,
+            recent: false,
+            tallies: tally(&sessions, today, day_start)
+                .into_iter()
+                .map(|tally| (tally.tag.clone(), tally))
+                .collect(),
+            today,
+            day_start,
+        };
+        picker.filter();
+        Ok(picker)
+    }
+
+    /// The quick pick, from the history's tally of each tag.
+    pub fn recent(history: &Store, now: DateTime<Utc>, day_start: NaiveTime) -> Result<Self> {
+        let tallies: Vec<Tally> = history
+            .tallies(day_start)?
+            .into_iter()
+            .take(RECENT)
+            .collect();
+        let mut picker = Picker {
+            query: String::new(),
+            // the order they come in
+            tags: tallies
+                .iter()
+                .map(|tally| (tally.tag.clone(), 0.0))
+                .collect(),
+            matches: Vec::new(),
+            selected: 0,
+            recent: true,
+            tallies: tallies
+                .into_iter()
+                .map(|tally| (tally.tag.clone(), tally))
+                .collect(),
+            today: report::day(now, day_start),
+            day_start,
+        };
+        picker.filter();
+        Ok(picker)
+    }
+
+    /// A session that ended while the picker is open, so its counts stay
+    /// right.
+    pub fn add(&mut self, session: &Session) {
+        if session.phase != Phase::Work {
+            return;
+        }
+        let mut new = false;
+        for tag in &session.tags {
+            self.tallies
+                .entry(tag.clone())
+                .or_insert_with(|| Tally::new(tag, session.ended))
+                .add(session, self.today, self.day_start);
+            // a tag first used just now, if it's not the quick pick
+            if !self.recent && !self.tags.iter().any(|(t, _)| t == tag) {
+                self.tags.push((tag.clone(), 1.0));
+                new = true;
+            }
+        }
+        if new {
+            self.filter();
+        }
+    }
+
+    /// What is shown beside a tag: its sessions that ran out, today and in
+    /// all, and in the quick pick when it was last worked on.
+    fn about(&self, tag: &str) -> String {
+        let Some(tally) = self.tallies.get(tag) else {
+            return String::new();
+        };
+        let counts = format!("{} today, {} in all", tally.today, tally.completed);
+        match self.recent {
+            true => {
+                let last = tally.last.with_timezone(&Local).format("%a %m-%d %H:%M");
+                format!("{}, {}", last, counts)
+            }
+            false => counts,
+        }
+    }
+
+    /// Match the tags against the query again, after it changed.
+    pub fn filter(&mut self) {
+        let mut scored: Vec<(f64, usize)> = self
+            .tags
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (tag, used))| {
+                score(&self.query, tag).map(|score| (score as f64 + 4.0 * used.ln_1p(), i))
+            })
+            .collect();
+        scored.sort_by(|a, b| match self.recent {
+            true => b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)),
+            false => {
+                b.0.total_cmp(&a.0)
+                    .then_with(|| self.tags[a.1].0.cmp(&self.tags[b.1].0))
+            }
+        });
+        self.matches = scored.into_iter().map(|(_, i)| i).collect();
+        self.selected = 0;
+    }
+
+    /// Tags to pick from, matching or not.
+    pub fn count(&self) -> usize {
+        self.tags.len()
+    }
+
+    pub fn select(&mut self, by: isize) {
+        let last = self.matches.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(by).min(last);
+    }
+
+    /// Tab: the query becomes the selected tag, to go on typing from; again,
+    /// the next one (`by` -1 for the one before).
+    pub fn complete(&mut self, by: isize) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let len = self.matches.len() as isize;
+        if self.picked().as_deref() == Some(self.query.as_str()) {
+            self.selected = (self.selected as isize + by).rem_euclid(len) as usize;
+        }
+        self.query = self.tags[self.matches[self.selected]].0.clone();
+    }
+
+    /// The `n`th of the quick pick, counting from 1.
+    pub fn numbered(&self, n: usize) -> Option<String> {
+        if !self.recent {
+            return None;
+        }
+        let i = n.checked_sub(1)?;
+        self.tags.get(i).map(|(tag, _)| tag.clone())
+    }
+
+    /// The selected tag, or what was typed when nothing matches it.
+    pub fn picked(&self) -> Option<String> {
+        match self.matches.get(self.selected) {
+            Some(&i) => Some(self.tags[i].0.clone()),
+            None => (!self.query.is_empty()).then(|| self.query.clone()),
+        }
+    }
+}
+
+/// Every tag in `sessions` with how much it has been used, recent uses
+/// counting more; in no particular order.
+pub fn used(sessions: &[Session], now: DateTime<Utc>) -> Vec<(String, f64)> {
+    let mut used: HashMap<String, f64> = HashMap::new();
+    for session in sessions {
+        let days = (now - session.ended).num_minutes().max(0) as f64 / (24.0 * 60.0);
+        for tag in &session.tags {
+            *used.entry(tag.clone()).or_default() += 0.5f64.powf(days / HALF_LIFE);
+        }
+    }
+    used.into_iter().collect()
+}
+
+/// How well `query` matches `tag` as a subsequence, fzf style: letters in
+/// a row and at the start of words count more, gaps count against it.
+pub fn score(query: &str, tag: &str) -> Option<i64> {
+    let tag: Vec<char> = tag.chars().collect();
+    let mut score = 0;
+    let mut at = 0;
+    let mut last: Option<usize> = None;
+    for wanted in query.chars().flat_map(char::to_lowercase) {
+        let found = (at..tag.len()).find(|&i| tag[i].to_lowercase().eq(Some(wanted)))?;
+        score += 1;
+        if last.is_some_and(|last| last + 1 == found) {
+            score += 5;
+        } else if let Some(last) = last {
+            score -= (found - last - 1).min(5) as i64;
+        }
+        if found == 0 || !tag[found - 1].is_alphanumeric() {
+            score += 8;
+        }
+        last = Some(found);
+        at = found + 1;
+    }
+    Some(score)
+}
+
+pub fn draw<B: Backend>(f: &mut Frame<B>, picker: &Picker, area: Rect) {
+    let block = Block::default().title(match picker.recent {
+        true => format!("Resume: the {} tags worked on last", picker.tags.len()),
+        false => format!(
+            "Focus on: {} of {} tags",
+            picker.matches.len(),
+            picker.tags.len()
+        ),
+    });
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let mut lines = vec![Spans::from(vec![
+        Span::styled("> ", Style::default().fg(Color::Yellow)),
+        Span::raw(format!("{}_", picker.query)),
+    ])];
+    let rows = (inner.height as usize).saturating_sub(1);
+    // keep the selection in view
+    let offset = (picker.selected + 1).saturating_sub(rows);
+    let width = inner.width as usize;
+    lines.extend(
+        picker
+            .matches
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(rows)
+            .map(|(i, &tag)| {
+                let name = &picker.tags[tag].0;
+                let line = match picker.recent {
+                    true => format!("{} #{}", tag + 1, name),
+                    false => format!("#{}", name),
+                };
+                let about = picker.about(name);
+                let gap = width.saturating_sub(line.chars().count() + about.chars().count());
+                if i == picker.selected {
+                    Spans::from(Span::styled(
+                        format!("{}{:gap$}{}", line, "", about, gap = gap),
+                        Style::default().add_modifier(Modifier::REVERSED),
+                    ))
+                } else {
+                    Spans::from(vec![
+                        Span::raw(format!("{}{:gap$}", line, "", gap = gap)),
+                        Span::styled(about, Style::default().fg(Color::DarkGray)),
+                    ])
+                }
+            }),
+    );
+    if picker.matches.is_empty() && !picker.query.is_empty() {
+        lines.push(Spans::from(Span::styled(
+            format!("nothing matches, [ enter ] for a new #{}", picker.query),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    f.render_widget(Paragraph::new(lines), inner);
+}
@@ -0,0 +1,63 @@
+use std::{
+    ffi::CString,
+    fs::{self, File},
+    io::{self, BufRead, BufReader},
+    os::unix::{ffi::OsStrExt, fs::FileTypeExt},
+    path::{Path, PathBuf},
+    thread,
+};
+
+use crate::{app::Command, control::Handle};
+
+/// Read text commands (`pause`, `skip`, `add 300`...) from a FIFO, one per
+/// line, so shell scripts can do `echo skip > $pipe`.
+pub fn serve(path: PathBuf, handle: Handle) {
+    if let Err(err) = create(&path) {
+        log::warn!("command pipe {} unavailable: {}", path.display(), err);
+        return;
+    }
+
+    thread::spawn(move || loop {
+        // blocks until a writer shows up; EOF means it went away, so reopen
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(err) => {
+                log::warn!("command pipe {} closed: {}", path.display(), err);
+                return;
+            }
+        };
+
+        for line in BufReader::new(file).lines().map_while(io::Result::ok) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match line.parse::<Command>() {
+                Ok(command) => handle.send(command),
+                Err(err) => log::warn!("command pipe: {}", err),
+            }
+        }
+    });
+}
+
+fn create(path: &Path) -> io::Result<()> {
+    match fs::metadata(path) {
+        Ok(meta) if meta.file_type().is_fifo() => return Ok(()),
+        Ok(_) => {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "exists and is not a FIFO",
+            ))
+        }
+        Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+        Err(_) => {}
+    }
+
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    // SAFETY: c_path is a valid NUL-terminated string for the whole call
+    if unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
@@ -0,0 +1,314 @@
+use directories::ProjectDirs;
+use std::{
+    cell::Cell,
+    env, io,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::OnceLock,
+};
+
+use crate::{
+    audio::Player,
+    config::Config,
+    error::{PomError, Result},
+    notify::Notifier,
+    speech::Speaker,
+};
+
+/// Beside the binary, what makes a copy of pom portable.
+const PORTABLE: &str = "pom-data";
+
+static DIRS: OnceLock<Option<Dirs>> = OnceLock::new();
+
+thread_local! {
+    /// where this thread keeps its files instead, see [`locate_here`]
+    static HERE: Cell<Option<&'static Dirs>> = const { Cell::new(None) };
+}
+
+/// Where pom keeps its files.
+pub struct Dirs {
+    config: PathBuf,
+    data: PathBuf,
+    /// what belongs to this machine only, like the running timer's state
+    data_local: PathBuf,
+    cache: PathBuf,
+}
+
+impl Dirs {
+    pub fn config_dir(&self) -> &Path {
+        &self.config
+    }
+
+    pub fn data_dir(&self) -> &Path {
+        &self.data
+    }
+
+    pub fn data_local_dir(&self) -> &Path {
+        &self.data_local
+    }
+
+    pub fn cache_dir(&self) -> &Path {
+        &self.cache
+    }
+
+    /// Per-user config/data locations (XDG on Linux, AppData on Windows,
+    /// Application Support on macOS).
+    fn per_user() -> Option<Dirs> {
+        let dirs = ProjectDirs::from("org", "pomtui", "pom")?;
+        Some(Dirs {
+            config: dirs.config_dir().to_path_buf(),
+            data: dirs.data_dir().to_path_buf(),
+            data_local: dirs.data_local_dir().to_path_buf(),
+            cache: dirs.cache_dir().to_path_buf(),
+        })
+    }
+
+    /// Everything in one directory, config included.
+    fn portable(dir: PathBuf) -> Dirs {
+        Dirs {
+            config: dir.clone(),
+            data: dir.clone(),
+            data_local: dir.clone(),
+            cache: dir.join("cache"),
+        }
+    }
+}
+
+/// Where pom keeps its files, once `locate` has decided.
+pub fn project_dirs() -> Option<&'static Dirs> {
+    if let Some(dirs) = HERE.with(Cell::get) {
+        return Some(dirs);
+    }
+    DIRS.get_or_init(Dirs::per_user).as_ref()
+}
+
+/// Everything in `dir`, config included, for this thread only and until
+/// the guard is dropped; for runs side by side in one process, like the
+/// headless tests. The few bytes of each `Dirs` are never freed.
+pub fn locate_here(dir: PathBuf) -> Here {
+    let dirs: &'static Dirs = Box::leak(Box::new(Dirs::portable(dir)));
+    Here(HERE.with(|here| here.replace(Some(dirs))))
+}
+
+/// Where this thread kept its files before [`locate_here`].
+pub struct Here(Option<&'static Dirs>);
+
+impl Drop for Here {
+    fn drop(&mut self) {
+        HERE.with(|here| here.set(self.0));
+    }
+}
+
+/// Decide where pom keeps its files, before anything is read or written:
+/// `pom-data` beside the binary with `--portable`, or when it is already
+/// there; the per-user places otherwise. History and state go in
+/// `data_dir` instead if one is given, or set in the config.
+pub fn locate(portable: bool, data_dir: Option<PathBuf>) {
+    let beside = env::current_exe()
+        .ok()
+        .and_then(|exe| Some(exe.parent()?.join(PORTABLE)));
+    let mut dirs = match beside {
+        Some(dir) if portable || dir.is_dir() => Some(Dirs::portable(dir)),
+        _ => Dirs::per_user(),
+    };
+
+    let cwd = env::current_dir().unwrap_or_default();
+    let data_dir = match data_dir {
+        Some(dir) => Some(cwd.join(dir)),
+        // relative to the config it is set in
+        None => dirs.as_ref().and_then(|dirs| {
+            let config = dirs.config.join("config.toml");
+            let dir = Config::peek(&config)?.data_dir?;
+            Some(dirs.config.join(dir))
+        }),
+    };
+    if let Some(dir) = data_dir {
+        let cache = dirs
+            .as_ref()
+            .map_or_else(|| dir.join("cache"), |dirs| dirs.cache.clone());
+        let config = dirs
+            .as_ref()
+            .map_or_else(|| dir.clone(), |dirs| dirs.config.clone());
+        dirs = Some(Dirs {
+            config,
+            data: dir.clone(),
+            data_local: dir,
+            cache,
+        });
+    }
+    let _ = DIRS.set(dirs);
+}
+
+/// What the running platform and terminal can actually do.
+///
+/// Checked at runtime rather than assumed from the build target, so a
+/// binary behaves sensibly under conhost, Windows Terminal, ssh, etc.
+pub struct Capabilities {
+    /// shell job control (^Z / SIGTSTP) is available
+    pub job_control: bool,
+    /// how completion notifications get delivered
+    pub notifier: Notifier,
+    /// program used for sounds, if any is installed
+    pub player: Option<Player>,
+    /// text-to-speech program, if any is installed
+    pub speaker: Option<Speaker>,
+    /// the terminal is set up for UTF-8, so symbols beyond ASCII show
+    pub unicode: bool,
+    /// how many colors the terminal shows
+    pub colors: Colors,
+    /// blinking text blinks, rather than showing as something else
+    pub blink: bool,
+    /// OSC 8 links show as links, rather than as escape codes
+    pub hyperlinks: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+pub enum Colors {
+    /// none at all, or `NO_COLOR` asked for none
+    Mono,
+    /// the 16 ANSI colors
+    Basic,
+    /// the xterm 256 color palette
+    Indexed,
+    /// 24-bit RGB
+    True,
+}
+
+impl Capabilities {
+    pub fn detect() -> Self {
+        Capabilities {
+            job_control: job_control(),
+            notifier: Notifier::detect(),
+            player: Player::detect(),
+            speaker: Speaker::detect(),
+            unicode: unicode(),
+            colors: colors(),
+            blink: blink(),
+            hyperlinks: hyperlinks(),
+        }
+    }
+}
+
+/// Whether ^Z hands the terminal to a shell that can resume pom: stdin is
+/// a terminal with pom's process group in the foreground, and pom isn't
+/// the session leader, as it is when a terminal runs it directly.
+#[cfg(unix)]
+fn job_control() -> bool {
+    // SAFETY: these only read the ids of this process and its terminal
+    unsafe {
+        let group = libc::getpgrp();
+        libc::tcgetpgrp(libc::STDIN_FILENO) == group && libc::getsid(0) != libc::getpid()
+    }
+}
+
+#[cfg(not(unix))]
+fn job_control() -> bool {
+    false
+}
+
+/// Going by `COLORTERM` and `TERM`, the way most programs do; `NO_COLOR`
+/// (see no-color.org) turns them off.
+fn colors() -> Colors {
+    let term = env::var("TERM").unwrap_or_default();
+    let colorterm = env::var("COLORTERM").unwrap_or_default();
+    if env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) || term == "dumb" {
+        Colors::Mono
+    } else if colorterm == "truecolor"
+        || colorterm == "24bit"
+        || env::var_os("WT_SESSION").is_some()
+    {
+        Colors::True
+    } else if term.contains("256color") {
+        Colors::Indexed
+    } else if term.is_empty() && !cfg!(windows) {
+        Colors::Mono
+    } else {
+        Colors::Basic
+    }
+}
+
+/// The Linux console shows blink as a bright background, and the Windows
+/// console ignores it.
+fn blink() -> bool {
+    let term = env::var("TERM").unwrap_or_default();
+    if cfg!(windows) {
+        return env::var_os("WT_SESSION").is_some();
+    }
+    !(term.is_empty() || term == "dumb" || term == "linux")
+}
+
+/// Terminals that don't know OSC 8 mostly skip it; the Linux and Windows
+/// consoles print it.
+fn hyperlinks() -> bool {
+    let term = env::var("TERM").unwrap_or_default();
+    if cfg!(windows) {
+        return env::var_os("WT_SESSION").is_some();
+    }
+    !(term.is_empty() || term == "dumb" || term == "linux")
+}
+
+/// Going by the locale; Windows Terminal is UTF-8 whatever the code page.
+fn unicode() -> bool {
+    if env::var_os("WT_SESSION").is_some() {
+        return true;
+    }
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|var| env::var(var).ok().filter(|value| !value.is_empty()));
+    locale.is_some_and(|locale| {
+        let locale = locale.to_ascii_uppercase();
+        locale.contains("UTF-8") || locale.contains("UTF8")
+    })
+}
+
+/// Look up an executable on PATH, honouring the platform's exe suffix.
+pub fn find_executable(name: &str) -> Option<PathBuf> {
+    let file = format!("{}{}", name, env::consts::EXE_SUFFIX);
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(&file))
+        .find(|path| path.is_file())
+}
+
+/// Run a user-configured shell command (`pass show caldav`, ...) and return
+/// the first line it prints, for secrets that shouldn't sit in the config.
+pub fn run_secret_command(command: &str) -> Result<String> {
+    let output = if cfg!(windows) {
+        Command::new("cmd").args(["/C", command]).output()
+    } else {
+        Command::new("sh").args(["-c", command]).output()
+    }
+    .map_err(|source| PomError::SecretCommand {
+        command: command.to_string(),
+        source,
+    })?;
+
+    if !output.status.success() {
+        return Err(PomError::SecretCommand {
+            command: command.to_string(),
+            source: io::Error::other(format!("exited with {}", output.status)),
+        });
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout.lines().next().unwrap_or_default().to_string())
+}
+
+/// This machine's name, or `pom` when it has none to give.
+pub fn hostname() -> String {
+    #[cfg(unix)]
+    {
+        let mut name = [0u8; 256];
+        // SAFETY: the buffer outlives the call and its length is passed
+        if unsafe { libc::gethostname(name.as_mut_ptr().cast(), name.len()) } == 0 {
+            let end = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+            let name = String::from_utf8_lossy(&name[..end]);
+            // `laptop.local` and `laptop` are the same machine
+            if let Some(name) = name.split('.').next().filter(|name| !name.is_empty()) {
+                return name.to_string();
+            }
+        }
+    }
+    env::var("COMPUTERNAME")
+        .ok()
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "pom".to_string())
+}
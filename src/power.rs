@@ -0,0 +1,117 @@
+//! Whether the machine is running on its battery, for `low_power = "auto"`.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+/// How often the power source is looked at; unplugging isn't urgent.
+const POLL: Duration = Duration::from_secs(30);
+
+/// The power source, sampled on a background thread since on macOS that
+/// means running a helper program.
+pub struct Monitor {
+    on_battery: Arc<AtomicBool>,
+}
+
+impl Monitor {
+    pub fn start() -> Self {
+        let state = Arc::new(AtomicBool::new(false));
+        let shared = Arc::clone(&state);
+
+        thread::spawn(move || {
+            if on_battery().is_none() {
+                log::info!("battery detection unavailable on this machine");
+                return;
+            }
+            loop {
+                shared.store(on_battery().unwrap_or(false), Ordering::Relaxed);
+                thread::sleep(POLL);
+            }
+        });
+
+        Monitor { on_battery: state }
+    }
+
+    /// False when it can't be told, or there is no battery.
+    pub fn on_battery(&self) -> bool {
+        self.on_battery.load(Ordering::Relaxed)
+    }
+}
+
+/// A system battery discharging; mice and headsets report batteries too,
+/// scoped to the device.
+#[cfg(target_os = "linux")]
+fn on_battery() -> Option<bool> {
+    let supplies = std::fs::read_dir("/sys/class/power_supply").ok()?;
+    let mut battery = None;
+    for supply in supplies.flatten() {
+        let read = |name| std::fs::read_to_string(supply.path().join(name)).unwrap_or_default();
+        if read("type").trim() == "Battery" && read("scope").trim() != "Device" {
+            let discharging = read("status").trim() == "Discharging";
+            battery = Some(battery.unwrap_or(false) || discharging);
+        }
+    }
+    battery
+}
+
+/// `Now drawing from 'Battery Power'`, or `'AC Power'`.
+#[cfg(target_os = "macos")]
+fn on_battery() -> Option<bool> {
+    let output = std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let line = text.lines().find(|line| line.contains("drawing from"))?;
+    Some(line.contains("'Battery Power'"))
+}
+
+#[cfg(windows)]
+fn on_battery() -> Option<bool> {
+    windows::on_battery()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn on_battery() -> Option<bool> {
+    None
+}
+
+#[cfg(windows)]
+mod windows {
+    #[repr(C)]
+    #[derive(Default)]
+    // only the line status is read, the rest is there for the layout
+    #[allow(dead_code)]
+    struct SystemPowerStatus {
+        ac_line_status: u8,
+        battery_flag: u8,
+        battery_life_percent: u8,
+        system_status_flag: u8,
+        battery_life_time: u32,
+        battery_full_life_time: u32,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetSystemPowerStatus(status: *mut SystemPowerStatus) -> i32;
+    }
+
+    pub fn on_battery() -> Option<bool> {
+        let mut status = SystemPowerStatus::default();
+        // SAFETY: `status` is a properly laid out SYSTEM_POWER_STATUS that outlives the call
+        if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+            return None;
+        }
+        // 0 is offline, 1 online, 255 unknown
+        match status.ac_line_status {
+            0 => Some(true),
+            1 => Some(false),
+            _ => None,
+        }
+    }
+}
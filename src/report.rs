@@ -0,0 +1,289 @@
+//! `pom report`: the recorded history summed up over a period.
+//!
+//! Reports go through a Jinja template (minijinja), so a team can match
+//! its standup or retro format. Templates see:
+//!
+//! - `period`: `today`, `week`, `month` or `all`; `from`, `to`: dates
+//! - `focus`: seconds worked, pauses excluded; `breaks`: seconds of break
+//! - `pomodoros`: work sessions completed; `abandoned`: skipped or reset
+//! - `days`: `date`, `weekday`, `focus`, `pomodoros` for each day
+//! - `sessions`: `phase`, `date`, `start`, `end`, `planned`, `elapsed`,
+//!   `completed`, `pauses`, `tags`, `note`, `source` for each session,
+//!   oldest first
+//!
+//! plus a `duration` filter that turns seconds into `1h 05m`.
+//!
+//! `--email` mails the report instead of printing it; run
+//! `pom report --previous --email` from cron on Monday mornings for a
+//! weekly summary of the week before.
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, Utc};
+use clap::ValueEnum;
+use minijinja::{value::Serde, Environment};
+use serde::Serialize;
+use std::{collections::BTreeMap, fs, path::Path};
+
+use crate::{
+    app::{Phase, Session},
+    config::Config,
+    email,
+    error::{PomError, Result},
+    history::Store,
+};
+
+const DEFAULT_TEMPLATE: &str = "\
+{{ period | capitalize }}: {{ from }}{% if to != from %} to {{ to }}{% endif %}
+Focus {{ focus | duration }} in {{ pomodoros }} pomodoro{% if pomodoros != 1 %}s{% endif %}
+{%- if abandoned %}, {{ abandoned }} abandoned{% endif %}
+{%- if breaks %}, breaks {{ breaks | duration }}{% endif %}
+{% if days | length > 1 %}
+{% for day in days -%}
+{{ day.weekday }} {{ day.date }}  {{ day.focus | duration }}  {{ day.pomodoros }}
+{% endfor %}{% endif %}
+{%- set notes = sessions | selectattr(\"note\") | list %}{% if notes %}
+{% for session in notes -%}
+{{ session.date }} {{ session.start }}  {{ session.note }}
+{% endfor %}{% endif %}";
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Period {
+    Today,
+    /// since Monday
+    Week,
+    Month,
+    All,
+}
+
+impl Period {
+    pub fn name(self) -> &'static str {
+        match self {
+            Period::Today => "today",
+            Period::Week => "week",
+            Period::Month => "month",
+            Period::All => "all",
+        }
+    }
+
+    /// First and last day of the period that contains `today`, or of the
+    /// one before it.
+    pub fn range(self, today: NaiveDate, previous: bool) -> Option<(NaiveDate, NaiveDate)> {
+        let start = match self {
+            Period::Today => today,
+            Period::Week => today - Duration::days(today.weekday().num_days_from_monday() as i64),
+            Period::Month => today.with_day(1)?,
+            Period::All => return None,
+        };
+        if !previous {
+            return Some((start, today));
+        }
+
+        let end = start - Duration::days(1);
+        let start = match self {
+            Period::Today => end,
+            Period::Week => start - Duration::days(7),
+            Period::Month => end.with_day(1)?,
+            Period::All => return None,
+        };
+        Some((start, end))
+    }
+
+    /// The next longer period, wrapping around; `step(-1)` for shorter.
+    pub fn step(self, by: i32) -> Self {
+        let all = Period::value_variants();
+        let at = all.iter().position(|p| *p == self).unwrap_or(0) as i32;
+        all[(at + by).rem_euclid(all.len() as i32) as usize]
+    }
+}
+
+#[derive(Serialize)]
+struct Report {
+    period: &'static str,
+    from: NaiveDate,
+    to: NaiveDate,
+    focus: i64,
+    breaks: i64,
+    pomodoros: usize,
+    abandoned: usize,
+    days: Vec<Day>,
+    sessions: Vec<Entry>,
+}
+
+#[derive(Serialize)]
+struct Day {
+    date: NaiveDate,
+    /// `Mon`, `Tue`...
+    weekday: String,
+    focus: i64,
+    pomodoros: usize,
+}
+
+#[derive(Serialize)]
+struct Entry {
+    phase: &'static str,
+    date: NaiveDate,
+    /// `HH:MM`, local time
+    start: String,
+    end: String,
+    planned: i64,
+    elapsed: i64,
+    completed: bool,
+    pauses: u32,
+    tags: Vec<String>,
+    note: Option<String>,
+    source: Option<String>,
+}
+
+impl Report {
+    fn new(period: Period, previous: bool, sessions: &[Session], day_start: NaiveTime) -> Self {
+        let today = day(Utc::now(), day_start);
+        let local_date = |session: &Session| local_date(session, day_start);
+        let range = period.range(today, previous);
+        let sessions: Vec<&Session> = sessions
+            .iter()
+            .filter(|s| range.is_none_or(|(from, to)| (from..=to).contains(&local_date(s))))
+            .collect();
+        let (from, to) = range.unwrap_or_else(|| {
+            let first = sessions.iter().map(|s| local_date(s)).min();
+            (first.unwrap_or(today), today)
+        });
+
+        // every day of a bounded period, so idle days show up as zero
+        let mut days = BTreeMap::new();
+        if range.is_some() {
+            let mut date = from;
+            while date <= to {
+                days.insert(date, (0, 0));
+                date += Duration::days(1);
+            }
+        }
+
+        let mut report = Report {
+            period: period.name(),
+            from,
+            to,
+            focus: 0,
+            breaks: 0,
+            pomodoros: 0,
+            abandoned: 0,
+            days: Vec::new(),
+            sessions: Vec::new(),
+        };
+        for session in sessions {
+            let (focus, pomodoros) = days.entry(local_date(session)).or_insert((0, 0));
+            match (session.phase, session.completed) {
+                (Phase::Work, completed) => {
+                    report.focus += session.elapsed;
+                    *focus += session.elapsed;
+                    if completed {
+                        report.pomodoros += 1;
+                        *pomodoros += 1;
+                    } else {
+                        report.abandoned += 1;
+                    }
+                }
+                (Phase::ShortBreak | Phase::LongBreak, _) => report.breaks += session.elapsed,
+            }
+            report.sessions.push(Entry::new(session, day_start));
+        }
+
+        report.days = days
+            .into_iter()
+            .map(|(date, (focus, pomodoros))| Day {
+                date,
+                weekday: date.format("%a").to_string(),
+                focus,
+                pomodoros,
+            })
+            .collect();
+        report
+    }
+}
+
+impl Entry {
+    fn new(session: &Session, day_start: NaiveTime) -> Self {
+        let started = session.started.with_timezone(&Local);
+        let ended = session.ended.with_timezone(&Local);
+        Entry {
+            phase: session.phase.name(),
+            date: local_date(session, day_start),
+            start: started.format("%H:%M").to_string(),
+            end: ended.format("%H:%M").to_string(),
+            planned: session.planned,
+            elapsed: session.elapsed,
+            completed: session.completed,
+            pauses: session.pauses,
+            tags: session.tags.clone(),
+            note: session.note.clone(),
+            source: session.source.clone(),
+        }
+    }
+}
+
+/// Sessions count towards the day they started on.
+pub fn local_date(session: &Session, day_start: NaiveTime) -> NaiveDate {
+    day(session.started, day_start)
+}
+
+/// The day `at` belongs to, when days begin at `day_start` rather than
+/// midnight.
+pub fn day(at: DateTime<Utc>, day_start: NaiveTime) -> NaiveDate {
+    (at.with_timezone(&Local).naive_local() - (day_start - NaiveTime::MIN)).date()
+}
+
+/// Whether `tag` is `project` or one of its sub-projects: `work/api` is
+/// within `work`.
+pub fn within(tag: &str, project: &str) -> bool {
+    tag.strip_prefix(project)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// `work/backend/api` and the projects above it, top down: `work`,
+/// `work/backend`, `work/backend/api`.
+pub fn levels(tag: &str) -> impl Iterator<Item = &str> {
+    tag.match_indices('/')
+        .map(|(i, _)| &tag[..i])
+        .chain(std::iter::once(tag))
+}
+
+/// `1h 05m`, or just `25m` under an hour.
+pub fn duration(secs: i64) -> String {
+    let minutes = secs.max(0) / 60;
+    match minutes / 60 {
+        0 => format!("{}m", minutes),
+        hours => format!("{}h {:02}m", hours, minutes % 60),
+    }
+}
+
+/// `pom report`: render the period's report to stdout, or mail it.
+pub fn run(period: Period, previous: bool, template: Option<&Path>, email: bool) -> Result<()> {
+    let source = match template {
+        Some(path) => fs::read_to_string(path).map_err(|source| PomError::TemplateRead {
+            path: path.to_path_buf(),
+            source,
+        })?,
+        None => DEFAULT_TEMPLATE.to_string(),
+    };
+    let config = Config::load()?;
+    let sessions = Store::configured(&config.history)?.load()?;
+    let report = Report::new(period, previous, &sessions, config.day_starts_at);
+    let text = render(&source, &report)?;
+
+    if email {
+        let email = config.email.as_ref().ok_or(PomError::NoEmail)?;
+        let subject = render(&email.subject, &report)?;
+        return email::send(email, subject.trim(), text);
+    }
+
+    print!("{}", text);
+    if !text.ends_with('\n') {
+        println!();
+    }
+    Ok(())
+}
+
+fn render(source: &str, report: &Report) -> Result<String> {
+    let mut env = Environment::new();
+    env.add_filter("duration", duration);
+    env.render_str(source, Serde(report))
+        .map_err(PomError::Report)
+}
@@ -0,0 +1,164 @@
+//! `pom review`: how today went, what is overdue, and what to pick up
+//! tomorrow.
+
+use chrono::{Duration, Local, Utc};
+use std::io::{self, IsTerminal, Write};
+
+use crate::{
+    app::{Phase, Session},
+    config::Config,
+    error::Result,
+    goal::Streak,
+    history::Store,
+    journal,
+    report::{self, duration},
+    tasks,
+};
+
+pub fn run() -> Result<()> {
+    let config = Config::load_today(None)?;
+    let history = Store::configured(&config.history)?.load()?;
+    let today = report::day(Utc::now(), config.day_starts_at);
+    let streak = Streak::new(&history, today, config.goal.daily, &config);
+    let sessions: Vec<&Session> = history
+        .iter()
+        .filter(|s| report::local_date(s, config.day_starts_at) == today)
+        .collect();
+
+    println!("{}\n", today.format("%A, %B %-d"));
+    if sessions.is_empty() {
+        println!("  no sessions yet\n");
+    }
+    for session in &sessions {
+        println!("  {}", line(session));
+    }
+    if !sessions.is_empty() {
+        println!();
+    }
+
+    let work: Vec<&&Session> = sessions.iter().filter(|s| s.phase == Phase::Work).collect();
+    let focus: i64 = work.iter().map(|s| s.elapsed).sum();
+    let done = work.iter().filter(|s| s.completed).count() as u32;
+    let abandoned = work.len() as u32 - done;
+    let pauses: u32 = work.iter().map(|s| s.pauses).sum();
+
+    print!(
+        "Focus {} in {} pomodoro{}",
+        duration(focus),
+        done,
+        plural(done)
+    );
+    match config.goal.daily {
+        0 => println!(),
+        goal if done >= goal => println!(
+            ", daily goal of {} met, {} day{} in a row",
+            goal,
+            streak.days(),
+            plural(streak.days())
+        ),
+        goal => println!(", {} short of the daily goal of {}", goal - done, goal),
+    }
+    println!(
+        "Interruptions: {} abandoned, {} pause{}",
+        abandoned,
+        pauses,
+        plural(pauses)
+    );
+
+    // tasks past their due date first, then those due today
+    let tasks = tasks::load();
+    let due = tasks.due(today);
+    if !due.is_empty() {
+        println!("\nOverdue focus\n");
+    }
+    for task in due {
+        let days = (today - task.due.unwrap_or(today)).num_days() as u32;
+        let late = match days {
+            0 => "due today".to_string(),
+            days => format!("{} day{} overdue", days, plural(days)),
+        };
+        let worked = work
+            .iter()
+            .filter(|s| s.completed && s.tags.contains(&task.tag))
+            .count() as u32;
+        println!(
+            "  #{:<16}  {:<15}  {} pomodoro{} today",
+            task.tag,
+            late,
+            worked,
+            plural(worked)
+        );
+    }
+
+    // what's left goes to the top of tomorrow's journal
+    let Some(config) = &config.journal else {
+        return Ok(());
+    };
+    if !io::stdin().is_terminal() {
+        return Ok(());
+    }
+    println!("\nCarry anything over to tomorrow? One per line, empty line to finish.");
+    let mut items = Vec::new();
+    loop {
+        print!("> ");
+        io::stdout().flush()?;
+        let mut line = String::new();
+        if io::stdin().read_line(&mut line)? == 0 {
+            break;
+        }
+        match line.trim() {
+            "" => break,
+            item => items.push(item.to_string()),
+        }
+    }
+    if !items.is_empty() {
+        journal::carry_over(config, today + Duration::days(1), &items)?;
+        println!(
+            "{} item{} carried over",
+            items.len(),
+            plural(items.len() as u32)
+        );
+    }
+    Ok(())
+}
+
+/// `09:00–09:25  work         25m  done  #rust  the note`
+fn line(session: &Session) -> String {
+    let started = session.started.with_timezone(&Local);
+    let ended = session.ended.with_timezone(&Local);
+    let outcome = match (session.phase, session.completed) {
+        (_, true) => "done",
+        (Phase::Work, false) => "abandoned",
+        (_, false) => "skipped",
+    };
+    let mut line = format!(
+        "{}\u{2013}{}  {:<11}  {:>7}  {:<9}",
+        started.format("%H:%M"),
+        ended.format("%H:%M"),
+        session.phase.name(),
+        duration(session.elapsed),
+        outcome
+    );
+    if session.pauses > 0 {
+        line.push_str(&format!(
+            "  {} pause{}",
+            session.pauses,
+            plural(session.pauses)
+        ));
+    }
+    for tag in &session.tags {
+        line.push_str(&format!("  #{}", tag));
+    }
+    if let Some(note) = session.note.as_deref().and_then(|note| note.lines().next()) {
+        line.push_str(&format!("  {}", note));
+    }
+    line.trim_end().to_string()
+}
+
+fn plural(n: u32) -> &'static str {
+    if n == 1 {
+        ""
+    } else {
+        "s"
+    }
+}
@@ -0,0 +1,162 @@
+use mlua::{Function, Lua};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use crate::{
+    app::{Command, Event, Phase},
+    control::Handle,
+    notify::Notifier,
+};
+
+/// What a user script can hook into.
+enum Hook {
+    /// a phase began: its name and the work sessions completed so far
+    PhaseChange(Phase, u32),
+    /// a second passed in a running phase, with seconds left
+    Tick(Phase, i64),
+    /// a phase ran out rather than being skipped or reset
+    Complete(Phase),
+}
+
+/// A Lua script running on its own thread, so a slow hook never stalls
+/// the UI.
+///
+/// ```lua
+/// pom.on_complete(function(phase)
+///   if phase == "work" then pom.notify("pom", "stretch!") end
+/// end)
+/// pom.on_tick(function(phase, remaining)
+///   pom.set_status_text(remaining < 60 and "almost there" or nil)
+/// end)
+/// ```
+pub struct Script {
+    hooks: Sender<Hook>,
+    /// set by `pom.set_status_text`, shown in the status bar
+    status: Arc<Mutex<Option<String>>>,
+}
+
+impl Script {
+    pub fn start(path: PathBuf, handle: Handle, notifier: Notifier) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let status = Arc::new(Mutex::new(None));
+        let shared = Arc::clone(&status);
+
+        thread::spawn(move || {
+            let lua = Lua::new();
+            if let Err(err) = load(&lua, &path, handle, notifier, shared) {
+                log::warn!("script {}: {}", path.display(), err);
+                return;
+            }
+            run(&lua, rx);
+        });
+
+        Script { hooks: tx, status }
+    }
+
+    /// Pass timer events on to the script's hooks.
+    pub fn event(&self, event: &Event, round: u32) {
+        let hook = match *event {
+            Event::PhaseStarted { phase, .. } => Hook::PhaseChange(phase, round),
+            Event::PhaseEnded {
+                phase,
+                completed: true,
+                ..
+            } => Hook::Complete(phase),
+            _ => return,
+        };
+        let _ = self.hooks.send(hook);
+    }
+
+    pub fn tick(&self, phase: Phase, remaining: i64) {
+        let _ = self.hooks.send(Hook::Tick(phase, remaining));
+    }
+
+    pub fn status(&self) -> Option<String> {
+        self.status.lock().ok()?.clone()
+    }
+}
+
+/// Install the `pom` API table and run the script's top level, which
+/// registers its hooks.
+fn load(
+    lua: &Lua,
+    path: &Path,
+    handle: Handle,
+    notifier: Notifier,
+    status: Arc<Mutex<Option<String>>>,
+) -> mlua::Result<()> {
+    let pom = lua.create_table()?;
+
+    for hook in ["on_phase_change", "on_tick", "on_complete"] {
+        let register = lua.create_function(move |lua, callback: Function| {
+            lua.set_named_registry_value(hook, callback)
+        })?;
+        pom.set(hook, register)?;
+    }
+
+    pom.set(
+        "notify",
+        lua.create_function(move |_, (title, body): (String, String)| {
+            if let Err(err) = notifier.send(&title, &body) {
+                log::warn!("{}", err.report());
+            }
+            Ok(())
+        })?,
+    )?;
+    pom.set(
+        "set_status_text",
+        lua.create_function(move |_, text: Option<String>| {
+            if let Ok(mut status) = status.lock() {
+                *status = text;
+            }
+            Ok(())
+        })?,
+    )?;
+    pom.set(
+        "skip",
+        lua.create_function(move |_, ()| {
+            handle.send(Command::Skip);
+            Ok(())
+        })?,
+    )?;
+    pom.set(
+        "log",
+        lua.create_function(|_, message: String| {
+            log::info!("{}", message);
+            Ok(())
+        })?,
+    )?;
+    lua.globals().set("pom", pom)?;
+
+    let code = fs::read_to_string(path).map_err(mlua::Error::external)?;
+    lua.load(code)
+        .set_name(format!("@{}", path.display()))
+        .exec()
+}
+
+fn run(lua: &Lua, hooks: Receiver<Hook>) {
+    for hook in hooks {
+        let result = match hook {
+            Hook::PhaseChange(phase, round) => call(lua, "on_phase_change", (phase.name(), round)),
+            Hook::Tick(phase, remaining) => call(lua, "on_tick", (phase.name(), remaining)),
+            Hook::Complete(phase) => call(lua, "on_complete", phase.name()),
+        };
+        if let Err(err) = result {
+            log::warn!("script: {}", err);
+        }
+    }
+}
+
+fn call(lua: &Lua, hook: &str, args: impl mlua::IntoLuaMulti) -> mlua::Result<()> {
+    match lua.named_registry_value::<Option<Function>>(hook)? {
+        Some(callback) => callback.call(args),
+        None => Ok(()),
+    }
+}
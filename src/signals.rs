@@ -0,0 +1,82 @@
+use signal_hook::{consts::*, flag};
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// Flags set by process signal handlers and polled by the event loop.
+pub struct Signals {
+    terminate: Arc<AtomicBool>,
+    suspend: Arc<AtomicBool>,
+    resumed: Arc<AtomicBool>,
+}
+
+impl Signals {
+    pub fn register() -> io::Result<Self> {
+        let terminate = Arc::new(AtomicBool::new(false));
+
+        #[cfg(unix)]
+        let sigs = [SIGTERM, SIGINT, SIGHUP];
+        #[cfg(not(unix))]
+        let sigs = [SIGTERM, SIGINT];
+
+        for sig in sigs {
+            // a second signal while we are still shutting down kills us outright
+            flag::register_conditional_shutdown(sig, 1, Arc::clone(&terminate))?;
+            flag::register(sig, Arc::clone(&terminate))?;
+        }
+
+        // job control: SIGTSTP asks us to suspend, SIGCONT tells us we are back
+        let suspend = Arc::new(AtomicBool::new(false));
+        let resumed = Arc::new(AtomicBool::new(false));
+        #[cfg(unix)]
+        {
+            flag::register(SIGTSTP, Arc::clone(&suspend))?;
+            flag::register(SIGCONT, Arc::clone(&resumed))?;
+        }
+
+        Ok(Signals {
+            terminate,
+            suspend,
+            resumed,
+        })
+    }
+
+    /// Flags nothing ever sets, for a loop with no process of its own.
+    pub fn none() -> Self {
+        Signals {
+            terminate: Arc::new(AtomicBool::new(false)),
+            suspend: Arc::new(AtomicBool::new(false)),
+            resumed: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn should_terminate(&self) -> bool {
+        self.terminate.load(Ordering::Relaxed)
+    }
+
+    pub fn take_suspend(&self) -> bool {
+        self.suspend.swap(false, Ordering::Relaxed)
+    }
+
+    pub fn take_resumed(&self) -> bool {
+        self.resumed.swap(false, Ordering::Relaxed)
+    }
+}
+
+/// Stop the process the way the default SIGTSTP handler would.
+///
+/// The caller is expected to have restored the terminal already; execution
+/// continues here once the shell sends SIGCONT.
+#[cfg(unix)]
+pub fn stop() -> io::Result<()> {
+    signal_hook::low_level::emulate_default_handler(SIGTSTP)
+}
+
+#[cfg(not(unix))]
+pub fn stop() -> io::Result<()> {
+    Ok(())
+}
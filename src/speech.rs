@@ -0,0 +1,80 @@
+//! Spoken announcements, for when the screen is out of sight.
+
+use std::{process::Command, thread};
+
+use crate::{
+    error::{PomError, Result},
+    platform::find_executable,
+};
+
+/// `1 minute`, `5 minutes`.
+pub fn minutes(minutes: i64) -> String {
+    let plural = if minutes == 1 { "" } else { "s" };
+    format!("{} minute{}", minutes, plural)
+}
+
+/// The text-to-speech program on this machine.
+#[derive(Clone, Copy)]
+pub enum Speaker {
+    /// eSpeak NG, or the original eSpeak (Linux/BSD)
+    Espeak(&'static str),
+    /// `say` (macOS)
+    Say,
+    /// System.Speech through PowerShell (Windows)
+    Sapi,
+}
+
+impl Speaker {
+    pub fn detect() -> Option<Self> {
+        let candidates: &[(&str, Speaker)] = if cfg!(windows) {
+            &[("powershell", Speaker::Sapi)]
+        } else if cfg!(target_os = "macos") {
+            &[("say", Speaker::Say)]
+        } else {
+            &[
+                ("espeak-ng", Speaker::Espeak("espeak-ng")),
+                ("espeak", Speaker::Espeak("espeak")),
+            ]
+        };
+
+        candidates
+            .iter()
+            .find(|(exe, _)| find_executable(exe).is_some())
+            .map(|(_, speaker)| *speaker)
+    }
+
+    /// Fire and forget, like notifications: the UI never waits for the
+    /// sentence to finish.
+    pub fn say(&self, text: &str) -> Result<()> {
+        let mut command = match self {
+            Speaker::Espeak(exe) => {
+                let mut c = Command::new(exe);
+                c.arg(text);
+                c
+            }
+            Speaker::Say => {
+                let mut c = Command::new("say");
+                c.arg(text);
+                c
+            }
+            Speaker::Sapi => {
+                let mut c = Command::new("powershell");
+                c.args([
+                    "-NoProfile",
+                    "-NonInteractive",
+                    "-Command",
+                    &format!(
+                        "Add-Type -AssemblyName System.Speech; \
+                         (New-Object System.Speech.Synthesis.SpeechSynthesizer).Speak('{}')",
+                        text.replace('\'', "''")
+                    ),
+                ]);
+                c
+            }
+        };
+
+        let mut child = command.spawn().map_err(PomError::Speech)?;
+        thread::spawn(move || child.wait());
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::PathBuf, process};
+
+use crate::{
+    error::{PomError, Result},
+    platform, template,
+};
+
+/// A state file this old is only trusted if its writer is still alive.
+const STALE_AFTER_SECS: i64 = 5;
+
+/// What the running app writes for `pom status` and other readers.
+#[derive(Serialize, Deserialize)]
+pub struct StateFile {
+    pub pid: u32,
+    pub updated_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub context: template::Context,
+}
+
+pub fn path() -> Option<PathBuf> {
+    platform::project_dirs().map(|dirs| dirs.data_local_dir().join("state.json"))
+}
+
+/// Replace the state file atomically, so readers never see half of it.
+pub fn write(context: template::Context) -> io::Result<()> {
+    let Some(path) = path() else {
+        return Ok(());
+    };
+    let state = StateFile {
+        pid: process::id(),
+        updated_at: Utc::now(),
+        context,
+    };
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_vec(&state)?)?;
+    fs::rename(tmp, path)
+}
+
+/// Called on a clean exit so nobody reads a timer that isn't there.
+pub fn remove() {
+    if let Some(path) = path() {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// What there is to say when pom isn't running.
+pub fn stopped() -> template::Context {
+    template::Context {
+        state: "stopped".to_string(),
+        phase: String::new(),
+        remaining: 0,
+        round: 0,
+        task: String::new(),
+        count_today: 0,
+    }
+}
+
+/// The running app's state, brought up to date, or `stopped`.
+pub fn read() -> Result<template::Context> {
+    let Some(path) = path() else {
+        return Ok(stopped());
+    };
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(stopped()),
+        Err(source) => return Err(PomError::StateRead { path, source }),
+    };
+    let state: StateFile = serde_json::from_str(&text).map_err(|source| PomError::StateRead {
+        path,
+        source: source.into(),
+    })?;
+
+    let age = (Utc::now() - state.updated_at).num_seconds().max(0);
+    if age > STALE_AFTER_SECS && !alive(state.pid) {
+        return Ok(stopped());
+    }
+
+    let mut context = state.context;
+    if context.state == "running" {
+        context.remaining = (context.remaining - age).max(0);
+    }
+    Ok(context)
+}
+
+/// `pom status`: print the state as JSON, or through a template.
+pub fn print_status(format: Option<&str>) -> Result<()> {
+    let context = read()?;
+    match format {
+        Some(format) => println!("{}", template::render(format, &context)),
+        None => println!(
+            "{}",
+            serde_json::to_string(&context).map_err(io::Error::from)?
+        ),
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn alive(pid: u32) -> bool {
+    // SAFETY: signal 0 only checks that the process exists and can be signalled
+    unsafe { libc::kill(pid as libc::pid_t, 0) == 0 }
+}
+
+#[cfg(not(unix))]
+fn alive(_pid: u32) -> bool {
+    false
+}
@@ -0,0 +1,726 @@
+//! The stats screen: focus time over a period, with two tags side by side,
+//! every project and sub-project in a tree, or as bars per day, week or
+//! month.
+
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveTime, Utc};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{BarChart, Block, Paragraph},
+    Frame,
+};
+
+use crate::{
+    app::{Phase, Session},
+    config::{Config, Hours},
+    image::Content,
+    link::Link,
+    report::{self, Period},
+};
+
+const LEFT: Color = Color::Cyan;
+const RIGHT: Color = Color::Magenta;
+
+/// Width of the day column, `Mon 10-12`.
+const LABEL: usize = 10;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    Compare,
+    Tree,
+    Bars,
+}
+
+/// What each bar of the chart adds up.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Group {
+    Day,
+    Week,
+    Month,
+}
+
+impl Group {
+    pub fn name(self) -> &'static str {
+        match self {
+            Group::Day => "day",
+            Group::Week => "week",
+            Group::Month => "month",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Group::Day => Group::Week,
+            Group::Week => Group::Month,
+            Group::Month => Group::Day,
+        }
+    }
+
+    /// The first day of the day, week or month `date` is in.
+    fn start(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Group::Day => date,
+            Group::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            Group::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    /// `10-14` or `14`, `W42`, `Oct`, to fit under a bar `width` wide.
+    fn label(self, start: NaiveDate, width: u16) -> String {
+        let format = match self {
+            Group::Day if width < 5 => "%d",
+            Group::Day => "%m-%d",
+            Group::Week => "W%V",
+            Group::Month => "%b",
+        };
+        start.format(format).to_string()
+    }
+}
+
+pub struct Stats {
+    sessions: Vec<Session>,
+    pub period: Period,
+    /// every tag in the history and the projects above them, most focused
+    /// first
+    tags: Vec<String>,
+    /// the two tags being compared
+    left: Option<String>,
+    right: Option<String>,
+    pub view: View,
+    /// how the bars add up the period
+    pub group: Group,
+    /// when one day ends and the next begins
+    day_start: NaiveTime,
+}
+
+impl Stats {
+    pub fn new(sessions: Vec<Session>, day_start: NaiveTime) -> Self {
+        let mut stats = Stats {
+            sessions,
+            period: Period::Week,
+            tags: Vec::new(),
+            left: None,
+            right: None,
+            view: View::Compare,
+            group: Group::Day,
+            day_start,
+        };
+        stats.refresh_tags();
+        stats.left = stats.tags.first().cloned();
+        stats.right = stats.tags.get(1).cloned();
+        stats
+    }
+
+    /// A session that ended while the screen is open.
+    pub fn add(&mut self, session: Session) {
+        self.sessions.push(session);
+        self.refresh_tags();
+        if self.left.is_none() {
+            self.left = self.tags.first().cloned();
+        }
+    }
+
+    fn refresh_tags(&mut self) {
+        let mut focus: HashMap<&str, i64> = HashMap::new();
+        for session in self.sessions.iter().filter(|s| s.phase == Phase::Work) {
+            for tag in projects(session) {
+                *focus.entry(tag).or_default() += session.elapsed;
+            }
+        }
+        let mut tags: Vec<(&str, i64)> = focus.into_iter().collect();
+        tags.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        self.tags = tags.into_iter().map(|(tag, _)| tag.to_string()).collect();
+    }
+
+    /// Two tags, the projects, the bars, and around again.
+    pub fn next_view(&mut self) {
+        self.view = match self.view {
+            View::Compare => View::Tree,
+            View::Tree => View::Bars,
+            View::Bars => View::Compare,
+        };
+    }
+
+    /// Put the next tag in the left column, or with `right` the right one.
+    pub fn cycle(&mut self, right: bool) {
+        let side = if right {
+            &mut self.right
+        } else {
+            &mut self.left
+        };
+        let at = side
+            .as_ref()
+            .and_then(|tag| self.tags.iter().position(|t| t == tag));
+        *side = match at {
+            Some(at) => self.tags.get((at + 1) % self.tags.len()).cloned(),
+            None => self.tags.first().cloned(),
+        };
+    }
+
+    /// `Today: 6 pomodoros, 2h 30m focus`, for pasting elsewhere.
+    pub fn summary(&self) -> String {
+        let range = self.period.range(self.today(), false);
+        let work = self.sessions.iter().filter(|session| {
+            let date = report::local_date(session, self.day_start);
+            session.phase == Phase::Work
+                && range.is_none_or(|(from, to)| (from..=to).contains(&date))
+        });
+        let (pomodoros, focus) = work.fold((0, 0), |(count, focus), session| {
+            (count + session.completed as u32, focus + session.elapsed)
+        });
+        let when = match self.period {
+            Period::Today => "Today",
+            Period::Week => "This week",
+            Period::Month => "This month",
+            Period::All => "All time",
+        };
+        let plural = if pomodoros == 1 { "" } else { "s" };
+        format!(
+            "{}: {} pomodoro{}, {} focus",
+            when,
+            pomodoros,
+            plural,
+            report::duration(focus)
+        )
+    }
+
+    fn today(&self) -> NaiveDate {
+        report::day(Utc::now(), self.day_start)
+    }
+
+    /// Focus seconds within the working hours and outside them, going by
+    /// when each session started.
+    fn by_hours(&self, hours: &Hours, range: Option<(NaiveDate, NaiveDate)>) -> (i64, i64) {
+        let mut split = (0, 0);
+        for session in self.sessions.iter().filter(|s| s.phase == Phase::Work) {
+            let date = report::local_date(session, self.day_start);
+            if range.is_some_and(|(from, to)| !(from..=to).contains(&date)) {
+                continue;
+            }
+            if hours.contain(session.started.with_timezone(&Local)) {
+                split.0 += session.elapsed;
+            } else {
+                split.1 += session.elapsed;
+            }
+        }
+        split
+    }
+
+    /// `7-day average 1h 05m, 30-day 40m; best Tue 10-13 1h 30m, worst
+    /// ...`: the averages up to today, idle days included, and the best
+    /// and worst days of the period with a minute of focus or more.
+    fn trend(&self, range: Option<(NaiveDate, NaiveDate)>) -> Option<String> {
+        let mut days: BTreeMap<NaiveDate, i64> = BTreeMap::new();
+        for session in self.sessions.iter().filter(|s| s.phase == Phase::Work) {
+            *days
+                .entry(report::local_date(session, self.day_start))
+                .or_default() += session.elapsed;
+        }
+        if days.is_empty() {
+            return None;
+        }
+        let today = self.today();
+        let average = |n: i64| {
+            let from = today - Duration::days(n - 1);
+            days.range(from..=today).map(|(_, secs)| secs).sum::<i64>() / n
+        };
+        let mut trend = format!(
+            "7-day average {}, 30-day {}",
+            report::duration(average(7)),
+            report::duration(average(30))
+        );
+
+        let worked = days
+            .iter()
+            .filter(|(date, secs)| {
+                **secs >= 60 && range.is_none_or(|(from, to)| (from..=to).contains(*date))
+            })
+            .map(|(date, secs)| (*date, *secs));
+        // the earlier day of a tie
+        let best = worked.clone().rev().max_by_key(|(_, secs)| *secs);
+        let worst = worked.min_by_key(|(_, secs)| *secs);
+        let day = |(date, secs): (NaiveDate, i64)| {
+            format!("{} {}", date.format("%a %m-%d"), report::duration(secs))
+        };
+        match (best, worst) {
+            (Some(best), Some(worst)) if best != worst => {
+                trend += &format!("; best {}, worst {}", day(best), day(worst));
+            }
+            (Some(best), _) => trend += &format!("; best {}", day(best)),
+            _ => {}
+        }
+        Some(trend)
+    }
+
+    /// Focus seconds per day for `tag`, within the period.
+    fn by_day(
+        &self,
+        tag: Option<&str>,
+        range: Option<(NaiveDate, NaiveDate)>,
+    ) -> BTreeMap<NaiveDate, i64> {
+        let mut days = BTreeMap::new();
+        let Some(tag) = tag else {
+            return days;
+        };
+        for session in &self.sessions {
+            let date = report::local_date(session, self.day_start);
+            if session.phase != Phase::Work
+                || !session.tags.iter().any(|t| report::within(t, tag))
+                || range.is_some_and(|(from, to)| !(from..=to).contains(&date))
+            {
+                continue;
+            }
+            *days.entry(date).or_insert(0) += session.elapsed;
+        }
+        days
+    }
+}
+
+/// With `images`, the days are left for a chart image, returned with where
+/// it goes; tags that point somewhere go in `links`.
+pub fn draw<B: Backend>(
+    f: &mut Frame<B>,
+    stats: &Stats,
+    config: &Config,
+    area: Rect,
+    images: bool,
+    links: Option<&mut Vec<Link>>,
+) -> Option<(Rect, Content)> {
+    let hours = &config.hours;
+    let range = stats.period.range(stats.today(), false);
+    let title = match range {
+        Some((from, to)) if from == to => format!("Stats: {}, {}", stats.period.name(), to),
+        Some((from, to)) => format!("Stats: {}, {} to {}", stats.period.name(), from, to),
+        None => "Stats: all time".to_string(),
+    };
+    let title = match stats.view {
+        View::Bars => format!("{}, minutes by {}", title, stats.group.name()),
+        _ => title,
+    };
+    let title = if hours.shifts.is_empty() {
+        title
+    } else {
+        let (within, outside) = stats.by_hours(hours, range);
+        format!(
+            "{} \u{2014} {} in working hours, {} outside",
+            title,
+            report::duration(within),
+            report::duration(outside)
+        )
+    };
+    let block = Block::default().title(title);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let mut top = stats
+        .trend(range)
+        .map(Spans::from)
+        .into_iter()
+        .collect::<Vec<_>>();
+    top.extend(budgets(stats, &config.budgets, inner.width as usize));
+    let inner = if top.is_empty() {
+        inner
+    } else {
+        // and a blank line under them
+        let rows = (top.len() as u16 + 1).min(inner.height / 2);
+        f.render_widget(
+            Paragraph::new(top),
+            Rect {
+                height: rows,
+                ..inner
+            },
+        );
+        Rect::new(inner.x, inner.y + rows, inner.width, inner.height - rows)
+    };
+
+    match stats.view {
+        View::Tree => {
+            let tree = tree(stats, range, inner.width as usize);
+            f.render_widget(Paragraph::new(tree), inner);
+            return None;
+        }
+        View::Bars => {
+            bars(f, stats, range, inner);
+            return None;
+        }
+        View::Compare => {}
+    }
+
+    if stats.tags.is_empty() {
+        let hint = "No tagged sessions yet. Start pom with --tag <name> to compare tags here.";
+        f.render_widget(Paragraph::new(hint), inner);
+        return None;
+    }
+
+    let left = stats.by_day(stats.left.as_deref(), range);
+    let right = stats.by_day(stats.right.as_deref(), range);
+    let mut days: Vec<NaiveDate> = match range {
+        // every day of a bounded period, so idle days show up as gaps
+        Some((from, to)) => from.iter_days().take_while(|d| *d <= to).collect(),
+        None => left.keys().chain(right.keys()).copied().collect(),
+    };
+    days.sort();
+    days.dedup();
+
+    let half = (inner.width as usize).saturating_sub(LABEL + 3) / 2;
+    if let Some(links) = links {
+        let x = inner.x + LABEL as u16;
+        let sides = [
+            (&stats.left, x, LEFT),
+            (&stats.right, x + half as u16 + 3, RIGHT),
+        ];
+        for (tag, x, color) in sides {
+            let Some(tag) = tag else {
+                continue;
+            };
+            if let Some(url) = config.links.url(tag) {
+                links.push(Link {
+                    x,
+                    y: inner.y,
+                    text: truncate(tag, half),
+                    color,
+                    url,
+                });
+            }
+        }
+    }
+    if images && !days.is_empty() && inner.height >= 8 {
+        return Some(chart(f, stats, &days, &left, &right, inner));
+    }
+    // the most recent days when they don't all fit
+    let rows = (inner.height as usize).saturating_sub(3);
+    let days = &days[days.len().saturating_sub(rows)..];
+
+    let mut lines = header(stats, &left, &right, half);
+    let most = days
+        .iter()
+        .flat_map(|d| [left.get(d), right.get(d)])
+        .flatten()
+        .copied()
+        .max();
+    for day in days {
+        let label = day.format("%a %m-%d").to_string();
+        let (l, r) = (left.get(day).copied(), right.get(day).copied());
+        lines.push(row(&label, l.unwrap_or(0), r.unwrap_or(0), most, half));
+    }
+    f.render_widget(Paragraph::new(lines), inner);
+    None
+}
+
+/// Every tag of `session` and the projects above them, each once, so a
+/// session tagged `work/api` and `work/web` counts once towards `work`.
+fn projects(session: &Session) -> BTreeSet<&str> {
+    session
+        .tags
+        .iter()
+        .flat_map(|tag| report::levels(tag))
+        .collect()
+}
+
+/// The projects and their focus within the period, each under the one
+/// above it, with bars scaled to the most focused.
+fn tree(stats: &Stats, range: Option<(NaiveDate, NaiveDate)>, width: usize) -> Vec<Spans<'static>> {
+    // by path, so sub-projects follow their parent
+    let mut focus: BTreeMap<Vec<&str>, i64> = BTreeMap::new();
+    for session in stats.sessions.iter().filter(|s| s.phase == Phase::Work) {
+        let date = report::local_date(session, stats.day_start);
+        if range.is_some_and(|(from, to)| !(from..=to).contains(&date)) {
+            continue;
+        }
+        for project in projects(session) {
+            *focus.entry(project.split('/').collect()).or_default() += session.elapsed;
+        }
+    }
+    let nodes: Vec<(Vec<&str>, i64)> = focus.into_iter().collect();
+    // whether nothing after `i` shares its parent
+    let last = |i: usize| {
+        let (path, _) = &nodes[i];
+        let parent = &path[..path.len() - 1];
+        !nodes[i + 1..]
+            .iter()
+            .take_while(|(other, _)| other.starts_with(parent))
+            .any(|(other, _)| other.len() == path.len())
+    };
+
+    let mut labels = Vec::new();
+    // for each level above, whether it was the last, to draw the lines down
+    let mut open: Vec<bool> = Vec::new();
+    for (i, (path, _)) in nodes.iter().enumerate() {
+        let depth = path.len() - 1;
+        open.truncate(depth);
+        let mut label: String = open
+            .iter()
+            .skip(1)
+            .map(|&open| if open { "\u{2502}  " } else { "   " })
+            .collect();
+        if depth > 0 {
+            label += if last(i) {
+                "\u{2514}\u{2500} "
+            } else {
+                "\u{251c}\u{2500} "
+            };
+        }
+        label += path[depth];
+        labels.push(label);
+        open.push(!last(i));
+    }
+
+    let label_width = labels
+        .iter()
+        .map(|label| label.chars().count())
+        .max()
+        .unwrap_or(0)
+        .min(width / 2);
+    let most = nodes
+        .iter()
+        .map(|(_, secs)| *secs)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let bar_width = width.saturating_sub(label_width + 10);
+    let mut lines: Vec<Spans> = nodes
+        .iter()
+        .zip(labels)
+        .map(|((path, secs), label)| {
+            let filled = (*secs as usize * bar_width)
+                .div_ceil(most as usize)
+                .min(bar_width);
+            let color = if path.len() == 1 { LEFT } else { RIGHT };
+            Spans::from(vec![
+                Span::raw(format!("{:<label_width$}  ", truncate(&label, label_width))),
+                Span::styled("\u{2588}".repeat(filled), Style::default().fg(color)),
+                Span::raw(format!(" {}", report::duration(*secs))),
+            ])
+        })
+        .collect();
+    if lines.is_empty() {
+        lines.push(Spans::from(Span::styled(
+            "Nothing tagged in this period.",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    lines
+}
+
+/// This week's focus against each budget, red once it is over.
+fn budgets(stats: &Stats, budgets: &HashMap<String, u32>, width: usize) -> Vec<Spans<'static>> {
+    let week = Period::Week.range(stats.today(), false);
+    let mut budgets: Vec<(&String, &u32)> = budgets.iter().collect();
+    budgets.sort();
+    let name_width = budgets
+        .iter()
+        .map(|(project, _)| project.chars().count())
+        .max()
+        .unwrap_or(0)
+        .min(width / 3);
+    budgets
+        .into_iter()
+        .map(|(project, minutes)| {
+            let focus: i64 = stats
+                .sessions
+                .iter()
+                .filter(|session| {
+                    let date = report::local_date(session, stats.day_start);
+                    session.phase == Phase::Work
+                        && week.is_none_or(|(from, to)| (from..=to).contains(&date))
+                        && session.tags.iter().any(|tag| report::within(tag, project))
+                })
+                .map(|session| session.elapsed)
+                .sum();
+            let budget = i64::from(*minutes) * 60;
+            let time = format!(
+                " {} of {}",
+                report::duration(focus),
+                report::duration(budget)
+            );
+            let bar_width = width.saturating_sub(name_width + 2 + time.len());
+            let filled = (focus.max(0) as usize * bar_width)
+                .div_ceil(budget.max(1) as usize)
+                .min(bar_width);
+            let color = if focus > budget { Color::Red } else { LEFT };
+            Spans::from(vec![
+                Span::raw(format!("{:<name_width$}  ", truncate(project, name_width))),
+                Span::styled("\u{2588}".repeat(filled), Style::default().fg(color)),
+                Span::styled(
+                    "\u{2500}".repeat(bar_width - filled),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(time, Style::default().fg(color)),
+            ])
+        })
+        .collect()
+}
+
+/// Focus minutes of every day, week or month of the period, the most
+/// recent when they don't all fit.
+fn bars<B: Backend>(
+    f: &mut Frame<B>,
+    stats: &Stats,
+    range: Option<(NaiveDate, NaiveDate)>,
+    area: Rect,
+) {
+    let mut focus: BTreeMap<NaiveDate, i64> = BTreeMap::new();
+    // every one of a bounded period, so idle ones show up as gaps
+    if let Some((from, to)) = range {
+        for date in from.iter_days().take_while(|d| *d <= to) {
+            focus.insert(stats.group.start(date), 0);
+        }
+    }
+    for session in stats.sessions.iter().filter(|s| s.phase == Phase::Work) {
+        let date = report::local_date(session, stats.day_start);
+        if range.is_none_or(|(from, to)| (from..=to).contains(&date)) {
+            *focus.entry(stats.group.start(date)).or_default() += session.elapsed;
+        }
+    }
+    if focus.values().all(|secs| *secs == 0) {
+        let empty = Span::styled(
+            "Nothing recorded in this period.",
+            Style::default().fg(Color::DarkGray),
+        );
+        f.render_widget(Paragraph::new(Spans::from(empty)), area);
+        return;
+    }
+
+    // at least three cells a bar, for the minutes, and one between
+    let fit = (area.width as usize + 1) / 4;
+    let shown: Vec<(NaiveDate, i64)> = focus.into_iter().collect();
+    let shown = &shown[shown.len().saturating_sub(fit)..];
+    let width = ((area.width as usize + 1) / shown.len().max(1) - 1).min(9) as u16;
+    let labels: Vec<String> = shown
+        .iter()
+        .map(|(start, _)| stats.group.label(*start, width))
+        .collect();
+    let data: Vec<(&str, u64)> = labels
+        .iter()
+        .zip(shown)
+        .map(|(label, (_, secs))| (label.as_str(), (secs / 60) as u64))
+        .collect();
+    let chart = BarChart::default()
+        .data(&data)
+        .bar_width(width)
+        .bar_gap(1)
+        .bar_style(Style::default().fg(LEFT))
+        .value_style(Style::default().fg(Color::Black).bg(LEFT))
+        .label_style(Style::default().fg(Color::DarkGray));
+    f.render_widget(chart, area);
+}
+
+/// The tag names and their totals, then a blank line.
+fn header(
+    stats: &Stats,
+    left: &BTreeMap<NaiveDate, i64>,
+    right: &BTreeMap<NaiveDate, i64>,
+    half: usize,
+) -> Vec<Spans<'static>> {
+    let name = |tag: &Option<String>, color| {
+        let tag = tag.as_deref().unwrap_or("-");
+        Span::styled(
+            format!("{:<half$}", truncate(tag, half)),
+            Style::default().fg(color),
+        )
+    };
+    vec![
+        Spans::from(vec![
+            Span::raw(" ".repeat(LABEL)),
+            name(&stats.left, LEFT),
+            Span::raw(" \u{2502} "),
+            name(&stats.right, RIGHT),
+        ]),
+        row(
+            "total",
+            left.values().sum(),
+            right.values().sum(),
+            None,
+            half,
+        ),
+        Spans::default(),
+    ]
+}
+
+/// The header in text over room for a chart of `days`, labelled below.
+fn chart<B: Backend>(
+    f: &mut Frame<B>,
+    stats: &Stats,
+    days: &[NaiveDate],
+    left: &BTreeMap<NaiveDate, i64>,
+    right: &BTreeMap<NaiveDate, i64>,
+    area: Rect,
+) -> (Rect, Content) {
+    let half = (area.width as usize).saturating_sub(LABEL + 3) / 2;
+    let mut lines = header(stats, left, right, half);
+    // columns at least three cells wide, the most recent days when they
+    // don't all fit
+    let days = &days[days.len().saturating_sub(area.width as usize / 3)..];
+    let focus: Vec<(i64, i64)> = days
+        .iter()
+        .map(|d| {
+            let secs = |side: &BTreeMap<NaiveDate, i64>| side.get(d).copied().unwrap_or(0);
+            (secs(left), secs(right))
+        })
+        .collect();
+    let most = focus.iter().map(|&(l, r)| l.max(r)).max().unwrap_or(0);
+    lines[2] = Spans::from(Span::styled(
+        format!("{:LABEL$}tallest {}", "", report::duration(most)),
+        Style::default().fg(Color::DarkGray),
+    ));
+
+    let slot = area.width as usize / days.len();
+    let mut axis = String::new();
+    for (i, day) in days.iter().enumerate() {
+        let label = if slot > 3 {
+            day.format("%a")
+        } else {
+            day.format("%d")
+        };
+        axis += &format!("{:<slot$}", label.to_string());
+        axis.truncate((i + 1) * slot);
+    }
+    let rows = lines.len() as u16;
+    let plot = Rect::new(
+        area.x,
+        area.y + rows,
+        (slot * days.len()) as u16,
+        area.height - rows - 1,
+    );
+    lines.extend(vec![Spans::default(); plot.height as usize]);
+    lines.push(Spans::from(Span::styled(
+        axis,
+        Style::default().fg(Color::DarkGray),
+    )));
+    f.render_widget(Paragraph::new(lines), area);
+    (plot, Content::Chart(focus))
+}
+
+/// One line of the comparison: a label, then each tag's bar and time.
+///
+/// Bars are scaled to `most`, or to the larger of the two when `None`.
+fn row(label: &str, left: i64, right: i64, most: Option<i64>, half: usize) -> Spans<'static> {
+    let most = most.unwrap_or(left.max(right)).max(1);
+    let bar = |secs: i64, color| {
+        let time = report::duration(secs);
+        let width = half.saturating_sub(time.len() + 1);
+        let filled = (secs.max(0) as usize * width)
+            .div_ceil(most as usize)
+            .min(width);
+        vec![
+            Span::styled("\u{2588}".repeat(filled), Style::default().fg(color)),
+            Span::raw(format!(
+                " {:<pad$}",
+                time,
+                pad = half.saturating_sub(filled + 1)
+            )),
+        ]
+    };
+    let mut spans = vec![Span::raw(format!("{:<LABEL$}", label))];
+    spans.extend(bar(left, LEFT));
+    spans.push(Span::raw(" \u{2502} "));
+    spans.extend(bar(right, RIGHT));
+    Spans::from(spans)
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
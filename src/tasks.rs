@@ -0,0 +1,665 @@
+//! The task list: the tags worked on, with a say over them beyond what
+//! sessions carry. For now, the order they're taken up in, how many work
+//! sessions each gets on the day they were planned for, when they're due
+//! and the days routine ones come back on, kept in `tasks.json`.
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt, fs, io, path::PathBuf};
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Paragraph},
+    Frame,
+};
+
+use crate::{
+    app::{Phase, Session},
+    config::Days,
+    history::Tally,
+    platform,
+};
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct Tasks {
+    /// the day `planned` is for
+    pub day: Option<NaiveDate>,
+    pub tasks: Vec<Task>,
+    /// the last day routine tasks were brought back on
+    pub recurred: Option<NaiveDate>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Task {
+    pub tag: String,
+    /// work sessions set aside for it on the planned day
+    #[serde(default)]
+    pub planned: u32,
+    /// done with, off the list but still in the history and reports
+    #[serde(default)]
+    pub archived: bool,
+    /// the days it comes back on once archived, for routine work
+    #[serde(default)]
+    pub repeat: Option<Repeat>,
+    #[serde(default)]
+    pub due: Option<NaiveDate>,
+}
+
+impl Task {
+    /// Due before `today`, and still to do.
+    pub fn overdue(&self, today: NaiveDate) -> bool {
+        !self.archived && self.due.is_some_and(|due| due < today)
+    }
+}
+
+impl Tasks {
+    /// What is planned for `today`, nothing if the plan is for another day.
+    pub fn planned(&self, today: NaiveDate) -> impl Iterator<Item = &Task> {
+        self.tasks
+            .iter()
+            .filter(move |task| self.day == Some(today) && task.planned > 0 && !task.archived)
+    }
+
+    fn get(&self, tag: &str, today: NaiveDate) -> Option<&Task> {
+        self.planned(today).find(|task| task.tag == tag)
+    }
+
+    /// The tasks still to do that are due by `today`, the longest overdue
+    /// first.
+    pub fn due(&self, today: NaiveDate) -> Vec<&Task> {
+        let mut due: Vec<&Task> = self
+            .tasks
+            .iter()
+            .filter(|task| !task.archived && task.due.is_some_and(|due| due <= today))
+            .collect();
+        due.sort_by_key(|task| task.due);
+        due
+    }
+
+    /// Routine tasks that were archived back on the list, the first time
+    /// this is called since a day they repeat on, so a day pom wasn't
+    /// opened on still counts. True when the list is to be kept.
+    pub fn recur(&mut self, today: NaiveDate) -> bool {
+        if self.recurred == Some(today) {
+            return false;
+        }
+        // every rule is weekly, so a week back covers any gap
+        let since = match self.recurred {
+            Some(last) if last < today => {
+                (last + Duration::days(1)).max(today - Duration::days(6))
+            }
+            _ => today,
+        };
+        self.recurred = Some(today);
+        let days: Vec<NaiveDate> = since.iter_days().take_while(|day| *day <= today).collect();
+        for task in &mut self.tasks {
+            match &task.repeat {
+                Some(repeat) if task.archived && days.iter().any(|day| repeat.on(*day)) => {
+                    log::info!("#{} is back on the list, it repeats {}", task.tag, repeat);
+                    task.archived = false;
+                }
+                _ => {}
+            }
+        }
+        true
+    }
+}
+
+/// When a task repeats: every day, or on the days given. Written the way
+/// it's typed, `every weekday`, `weekly on monday`.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub struct Repeat {
+    /// all of them when empty
+    days: Vec<Days>,
+}
+
+impl Repeat {
+    /// A rule such as `daily`, `every weekday`, `every mon and thu` or
+    /// `weekly on monday`.
+    pub fn parse(rule: &str) -> Option<Repeat> {
+        let mut days = Vec::new();
+        let mut daily = false;
+        for word in rule
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|word| !word.is_empty())
+        {
+            let word = word.to_lowercase();
+            let day = match word.trim_end_matches('s') {
+                "every" | "each" | "on" | "and" | "weekly" => continue,
+                "day" | "daily" => {
+                    daily = true;
+                    continue;
+                }
+                "weekday" => Days::Weekdays,
+                "weekend" => Days::Weekends,
+                "mon" | "monday" => Days::Mon,
+                "tue" | "tuesday" => Days::Tue,
+                "wed" | "wednesday" => Days::Wed,
+                "thu" | "thursday" => Days::Thu,
+                "fri" | "friday" => Days::Fri,
+                "sat" | "saturday" => Days::Sat,
+                "sun" | "sunday" => Days::Sun,
+                _ => return None,
+            };
+            if !days.contains(&day) {
+                days.push(day);
+            }
+        }
+        match (daily, days.is_empty()) {
+            (true, _) => Some(Repeat { days: Vec::new() }),
+            (false, false) => Some(Repeat { days }),
+            (false, true) => None,
+        }
+    }
+
+    pub fn on(&self, day: NaiveDate) -> bool {
+        Days::any(&self.days, day.weekday())
+    }
+}
+
+impl fmt::Display for Repeat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.days.is_empty() {
+            return f.write_str("every day");
+        }
+        let days: Vec<&str> = self
+            .days
+            .iter()
+            .map(|days| match days {
+                Days::Weekdays => "weekday",
+                Days::Weekends => "weekend",
+                Days::Mon => "monday",
+                Days::Tue => "tuesday",
+                Days::Wed => "wednesday",
+                Days::Thu => "thursday",
+                Days::Fri => "friday",
+                Days::Sat => "saturday",
+                Days::Sun => "sunday",
+            })
+            .collect();
+        write!(f, "every {}", days.join(" and "))
+    }
+}
+
+/// `today`, `tomorrow`, the next `friday` (today on a Friday), or
+/// `2026-10-20`.
+fn due_on(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let text = text.trim().to_lowercase();
+    match text.as_str() {
+        "today" => return Some(today),
+        "tomorrow" => return Some(today + Duration::days(1)),
+        _ => {}
+    }
+    if let Ok(day) = text.parse::<Weekday>() {
+        let ahead = (7 + day.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+        return Some(today + Duration::days(ahead as i64));
+    }
+    NaiveDate::parse_from_str(&text, "%Y-%m-%d").ok()
+}
+
+/// What can follow a tag typed on the planning screen.
+enum Change {
+    Repeat(Option<Repeat>),
+    Due(Option<NaiveDate>),
+}
+
+impl TryFrom<String> for Repeat {
+    type Error = String;
+
+    fn try_from(rule: String) -> Result<Self, Self::Error> {
+        Repeat::parse(&rule).ok_or_else(|| format!("not a rule to repeat by: {}", rule))
+    }
+}
+
+impl From<Repeat> for String {
+    fn from(repeat: Repeat) -> String {
+        repeat.to_string()
+    }
+}
+
+fn path() -> Option<PathBuf> {
+    platform::project_dirs().map(|dirs| dirs.data_dir().join("tasks.json"))
+}
+
+/// The task list, or an empty one the first time and whenever the file
+/// can't be read.
+pub fn load() -> Tasks {
+    let Some(path) = path() else {
+        return Tasks::default();
+    };
+    match fs::read_to_string(&path) {
+        Ok(text) => serde_json::from_str(&text).unwrap_or_else(|err| {
+            log::warn!("ignoring {}: {}", path.display(), err);
+            Tasks::default()
+        }),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Tasks::default(),
+        Err(err) => {
+            log::warn!("could not read {}: {}", path.display(), err);
+            Tasks::default()
+        }
+    }
+}
+
+pub fn save(tasks: &Tasks) -> io::Result<()> {
+    let Some(path) = path() else {
+        return Ok(());
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_vec_pretty(tasks)?)?;
+    fs::rename(tmp, path)
+}
+
+/// Each tag's work sessions that ran out on one day, kept up as more end.
+pub struct Done {
+    pub day: NaiveDate,
+    counts: HashMap<String, usize>,
+}
+
+impl Done {
+    pub fn new(tallies: &[Tally], day: NaiveDate) -> Self {
+        Done {
+            day,
+            counts: tallies
+                .iter()
+                .map(|tally| (tally.tag.clone(), tally.today))
+                .collect(),
+        }
+    }
+
+    pub fn of(&self, tag: &str) -> usize {
+        self.counts.get(tag).copied().unwrap_or_default()
+    }
+
+    /// Count in a session that just ended on `day`, and say when it goes
+    /// against the plan: past what was planned for its tag, on a tag that
+    /// isn't in it, or one of its sessions given up on.
+    pub fn add(&mut self, session: &Session, day: NaiveDate, tasks: &Tasks) {
+        if session.phase != Phase::Work {
+            return;
+        }
+        if day != self.day {
+            self.day = day;
+            self.counts.clear();
+        }
+        let planned: u32 = tasks.planned(day).map(|task| task.planned).sum();
+        for tag in &session.tags {
+            let task = tasks.get(tag, day);
+            if !session.completed {
+                if task.is_some() {
+                    log::warn!("a planned session of #{} was given up on", tag);
+                }
+                continue;
+            }
+            let done = self.counts.entry(tag.clone()).or_default();
+            *done += 1;
+            match task {
+                Some(task) if *done > task.planned as usize => {
+                    log::warn!(
+                        "#{} is past its plan, {} of {} sessions",
+                        tag,
+                        done,
+                        task.planned
+                    )
+                }
+                Some(task) if *done == task.planned as usize => {
+                    log::info!("#{} got the {} sessions planned for it", tag, task.planned)
+                }
+                Some(_) => {}
+                None if planned > 0 => log::warn!("#{} isn't in today's plan", tag),
+                None => {}
+            }
+        }
+    }
+}
+
+struct Row {
+    task: Task,
+    /// on the task list, rather than a recent tag offered to plan for
+    kept: bool,
+}
+
+/// The planning screen: today's sessions handed out to tasks, before the
+/// day starts.
+pub struct Planner {
+    rows: Vec<Row>,
+    /// of the rows shown
+    selected: usize,
+    /// a row is held by the mouse, and goes where it's dragged
+    pub dragging: bool,
+    /// a tag being typed, to plan for too
+    pub adding: String,
+    /// the tasks archived are shown instead of the ones still to do
+    pub archived: bool,
+    day: NaiveDate,
+    recurred: Option<NaiveDate>,
+}
+
+impl Planner {
+    /// The task list with what it has planned for `day`, then the tags
+    /// worked on last that aren't on it.
+    pub fn new(tasks: &Tasks, recent: &[Tally], day: NaiveDate) -> Self {
+        let mut rows: Vec<Row> = tasks
+            .tasks
+            .iter()
+            .map(|task| Row {
+                task: Task {
+                    planned: if tasks.day == Some(day) && !task.archived {
+                        task.planned
+                    } else {
+                        0
+                    },
+                    ..task.clone()
+                },
+                kept: true,
+            })
+            .collect();
+        // what is overdue comes first, in the order it was in
+        rows.sort_by_key(|row| !row.task.overdue(day));
+        for tally in recent {
+            if !rows.iter().any(|row| row.task.tag == tally.tag) {
+                rows.push(Row {
+                    task: Task {
+                        tag: tally.tag.clone(),
+                        planned: 0,
+                        archived: false,
+                        repeat: None,
+                        due: None,
+                    },
+                    kept: false,
+                });
+            }
+        }
+        Planner {
+            rows,
+            selected: 0,
+            dragging: false,
+            adding: String::new(),
+            archived: false,
+            day,
+            recurred: tasks.recurred,
+        }
+    }
+
+    /// Where the rows shown are among all of them.
+    fn shown(&self) -> Vec<usize> {
+        (0..self.rows.len())
+            .filter(|&i| self.rows[i].task.archived == self.archived)
+            .collect()
+    }
+
+    fn current(&mut self) -> Option<&mut Row> {
+        let i = *self.shown().get(self.selected)?;
+        self.rows.get_mut(i)
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn select(&mut self, by: isize) {
+        let last = self.shown().len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(by).min(last);
+    }
+
+    /// One session more for the selected task, or fewer.
+    pub fn change(&mut self, by: i32) {
+        if let Some(row) = self.current().filter(|row| !row.task.archived) {
+            row.task.planned = row.task.planned.saturating_add_signed(by);
+        }
+    }
+
+    /// The selected task moved up the list or down it, staying selected;
+    /// a recent tag moved goes on the task list. False at either end.
+    pub fn shift(&mut self, by: isize) -> bool {
+        let shown = self.shown();
+        let Some(to) = self
+            .selected
+            .checked_add_signed(by)
+            .filter(|&to| to < shown.len())
+        else {
+            return false;
+        };
+        // rows not shown stay where they are
+        let (from, to_row) = (shown[self.selected], shown[to]);
+        self.rows[from].kept = true;
+        self.rows.swap(from, to_row);
+        self.selected = to;
+        true
+    }
+
+    /// The selected task archived, or back on the list when archived
+    /// already; false with nothing to archive.
+    pub fn archive(&mut self) -> bool {
+        let Some(row) = self.current() else {
+            return false;
+        };
+        row.kept = true;
+        row.task.archived = !row.task.archived;
+        row.task.planned = 0;
+        self.select(0);
+        true
+    }
+
+    /// From the tasks still to do to the archived ones, and back.
+    pub fn toggle(&mut self) {
+        self.archived = !self.archived;
+        self.selected = 0;
+        self.adding.clear();
+    }
+
+    /// The row drawn at `y` by [`draw`] in `area`, if there is one.
+    pub fn row_at(&self, area: Rect, x: u16, y: u16) -> Option<usize> {
+        let inner = Block::default().title("").inner(area);
+        if !inner.intersects(Rect::new(x, y, 1, 1)) {
+            return None;
+        }
+        let rows = (inner.height as usize).saturating_sub(1);
+        let offset = (self.selected + 1).saturating_sub(rows);
+        let i = offset + (y - inner.y) as usize;
+        (i < offset + rows && i < self.shown().len()).then_some(i)
+    }
+
+    /// Select the row at `i`, as a click does.
+    pub fn select_at(&mut self, i: usize) {
+        self.selected = i.min(self.shown().len().saturating_sub(1));
+    }
+
+    /// The task list in the order shown, with what `tasks` plans for them:
+    /// the order and what is archived are kept as soon as they change,
+    /// planned or not.
+    pub fn order(&self, tasks: &Tasks) -> Tasks {
+        let planned = |tag: &str| {
+            tasks
+                .tasks
+                .iter()
+                .find(|task| task.tag == tag)
+                .map_or(0, |task| task.planned)
+        };
+        Tasks {
+            day: tasks.day,
+            recurred: tasks.recurred,
+            tasks: self
+                .rows
+                .iter()
+                .filter(|row| row.kept)
+                .map(|row| Task {
+                    planned: if row.task.archived {
+                        0
+                    } else {
+                        planned(&row.task.tag)
+                    },
+                    ..row.task.clone()
+                })
+                .collect(),
+        }
+    }
+
+    /// The tag typed, as a task with a session planned, selected. One
+    /// that was archived is back on the list.
+    ///
+    /// A rule after the tag, `#standup every weekday`, makes it a routine
+    /// task, and `never` makes it a one-off again. `due friday` gives it
+    /// a due date, and `due never` takes it away. True when either
+    /// changed the task list.
+    pub fn add(&mut self) -> bool {
+        let typed = std::mem::take(&mut self.adding);
+        let (tag, rule) = typed.trim().split_once(' ').unwrap_or((typed.trim(), ""));
+        let tag = tag.trim_start_matches('#');
+        if tag.is_empty() {
+            return false;
+        }
+        let change = match rule.trim() {
+            "" => None,
+            "never" => Some(Change::Repeat(None)),
+            "due never" => Some(Change::Due(None)),
+            rule => match rule.strip_prefix("due ") {
+                Some(day) => match due_on(day, self.day) {
+                    Some(day) => Some(Change::Due(Some(day))),
+                    None => {
+                        log::warn!("not a day to be due on: {}", day);
+                        return false;
+                    }
+                },
+                None => match Repeat::parse(rule) {
+                    Some(repeat) => Some(Change::Repeat(Some(repeat))),
+                    None => {
+                        log::warn!("not a rule to repeat by: {}", rule);
+                        return false;
+                    }
+                },
+            },
+        };
+        let i = match self.rows.iter().position(|row| row.task.tag == tag) {
+            Some(i) => {
+                self.rows[i].task.archived = false;
+                i
+            }
+            None => {
+                self.rows.push(Row {
+                    task: Task {
+                        tag: tag.to_string(),
+                        planned: 1,
+                        archived: false,
+                        repeat: None,
+                        due: None,
+                    },
+                    kept: false,
+                });
+                self.rows.len() - 1
+            }
+        };
+        let row = &mut self.rows[i];
+        match &change {
+            Some(Change::Repeat(Some(repeat))) => log::info!("#{} repeats {}", tag, repeat),
+            Some(Change::Repeat(None)) => log::info!("#{} no longer repeats", tag),
+            Some(Change::Due(Some(due))) => log::info!("#{} is due {}", tag, due.format("%a %m-%d")),
+            Some(Change::Due(None)) => log::info!("#{} is no longer due", tag),
+            None => {}
+        }
+        match &change {
+            Some(Change::Repeat(repeat)) => row.task.repeat = repeat.clone(),
+            Some(Change::Due(due)) => row.task.due = *due,
+            None => {}
+        }
+        if change.is_some() {
+            row.kept = true;
+        }
+        self.archived = false;
+        let shown = self.shown();
+        self.selected = shown
+            .iter()
+            .position(|&i| self.rows[i].task.tag == tag)
+            .unwrap_or_default();
+        change.is_some()
+    }
+
+    /// The task list as planned: everything that was on it, and whatever
+    /// else got sessions.
+    pub fn plan(&self) -> Tasks {
+        Tasks {
+            day: Some(self.day),
+            recurred: self.recurred,
+            tasks: self
+                .rows
+                .iter()
+                .filter(|row| row.kept || row.task.planned > 0)
+                .map(|row| row.task.clone())
+                .collect(),
+        }
+    }
+}
+
+pub fn draw<B: Backend>(f: &mut Frame<B>, planner: &Planner, done: &Done, area: Rect) {
+    let shown: Vec<&Task> = planner
+        .shown()
+        .into_iter()
+        .map(|i| &planner.rows[i].task)
+        .collect();
+    let title = if planner.archived {
+        format!(
+            "Archived: {} task{}",
+            shown.len(),
+            if shown.len() == 1 { "" } else { "s" }
+        )
+    } else {
+        let total: u32 = shown.iter().map(|task| task.planned).sum();
+        format!(
+            "Plan for {}: {} session{}",
+            planner.day.format("%a %m-%d"),
+            total,
+            if total == 1 { "" } else { "s" }
+        )
+    };
+    let block = Block::default().title(title);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let rows = (inner.height as usize).saturating_sub(1);
+    // keep the selection in view
+    let offset = (planner.selected + 1).saturating_sub(rows);
+    let width = inner.width as usize;
+    let mut lines: Vec<Spans> = shown
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .take(rows)
+        .map(|(i, row)| {
+            let mut name = format!("#{}", row.tag);
+            if let Some(due) = row.due {
+                name.push_str(&format!("  due {}", due.format("%a %m-%d")));
+            }
+            if let Some(repeat) = &row.repeat {
+                name.push_str(&format!("  {}", repeat));
+            }
+            let count = if planner.archived {
+                format!("{} done today", done.of(&row.tag))
+            } else {
+                format!("{} done  < {:>2} >", done.of(&row.tag), row.planned)
+            };
+            let gap = width.saturating_sub(name.chars().count() + count.chars().count());
+            let line = format!("{}{:gap$}{}", name, "", count, gap = gap);
+            let style = match (i == planner.selected, row.planned) {
+                (true, _) => Style::default().add_modifier(Modifier::REVERSED),
+                (false, _) if row.overdue(planner.day) => Style::default().fg(Color::Red),
+                (false, _) if row.due == Some(planner.day) => Style::default().fg(Color::Yellow),
+                (false, 0) => Style::default().fg(Color::DarkGray),
+                (false, _) => Style::default(),
+            };
+            Spans::from(Span::styled(line, style))
+        })
+        .collect();
+    if !planner.archived {
+        lines.push(Spans::from(vec![
+            Span::styled("+ ", Style::default().fg(Color::Yellow)),
+            Span::raw(format!("#{}_", planner.adding)),
+        ]));
+    }
+    f.render_widget(Paragraph::new(lines), inner);
+}
@@ -0,0 +1,89 @@
+//! Tiny `{placeholder}` formatter shared by notifications and status output.
+//!
+//! `{{` and `}}` produce literal braces; unknown placeholders are left in
+//! place so a typo shows up in the output instead of vanishing.
+
+use serde::{Deserialize, Serialize};
+
+/// Values available to templates.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Context {
+    /// `idle`, `running`, `paused`, or `stopped` when pom isn't running
+    pub state: String,
+    /// `work`, `short break` or `long break`; `idle` before starting
+    pub phase: String,
+    /// seconds left on the timer
+    pub remaining: i64,
+    /// work sessions completed this run
+    pub round: u32,
+    /// task being focused on, empty when none
+    pub task: String,
+    /// sessions completed today
+    pub count_today: usize,
+}
+
+impl Context {
+    fn get(&self, name: &str) -> Option<String> {
+        Some(match name {
+            "state" => self.state.clone(),
+            "phase" => self.phase.clone(),
+            "remaining" => format_clock(self.remaining),
+            "remaining_secs" => self.remaining.to_string(),
+            "round" => self.round.to_string(),
+            "task" => self.task.clone(),
+            "count_today" => self.count_today.to_string(),
+            _ => return None,
+        })
+    }
+}
+
+pub fn render(template: &str, context: &Context) -> String {
+    render_with(template, |name| context.get(name))
+}
+
+/// The same, with values from `get` instead of the timer's.
+pub fn render_with(template: &str, get: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(i) = rest.find(['{', '}']) {
+        out.push_str(&rest[..i]);
+        let tail = &rest[i..];
+
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            out.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+
+        let placeholder = tail
+            .strip_prefix('{')
+            .and_then(|t| t.find('}').map(|end| &t[..end]))
+            .filter(|name| !name.contains('{'));
+
+        match placeholder.and_then(|name| get(name.trim()).map(|v| (name, v))) {
+            Some((name, value)) => {
+                out.push_str(&value);
+                rest = &tail[name.len() + 2..];
+            }
+            None => {
+                out.push_str(&tail[..1]);
+                rest = &tail[1..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// `mm:ss`, or `h:mm:ss` once an hour or more is left.
+pub fn format_clock(secs: i64) -> String {
+    let secs = secs.max(0);
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        format!("{}:{:02}:{:02}", h, m, s)
+    } else {
+        format!("{:02}:{:02}", m, s)
+    }
+}
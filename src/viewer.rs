@@ -0,0 +1,168 @@
+//! The pom that is already running, seen from another one: attached over
+//! its socket, with the keys driving its timer, or read-only from its
+//! state file where there is no socket.
+
+use crossterm::event::{poll, read, Event, KeyCode, KeyEventKind, KeyModifiers};
+use pom_core::Font;
+use std::time::{Duration, Instant};
+use tui::{
+    backend::Backend,
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+    Frame, Terminal,
+};
+
+#[cfg(unix)]
+pub use crate::attach::Link;
+use crate::{
+    app::Command,
+    error::Result,
+    keys::{Action, Keymap},
+    signals::Signals,
+    state,
+    template::Context,
+};
+
+/// Nothing to attach to without Unix sockets.
+#[cfg(not(unix))]
+pub struct Link;
+
+#[cfg(not(unix))]
+impl Link {
+    fn send(&mut self, _command: Command) {}
+
+    fn context(&self) -> Option<Context> {
+        None
+    }
+}
+
+pub fn run<B: Backend>(
+    terminal: &mut Terminal<B>,
+    signals: &Signals,
+    pid: Option<u32>,
+    mut link: Option<Link>,
+    mut keys: Keymap,
+) -> Result<()> {
+    let font = Font::standard();
+    loop {
+        if signals.should_terminate() {
+            return Ok(());
+        }
+        let context = match &link {
+            Some(link) => link.context().unwrap_or_else(state::stopped),
+            None => state::read()?,
+        };
+        let attached = link.is_some();
+        terminal.draw(|f| draw(f, &context, &font, &keys, pid, attached))?;
+
+        let mut action = keys.expire(Instant::now());
+        if poll(Duration::from_millis(250))? {
+            if let Event::Key(key) = read()? {
+                let control = key.modifiers.contains(KeyModifiers::CONTROL);
+                match key.code {
+                    _ if key.kind == KeyEventKind::Release => {}
+                    KeyCode::Char('c') if control => return Ok(()),
+                    KeyCode::Char('q') | KeyCode::Esc if !attached => return Ok(()),
+                    _ => action = keys.feed(key.into(), Instant::now()),
+                }
+            }
+        }
+        let idle = context.state == "idle";
+        let command = match action {
+            Some(Action::Quit) => return Ok(()),
+            Some(Action::Start) if idle => Some(Command::Start),
+            Some(Action::Reset) if !idle => Some(Command::Reset),
+            Some(Action::Toggle) if !idle => Some(Command::Toggle),
+            Some(Action::Skip) if !idle => Some(Command::Skip),
+            Some(Action::Extend) if !idle => Some(Command::Add(5 * 60)),
+            Some(Action::Trim) if !idle => Some(Command::Add(-5 * 60)),
+            _ => None,
+        };
+        if let (Some(command), Some(link)) = (command, &mut link) {
+            link.send(command);
+        }
+    }
+}
+
+fn draw<B: Backend>(
+    f: &mut Frame<B>,
+    context: &Context,
+    font: &Font,
+    keys: &Keymap,
+    pid: Option<u32>,
+    attached: bool,
+) {
+    let title = match (pid, attached) {
+        (Some(pid), true) => format!("pom {}, attached", pid),
+        (None, true) => "pom, attached".to_string(),
+        (Some(pid), false) => format!("pom {}, read-only", pid),
+        (None, false) => "pom, read-only".to_string(),
+    };
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let area = f.size();
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let mut lines = Vec::new();
+    if context.state == "stopped" {
+        lines.push(Spans::from("the other pom has quit"));
+    } else {
+        let time = format!(
+            "{:02}:{:02}",
+            context.remaining / 60,
+            context.remaining % 60
+        );
+        let mut heading = context.phase.clone();
+        if context.state == "paused" {
+            heading.push_str(", paused");
+        }
+        lines.push(Spans::from(Span::styled(
+            heading,
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+        lines.push(Spans::from(""));
+        match font.render(&time).filter(|rows| {
+            rows.iter()
+                .all(|row| row.chars().count() <= inner.width as usize)
+        }) {
+            Some(rows) => lines.extend(rows.into_iter().map(Spans::from)),
+            None => lines.push(Spans::from(time)),
+        }
+        lines.push(Spans::from(""));
+        let mut about = format!("round {}, {} today", context.round, context.count_today);
+        if !context.task.is_empty() {
+            about = format!("{}, {}", context.task, about);
+        }
+        lines.push(Spans::from(Span::styled(about, dim)));
+    }
+    lines.push(Spans::from(""));
+    // read-only, q and esc are wired in
+    let hint = match context.state.as_str() {
+        _ if !attached => "[ q ] quit".to_string(),
+        "stopped" => keys.hints(&[(&[Action::Quit], "to quit")]),
+        "idle" => keys.hints(&[
+            (&[Action::Start], "to start"),
+            (&[Action::Quit], "to detach"),
+        ]),
+        _ => keys.hints(&[
+            (&[Action::Reset], "reset"),
+            (&[Action::Toggle], "pause/resume"),
+            (&[Action::Skip], "skip"),
+            (&[Action::Extend, Action::Trim], "±5 min"),
+            (&[Action::Quit], "to detach"),
+        ]),
+    };
+    lines.push(Spans::from(Span::styled(hint, dim)));
+
+    // in the middle, top to bottom
+    let height = (lines.len() as u16).min(inner.height);
+    let area = Rect {
+        y: inner.y + (inner.height - height) / 2,
+        height,
+        ..inner
+    };
+    f.render_widget(Paragraph::new(lines).alignment(Alignment::Center), area);
+}
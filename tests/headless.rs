@@ -0,0 +1,249 @@
+use chrono::Datelike;
+use pom::headless::{Headless, Scratch, Screen};
+use pom_core::Command;
+
+fn run(headless: Headless) -> Screen {
+    headless.run().expect("the event loop failed")
+}
+
+#[test]
+fn picks_a_duration() {
+    let screen = run(Headless::new(60, 16).keys("2"));
+    assert!(screen.contains("[ enter ]"), "{}", screen);
+    assert!(screen.contains("1500"), "{}", screen);
+}
+
+#[test]
+fn starts_work() {
+    let screen = run(Headless::new(60, 16).keys("2 enter"));
+    assert!(screen.contains("┌ Work ─"), "{}", screen);
+    assert!(screen.contains("[ p ] pause/resume"), "{}", screen);
+}
+
+#[test]
+fn pauses_and_resumes() {
+    let screen = run(Headless::new(60, 16).keys("2 enter p"));
+    assert!(screen.contains("Work (paused)"), "{}", screen);
+
+    let screen = run(Headless::new(60, 16).keys("2 enter p p"));
+    assert!(screen.contains("┌ Work ─"), "{}", screen);
+    assert!(!screen.contains("(paused)"), "{}", screen);
+}
+
+#[test]
+fn moves_on_to_a_break() {
+    // running out is the same as shortening the phase to nothing
+    let screen = run(Headless::new(60, 16)
+        .keys("2 enter")
+        .command(Command::Add(-1500)));
+    // set up, waiting for a start
+    assert!(screen.contains("┌ Short break (paused) ─"), "{}", screen);
+    assert!(screen.contains("300"), "{}", screen);
+    // the round's first session is done
+    assert!(screen.contains("*---"), "{}", screen);
+
+    let screen = run(Headless::new(60, 16)
+        .keys("2 enter")
+        .command(Command::Add(-1500))
+        .command(Command::Start));
+    assert!(screen.contains("┌ Short break ─"), "{}", screen);
+}
+
+#[test]
+fn advances_on_its_own() {
+    let headless = Headless::new(60, 16)
+        .config("auto_advance = true")
+        .expect("the config didn't parse")
+        .keys("2 enter")
+        .command(Command::Add(-1500));
+    let screen = run(headless);
+    assert!(screen.contains("┌ Short break ─"), "{}", screen);
+}
+
+#[test]
+fn skipping_does_not_count() {
+    let screen = run(Headless::new(60, 16).keys("2 enter s"));
+    assert!(screen.contains("┌ Short break ─"), "{}", screen);
+    assert!(screen.contains("----"), "{}", screen);
+}
+
+#[test]
+fn keeps_to_the_config() {
+    let headless = Headless::new(60, 16)
+        .config("[schedule]\nshort_break = 10")
+        .expect("the config didn't parse")
+        .keys("2 enter")
+        .command(Command::Add(-1500));
+    let screen = run(headless);
+    assert!(screen.contains("600"), "{}", screen);
+}
+
+#[test]
+fn palette_lists_actions() {
+    let screen = run(Headless::new(60, 16).keys(": s k"));
+    assert!(screen.contains("Command:"), "{}", screen);
+    assert!(screen.contains("skip"), "{}", screen);
+}
+
+#[test]
+fn palette_sets_a_duration() {
+    let screen = run(Headless::new(60, 16).keys(": d u r space 4 5 enter"));
+    assert!(screen.contains("2700"), "{}", screen);
+}
+
+#[test]
+fn palette_runs_an_action() {
+    let screen = run(Headless::new(60, 16).keys("2 enter : s k i p enter"));
+    assert!(screen.contains("┌ Short break ─"), "{}", screen);
+}
+
+#[test]
+fn instructions_follow_the_keymap() {
+    let headless = Headless::new(120, 16)
+        .config("[keys]\ntoggle = \"x\"\nextend = \"ctrl-e\"\ntrim = \"ctrl-t\"")
+        .expect("the config didn't parse")
+        .keys("2 enter");
+    let screen = run(headless);
+    assert!(screen.contains("[ x ] pause/resume"), "{}", screen);
+    assert!(screen.contains("[ ctrl-e/ctrl-t ] ±5 min"), "{}", screen);
+}
+
+#[test]
+fn palette_offers_profiles() {
+    let screen = run(Headless::new(60, 16).keys(": p r o f"));
+    assert!(screen.contains("profile <name>"), "{}", screen);
+    assert!(screen.contains("P"), "{}", screen);
+}
+
+#[test]
+fn completes_a_tag_from_the_history() {
+    let scratch = Scratch::new().expect("no scratch directory");
+    run(Headless::new(60, 16)
+        .scratch(&scratch)
+        .keys("t c o m p l e t e - m e enter 2 enter")
+        .command(Command::Add(-1500)));
+    let screen = run(Headless::new(60, 16).scratch(&scratch).keys("t c o m p tab"));
+    assert!(screen.contains("> complete-me_"), "{}", screen);
+}
+
+#[test]
+fn resumes_a_recent_tag() {
+    let scratch = Scratch::new().expect("no scratch directory");
+    run(Headless::new(80, 16)
+        .scratch(&scratch)
+        .keys("t r e s u m e - m e enter 2 enter")
+        .command(Command::Add(-1500)));
+    let screen = run(Headless::new(80, 16).scratch(&scratch).keys("r"));
+    assert!(screen.contains("Resume: the"), "{}", screen);
+    assert!(screen.contains("#resume-me  "), "{}", screen);
+    assert!(screen.contains("1 today, 1 in all"), "{}", screen);
+}
+
+#[test]
+fn skips_an_invalid_session() {
+    let scratch = Scratch::new().expect("no scratch directory");
+    run(Headless::new(80, 16)
+        .scratch(&scratch)
+        .keys("t r e s u m e - m e enter 2 enter")
+        .command(Command::Add(-1500)));
+    // a clock that jumped back while it ran
+    let skewed = r#"{"v":2,"phase":"work","started":"2020-01-02T00:00:00Z","ended":"2020-01-01T00:00:00Z","planned":1500,"elapsed":1500,"completed":true,"tags":["resume-me"]}"#;
+    let history = scratch.path().join("history.jsonl");
+    let mut text = std::fs::read_to_string(&history).expect("no history");
+    text.push_str(skewed);
+    text.push('\n');
+    std::fs::write(&history, text).expect("couldn't write the history");
+
+    let screen = run(Headless::new(80, 16).scratch(&scratch).keys("r"));
+    assert!(screen.contains("#resume-me  "), "{}", screen);
+    assert!(screen.contains("1 today, 1 in all"), "{}", screen);
+}
+
+#[test]
+fn counts_sessions_beside_tags() {
+    // the picker stays open as the session ends
+    let screen = run(Headless::new(80, 16)
+        .keys("t c o u n t - m e enter 2 enter t c o u n t - m e")
+        .command(Command::Add(-1500)));
+    assert!(screen.contains("Focus on:"), "{}", screen);
+    assert!(screen.contains("1 today, 1 in all"), "{}", screen);
+}
+
+#[test]
+fn shows_the_daily_goal() {
+    let headless = || {
+        Headless::new(80, 16)
+            .config("[goal]\ndaily = 1")
+            .expect("the config didn't parse")
+            .keys("2 enter")
+    };
+    let screen = run(headless());
+    assert!(screen.contains("goal 0/1"), "{}", screen);
+
+    let screen = run(headless().command(Command::Add(-1500)));
+    assert!(screen.contains("goal 1/1, 1-day streak"), "{}", screen);
+}
+
+#[test]
+fn plans_the_day() {
+    let screen = run(Headless::new(80, 16).keys("a p l a n - m e enter right"));
+    assert!(screen.contains("Plan for"), "{}", screen);
+    assert!(screen.contains("#plan-me"), "{}", screen);
+    assert!(screen.contains("0 done  <  2 >"), "{}", screen);
+
+    let screen = run(Headless::new(120, 16).keys("a p l a n - m e enter right enter"));
+    assert!(screen.contains("#plan-me 0/2"), "{}", screen);
+}
+
+#[test]
+fn moves_tasks_up_the_plan() {
+    let screen = run(Headless::new(100, 16).keys("a o n e enter t w o enter K"));
+    let lines = screen.lines();
+    let at = |tag: &str| lines.iter().position(|line| line.contains(tag));
+    assert!(at("#two ").is_some_and(|two| at("#one ").is_some_and(|one| two < one)), "{}", screen);
+}
+
+#[test]
+fn archives_tasks() {
+    let screen = run(Headless::new(100, 16).keys("a s h e l v e d enter X"));
+    assert!(!screen.contains("#shelved"), "{}", screen);
+
+    let screen = run(Headless::new(100, 16).keys("a s h e l v e d enter X tab"));
+    assert!(screen.contains("Archived:"), "{}", screen);
+    assert!(screen.contains("#shelved"), "{}", screen);
+}
+
+#[test]
+fn repeats_routine_tasks() {
+    let screen = run(Headless::new(100, 16).keys("a s t a n d u p space w e e k l y space o n space m o n d a y enter"));
+    assert!(screen.contains("#standup  every monday"), "{}", screen);
+
+    let screen = run(Headless::new(100, 16).keys("a c h o r e space o f t e n enter"));
+    assert!(!screen.contains("#chore"), "{}", screen);
+}
+
+#[test]
+fn repeats_on_a_day_pom_wasnt_opened() {
+    let scratch = Scratch::new().expect("no scratch directory");
+    let missed = chrono::Local::now().date_naive() - chrono::Duration::days(2);
+    let tasks = format!(
+        r#"{{"recurred":"2020-01-01","tasks":[{{"tag":"standup","archived":true,"repeat":"every {}"}}]}}"#,
+        missed.weekday()
+    );
+    std::fs::write(scratch.path().join("tasks.json"), tasks).expect("couldn't write the tasks");
+    let screen = run(Headless::new(100, 16).scratch(&scratch).keys("a"));
+    assert!(screen.contains("#standup"), "{}", screen);
+}
+
+#[test]
+fn puts_overdue_tasks_first() {
+    let screen = run(Headless::new(100, 16).keys("a l a t e r enter o v e r d u e space d u e space 2 0 2 0 - 0 1 - 0 1 enter"));
+    assert!(screen.contains("#overdue  due Wed 01-01"), "{}", screen);
+
+    // sorted as the planning screen opens
+    let screen = run(Headless::new(100, 16).keys("a l a t e r space d u e space 2 0 9 9 - 0 1 - 0 1 enter o v e r d u e space d u e space 2 0 2 0 - 0 1 - 0 1 enter esc a"));
+    let lines = screen.lines();
+    let at = |tag: &str| lines.iter().position(|line| line.contains(tag));
+    let first = lines.iter().position(|line| line.trim_start().starts_with('#'));
+    assert_eq!(at("#overdue "), first, "{}", screen);
+}